@@ -0,0 +1,153 @@
+/*
+ * xmem shared-memory layout
+ *
+ * Describes the metadata region of a pool so that non-Rust readers (eBPF
+ * probes, legacy C code, debuggers) can map it directly. Must match
+ * crates/xmem-core/src/meta.rs and meta_region.rs; the offsets below are
+ * checked at compile time here and on the Rust side.
+ *
+ * A pool named "/name" consists of:
+ *
+ *   /name_meta     xmem_region_header_t followed by `capacity` xmem_buffer_meta_t
+ *   /name_buf_<i>  data of CPU buffer <i>
+ *
+ * Fields the Rust side declares as atomics must be accessed with atomic
+ * operations (e.g. __atomic_load_n) while other processes use the pool.
+ *
+ * Define XMEM_LAYOUT_NO_STDINC to skip the standard includes (e.g. when
+ * building against vmlinux.h) and provide uint8_t..uint64_t yourself.
+ */
+
+#ifndef XMEM_LAYOUT_H
+#define XMEM_LAYOUT_H
+
+#ifndef XMEM_LAYOUT_NO_STDINC
+#include <stddef.h>
+#include <stdint.h>
+#endif
+
+#ifndef offsetof
+#define offsetof(type, field) __builtin_offsetof(type, field)
+#endif
+
+#ifdef __cplusplus
+#define XMEM_STATIC_ASSERT(cond, msg) static_assert(cond, msg)
+#else
+#define XMEM_STATIC_ASSERT(cond, msg) _Static_assert(cond, msg)
+#endif
+
+#define XMEM_CHECK_SIZE(type, size) \
+    XMEM_STATIC_ASSERT(sizeof(type) == (size), "size of " #type)
+#define XMEM_CHECK_OFFSET(type, field, offset) \
+    XMEM_STATIC_ASSERT(offsetof(type, field) == (offset), "offset of " #type "." #field)
+
+#define XMEM_MAGIC 0x584D454Du /* "XMEM" */
+#define XMEM_LAYOUT_VERSION 5
+
+#define XMEM_MAX_NDIM 8
+#define XMEM_LABEL_SIZE 32
+#define XMEM_CUDA_IPC_HANDLE_SIZE 64
+#define XMEM_LOCK_READER_WORDS 3
+#define XMEM_MAX_PROCESSES 192 /* XMEM_LOCK_READER_WORDS * 64 */
+
+/* storage_type values */
+#define XMEM_STORAGE_CPU 0
+#define XMEM_STORAGE_CUDA 1
+#define XMEM_STORAGE_VIEW 2
+#define XMEM_STORAGE_HEAP 3 /* process-local, never seen by other processes */
+#define XMEM_STORAGE_FILE 4 /* mmap'd file {dir}/{name}_buf_{index} */
+
+/* flags bits */
+#define XMEM_FLAG_SEQLOCK (1u << 0)
+#define XMEM_FLAG_PUBLISHED (1u << 1)
+
+/* cache bits (low 32 bits; the high 32 bits hold the claiming process slot + 1) */
+#define XMEM_CACHE_PRESENT (1u << 0)
+#define XMEM_CACHE_TRIMMED (1u << 1)
+
+/* Sentinel for next_free / parent_index / free_head */
+#define XMEM_INDEX_NONE 0xFFFFFFFFu
+
+/* numa_node of a buffer not bound to a NUMA node */
+#define XMEM_NUMA_NODE_NONE 0xFFu
+
+/* Start of the metadata region */
+typedef struct xmem_region_header {
+    uint32_t magic;       /* XMEM_MAGIC */
+    uint32_t version;     /* XMEM_LAYOUT_VERSION */
+    uint32_t capacity;    /* number of xmem_buffer_meta_t slots */
+    uint32_t next_id;     /* atomic: slots handed out so far */
+    uint32_t allocated;   /* atomic: slots currently allocated; futex woken on free */
+    uint32_t free_head;   /* atomic: free list head, XMEM_INDEX_NONE = empty */
+    uint32_t waiters;     /* atomic: processes blocked on allocated/publish_seq */
+    uint32_t publish_seq; /* atomic: next publish sequence number; futex woken on publish */
+    uint32_t processes[XMEM_MAX_PROCESSES]; /* atomic: pid registered in each process slot, 0 = free */
+} xmem_region_header_t;
+
+/* Per-buffer metadata, slot i is at sizeof(xmem_region_header_t) + i * sizeof(xmem_buffer_meta_t) */
+typedef struct xmem_buffer_meta {
+    uint32_t id;                  /* atomic */
+    int32_t ref_count;            /* atomic */
+    uint8_t storage_type;         /* atomic: XMEM_STORAGE_*, custom backends from 128 */
+    uint8_t device_id;            /* atomic: CUDA device */
+    uint8_t dtype;                /* atomic: same values as XmemDtype */
+    uint8_t ndim;                 /* atomic */
+    uint8_t flags;                /* atomic: XMEM_FLAG_* */
+    uint8_t numa_node;            /* atomic: bound NUMA node, XMEM_NUMA_NODE_NONE = unbound */
+    uint8_t _pad0[2];
+    uint64_t shape[XMEM_MAX_NDIM];   /* atomic */
+    uint64_t strides[XMEM_MAX_NDIM]; /* atomic, in bytes */
+    uint64_t size;                /* atomic: buffer size in bytes */
+    uint64_t timestamp;           /* atomic: milliseconds since the Unix epoch (release time if cached) */
+    uint64_t seq;                 /* atomic: sequence number / seqlock counter */
+    char content_type[XMEM_LABEL_SIZE]; /* NUL-padded, not terminated when full */
+    char producer[XMEM_LABEL_SIZE];     /* NUL-padded, not terminated when full */
+    uint8_t cuda_ipc_handle[XMEM_CUDA_IPC_HANDLE_SIZE]; /* CUDA IPC or backend-defined handle */
+    uint32_t next_free;           /* atomic: free list link */
+    uint32_t parent_index;        /* atomic: parent of a view */
+    uint64_t view_offset;         /* atomic: byte offset of a view in its parent */
+    uint64_t lock_state;          /* atomic: writer process slot + 1, 0 = none */
+    uint64_t lock_readers[XMEM_LOCK_READER_WORDS]; /* atomic: bitmap of reading process slots */
+    uint32_t publish_seq;         /* atomic: valid while XMEM_FLAG_PUBLISHED is set */
+    uint32_t generation;          /* atomic: bumped on each allocation */
+    uint64_t cache;               /* atomic: XMEM_CACHE_* of a free slot | (claiming process slot + 1) << 32 */
+} xmem_buffer_meta_t;
+
+XMEM_CHECK_SIZE(xmem_region_header_t, 800);
+XMEM_CHECK_OFFSET(xmem_region_header_t, magic, 0);
+XMEM_CHECK_OFFSET(xmem_region_header_t, version, 4);
+XMEM_CHECK_OFFSET(xmem_region_header_t, capacity, 8);
+XMEM_CHECK_OFFSET(xmem_region_header_t, next_id, 12);
+XMEM_CHECK_OFFSET(xmem_region_header_t, allocated, 16);
+XMEM_CHECK_OFFSET(xmem_region_header_t, free_head, 20);
+XMEM_CHECK_OFFSET(xmem_region_header_t, waiters, 24);
+XMEM_CHECK_OFFSET(xmem_region_header_t, publish_seq, 28);
+XMEM_CHECK_OFFSET(xmem_region_header_t, processes, 32);
+
+XMEM_CHECK_SIZE(xmem_buffer_meta_t, 360);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, id, 0);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, ref_count, 4);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, storage_type, 8);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, device_id, 9);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, dtype, 10);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, ndim, 11);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, flags, 12);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, numa_node, 13);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, shape, 16);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, strides, 80);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, size, 144);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, timestamp, 152);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, seq, 160);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, content_type, 168);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, producer, 200);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, cuda_ipc_handle, 232);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, next_free, 296);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, parent_index, 300);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, view_offset, 304);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, lock_state, 312);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, lock_readers, 320);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, publish_seq, 344);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, generation, 348);
+XMEM_CHECK_OFFSET(xmem_buffer_meta_t, cache, 352);
+
+#endif /* XMEM_LAYOUT_H */
@@ -0,0 +1,251 @@
+//! ndarray 集成（需启用 `ndarray` feature）
+//!
+//! 根据 [`BufferMeta`] 中记录的 dtype、shape 和字节 strides，
+//! 将 [`BufferGuard`] 包装为零拷贝的 [`ArrayViewD`] / [`ArrayViewMutD`]。
+//!
+//! # 示例
+//!
+//! ```
+//! use xmem_core::{BufferPool, DType};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = BufferPool::create("/my_pool_array_mod_doc")?;
+//! let mut buf = pool.acquire_cpu_array(&[2, 3], DType::Float32)?;
+//!
+//! buf.as_array_view_mut::<f32>()?.fill(1.5);
+//! assert_eq!(buf.as_array_view::<f32>()?.sum(), 9.0);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::dtype::DType;
+use crate::guard::BufferGuard;
+use crate::meta::{BufferMeta, TensorLayout};
+use crate::{Error, Result};
+use ndarray::{ArrayViewD, ArrayViewMutD, IxDyn, ShapeBuilder};
+
+/// 可映射为 ndarray 元素的类型
+///
+/// 每个实现对应一个 [`DType`]，用于校验 buffer 元数据中的 dtype。
+pub trait Element: Copy + 'static {
+    /// Corresponding xmem data type
+    const DTYPE: DType;
+}
+
+macro_rules! impl_element {
+    ($($ty:ty => $dtype:ident),* $(,)?) => {
+        $(
+            impl Element for $ty {
+                const DTYPE: DType = DType::$dtype;
+            }
+        )*
+    };
+}
+
+impl_element! {
+    u8 => UInt8,
+    i8 => Int8,
+    u16 => UInt16,
+    i16 => Int16,
+    u32 => UInt32,
+    i32 => Int32,
+    u64 => UInt64,
+    i64 => Int64,
+    f32 => Float32,
+    f64 => Float64,
+}
+
+/// 读取并校验布局，要求 dtype 与 `T` 一致
+fn layout<T: Element>(meta: &BufferMeta, size: usize, exclusive: bool) -> Result<TensorLayout> {
+    let dtype = meta.dtype();
+    if dtype != Some(T::DTYPE) {
+        return Err(Error::TypeMismatch {
+            expected: format!("{:?}", T::DTYPE),
+            actual: format!("{:?}", dtype),
+        });
+    }
+    TensorLayout::from_meta(meta, size, exclusive)
+}
+
+fn shape_builder(layout: &TensorLayout) -> ndarray::StrideShape<IxDyn> {
+    IxDyn(&layout.shape).strides(IxDyn(&layout.strides))
+}
+
+/// 检查数据指针按 `T` 对齐（视图可能位于父 buffer 的任意字节偏移）
+fn check_aligned<T: Element>(ptr: *const u8) -> Result<()> {
+    let align = std::mem::align_of::<T>();
+    if !(ptr as usize).is_multiple_of(align) {
+        return Err(Error::InvalidShape(format!(
+            "data at {:p} is not aligned to {} bytes for {:?}",
+            ptr,
+            align,
+            T::DTYPE
+        )));
+    }
+    Ok(())
+}
+
+impl BufferGuard {
+    /// 获取只读 ndarray 视图
+    ///
+    /// 使用元数据中的 shape 和字节 strides 构建视图，零拷贝。
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::TypeMismatch`]: dtype 与 `T` 不一致，或 buffer 不是 CPU 类型
+    /// - [`Error::InvalidShape`]: 未记录 shape，布局与 buffer 大小不一致，或数据未按 `T` 对齐
+    pub fn as_array_view<T: Element>(&self) -> Result<ArrayViewD<'_, T>> {
+        let slice = self.as_cpu_slice()?;
+        check_aligned::<T>(slice.as_ptr())?;
+        let layout = layout::<T>(self.meta()?, slice.len(), false)?;
+        let ptr = slice.as_ptr() as *const T;
+        // Safety: layout validated against buffer size and element alignment
+        Ok(unsafe { ArrayViewD::from_shape_ptr(shape_builder(&layout), ptr) })
+    }
+
+    /// 获取可写 ndarray 视图（需要 ReadWrite 模式）
+    ///
+    /// 除 [`as_array_view`](Self::as_array_view) 的检查外，
+    /// 还要求 strides 不产生元素重叠。
+    pub fn as_array_view_mut<T: Element>(&mut self) -> Result<ArrayViewMutD<'_, T>> {
+        let slice = self.as_cpu_slice_mut()?;
+        check_aligned::<T>(slice.as_ptr())?;
+        let (ptr, size) = (slice.as_mut_ptr() as *mut T, slice.len());
+        let layout = layout::<T>(self.meta()?, size, true)?;
+        // Safety: pointer aligned, layout validated as in-bounds and non-overlapping
+        Ok(unsafe { ArrayViewMutD::from_shape_ptr(shape_builder(&layout), ptr) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BufferPool;
+    use std::sync::atomic::Ordering;
+
+    fn unique_name() -> String {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        format!("/xmem_array_test_{}", ts)
+    }
+
+    #[test]
+    fn test_array_view_roundtrip() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let mut buf = pool.acquire_cpu_array(&[2, 3], DType::Int32).unwrap();
+        {
+            let mut view = buf.as_array_view_mut::<i32>().unwrap();
+            for (i, v) in view.iter_mut().enumerate() {
+                *v = i as i32;
+            }
+        }
+
+        let view = buf.as_array_view::<i32>().unwrap();
+        assert_eq!(view.shape(), &[2, 3]);
+        assert_eq!(view[[1, 2]], 5);
+    }
+
+    #[test]
+    fn test_array_view_custom_strides() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        // 2x2 view over every other column of a 2x4 u16 buffer
+        let mut buf = pool.acquire_cpu_array(&[2, 4], DType::UInt16).unwrap();
+        buf.as_array_view_mut::<u16>()
+            .unwrap()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = i as u16);
+
+        let meta = buf.meta().unwrap();
+        meta.shape[1].store(2, Ordering::SeqCst);
+        meta.strides[1].store(4, Ordering::SeqCst);
+
+        let view = buf.as_array_view::<u16>().unwrap();
+        assert_eq!(view.iter().copied().collect::<Vec<_>>(), vec![0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn test_array_view_rejects_bad_layout() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let mut buf = pool.acquire_cpu_array(&[4, 4], DType::Float32).unwrap();
+
+        // Wrong element type
+        assert!(matches!(
+            buf.as_array_view::<f64>(),
+            Err(Error::TypeMismatch { .. })
+        ));
+
+        let meta = buf.meta().unwrap();
+
+        // Stride not a multiple of element size
+        meta.strides[1].store(2, Ordering::SeqCst);
+        assert!(matches!(
+            buf.as_array_view::<f32>(),
+            Err(Error::InvalidShape(_))
+        ));
+
+        // Out of bounds
+        meta.strides[1].store(4, Ordering::SeqCst);
+        meta.shape[0].store(5, Ordering::SeqCst);
+        assert!(matches!(
+            buf.as_array_view::<f32>(),
+            Err(Error::InvalidShape(_))
+        ));
+
+        // Broadcast (zero stride) is fine read-only but not writable
+        meta.shape[0].store(4, Ordering::SeqCst);
+        meta.strides[0].store(0, Ordering::SeqCst);
+        assert!(buf.as_array_view::<f32>().is_ok());
+        assert!(matches!(
+            buf.as_array_view_mut::<f32>(),
+            Err(Error::InvalidShape(_))
+        ));
+    }
+
+    #[test]
+    fn test_array_view_rejects_misaligned_view() {
+        use crate::meta::TensorDesc;
+
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        let frame = pool.acquire_cpu(64).unwrap();
+
+        // A view at an odd byte offset cannot hold aligned f32 elements
+        let desc = TensorDesc::new([4], DType::Float32);
+        let mut odd = pool.create_view(frame.meta_index(), 1, 16, Some(&desc)).unwrap();
+        assert!(matches!(
+            odd.as_array_view::<f32>(),
+            Err(Error::InvalidShape(_))
+        ));
+        assert!(matches!(
+            odd.as_array_view_mut::<f32>(),
+            Err(Error::InvalidShape(_))
+        ));
+
+        // Byte-sized elements need no alignment
+        let bytes = TensorDesc::new([16], DType::UInt8);
+        let odd = pool.create_view(frame.meta_index(), 1, 16, Some(&bytes)).unwrap();
+        assert_eq!(odd.as_array_view::<u8>().unwrap().len(), 16);
+
+        let aligned = pool.create_view(frame.meta_index(), 4, 16, Some(&desc)).unwrap();
+        assert_eq!(aligned.as_array_view::<f32>().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_array_view_without_shape() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let buf = pool.acquire_cpu(64).unwrap();
+        assert!(buf.as_array_view::<u8>().is_err());
+    }
+}
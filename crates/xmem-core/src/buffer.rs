@@ -1,70 +1,94 @@
 //! Buffer handle and storage
 
 use crate::shm::SharedMemory;
-use crate::storage::StorageType;
-
-#[cfg(feature = "cuda")]
-use crate::cuda::CudaBuffer;
+use crate::storage::{StorageBackend, StorageSegment, StorageType};
+use std::sync::Arc;
 
 /// Buffer data storage
-pub enum BufferData {
-    Cpu(SharedMemory),
-    #[cfg(feature = "cuda")]
-    Cuda(CudaBuffer),
+///
+/// 由 [`StorageBackend`] 创建的数据段；视图为父 buffer 数据段中
+/// `[offset, offset + len)` 的字节范围。
+pub struct BufferData {
+    backend: Arc<dyn StorageBackend>,
+    segment: Box<dyn StorageSegment>,
+    offset: usize,
+    len: usize,
+    view: bool,
 }
 
 impl BufferData {
-    /// Get storage type
-    pub fn storage_type(&self) -> StorageType {
-        match self {
-            BufferData::Cpu(_) => StorageType::Cpu,
-            #[cfg(feature = "cuda")]
-            BufferData::Cuda(_) => StorageType::Cuda,
+    /// Wrap a whole segment created by `backend`
+    pub(crate) fn new(backend: Arc<dyn StorageBackend>, segment: Box<dyn StorageSegment>) -> Self {
+        let len = segment.size();
+        Self {
+            backend,
+            segment,
+            offset: 0,
+            len,
+            view: false,
+        }
+    }
+
+    /// Narrow to `len` bytes starting at `offset` within this data
+    pub(crate) fn into_view(self, offset: usize, len: usize) -> Self {
+        Self {
+            offset: self.offset + offset,
+            len,
+            view: true,
+            ..self
         }
     }
 
+    /// Get storage type id ([`StorageType::View`] for views)
+    pub fn storage_type(&self) -> u8 {
+        if self.view {
+            StorageType::View as u8
+        } else {
+            self.backend.storage_type()
+        }
+    }
+
+    /// Get the backend holding the data (views: the parent's)
+    pub fn backend(&self) -> &dyn StorageBackend {
+        &*self.backend
+    }
+
     /// Get size in bytes
     pub fn size(&self) -> usize {
-        match self {
-            BufferData::Cpu(shm) => shm.size(),
-            #[cfg(feature = "cuda")]
-            BufferData::Cuda(buf) => buf.size(),
-        }
+        self.len
+    }
+
+    /// Get the shared memory segment holding the data (views: the parent's)
+    pub fn shm(&self) -> Option<&SharedMemory> {
+        self.segment.shm()
+    }
+
+    /// Flush the data to persistent storage (file-backed pools), see [`StorageSegment::sync`]
+    pub fn sync(&self) -> crate::Result<()> {
+        self.segment.sync()
     }
 
-    /// Get CPU pointer (only for CPU buffers)
+    /// Get CPU pointer (only for host memory)
     pub fn as_cpu_ptr(&self) -> Option<*const u8> {
-        match self {
-            BufferData::Cpu(shm) => Some(shm.as_ptr()),
-            #[cfg(feature = "cuda")]
-            BufferData::Cuda(_) => None,
-        }
+        self.segment
+            .cpu_ptr()
+            .map(|p| unsafe { p.add(self.offset) } as *const u8)
     }
 
-    /// Get mutable CPU pointer (only for CPU buffers)
+    /// Get mutable CPU pointer (only for host memory)
     pub fn as_cpu_mut_ptr(&mut self) -> Option<*mut u8> {
-        match self {
-            BufferData::Cpu(shm) => Some(shm.as_mut_ptr()),
-            #[cfg(feature = "cuda")]
-            BufferData::Cuda(_) => None,
-        }
+        self.segment.cpu_ptr().map(|p| unsafe { p.add(self.offset) })
     }
 
     /// Get CUDA device pointer (only for CUDA buffers)
     #[cfg(feature = "cuda")]
     pub fn as_cuda_ptr(&self) -> Option<u64> {
-        match self {
-            BufferData::Cpu(_) => None,
-            BufferData::Cuda(buf) => Some(buf.device_ptr()),
-        }
+        self.segment.device_ptr().map(|p| p + self.offset as u64)
     }
 
     /// Get CUDA device ID (only for CUDA buffers)
     #[cfg(feature = "cuda")]
     pub fn cuda_device_id(&self) -> Option<i32> {
-        match self {
-            BufferData::Cpu(_) => None,
-            BufferData::Cuda(buf) => Some(buf.device_id()),
-        }
+        self.segment.device_id()
     }
 }
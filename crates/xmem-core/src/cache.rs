@@ -0,0 +1,175 @@
+//! 空闲 buffer 数据段的缓存与回收
+//!
+//! 默认情况下，buffer 的引用计数归 0 时立即释放数据段（见 [`crate::pool`]）。
+//! 启用 [`PoolConfig::cache_segments`] 后，CPU buffer 释放时保留数据段，只把槽位放回空闲链表；
+//! 之后从该槽分配同样大小的 buffer 时直接复用这个段，省去创建段和首次缺页的开销。
+//! 复用的 buffer 内容不会清零。
+//!
+//! 缓存的段会一直占用内存，直到被回收：
+//!
+//! - 空闲时间不少于 [`PoolConfig::trim_idle_after`] 的段用 `madvise(MADV_REMOVE)` 释放物理页
+//!   （进程内 heap 池为 `MADV_DONTNEED`）；段本身保留，复用时重新缺页。
+//!   未设置 `trim_idle_after` 时 [`BufferPool::trim`] 回收所有缓存段；其他平台不释放物理页
+//! - 缓存总字节数超过 [`PoolConfig::max_cached_bytes`] 时，从最早释放的段开始删除
+//!
+//! 每次释放 buffer 后自动检查字节预算；按空闲时间回收只在
+//! [`BufferPool::trim`] 中进行，长时间空闲的服务应定期调用它，例如每隔 `trim_idle_after` 调用一次。
+//!
+//! 缓存状态记录在共享元数据 [`BufferMeta::cache`] 中，任何进程都可以复用或回收缓存段。
+//! 释放 buffer 的进程按自己的配置决定是否缓存，因此共享池的各进程应使用相同的缓存设置。
+//! 操作缓存段前，进程把自己在[进程登记表](crate::process)中的槽位号 + 1
+//! 写入该字段的高 32 位来占有槽位；占有者崩溃后，其他进程会接管。
+//!
+//! # 示例
+//!
+//! ```
+//! use std::time::Duration;
+//! use xmem_core::{BufferPool, PoolConfig};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = PoolConfig::new()
+//!     .cache_segments(true)
+//!     .trim_idle_after(Duration::from_secs(30))
+//!     .max_cached_bytes(64 << 20);
+//! let pool = BufferPool::create_with_config("/cache_doc", &config)?;
+//!
+//! let buf = pool.acquire_cpu(4096)?;
+//! let index = buf.meta_index();
+//! drop(buf);
+//!
+//! // The same slot and segment are reused
+//! assert_eq!(pool.acquire_cpu(4096)?.meta_index(), index);
+//!
+//! // Run periodically to return idle memory to the system
+//! let report = pool.trim()?;
+//! assert!(report.unlinked.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`PoolConfig::cache_segments`]: crate::PoolConfig::cache_segments
+//! [`PoolConfig::trim_idle_after`]: crate::PoolConfig::trim_idle_after
+//! [`PoolConfig::max_cached_bytes`]: crate::PoolConfig::max_cached_bytes
+//! [`BufferPool::trim`]: crate::BufferPool::trim
+
+use crate::meta::{BufferMeta, CACHE_PRESENT};
+use crate::process::Holder;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Cache bits of [`BufferMeta::cache`]; the high half holds the claiming process slot + 1
+const BITS_MASK: u64 = u32::MAX as u64;
+
+/// Milliseconds since the Unix epoch
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Keep a released buffer's segment on its slot (before the slot is freed)
+pub(crate) fn put(meta: &BufferMeta) {
+    meta.timestamp.store(now_ms(), Ordering::SeqCst);
+    meta.cache.store(CACHE_PRESENT, Ordering::SeqCst);
+}
+
+/// 占有槽位上的缓存段，返回其缓存位；没有缓存段时返回 `None`
+///
+/// 其他存活进程（或本进程的其他线程）正占有时，`wait` 为 true 则等待其结束，
+/// 否则返回 `None`。占有者已退出时直接接管。
+pub(crate) fn claim(holder: &Holder<'_>, meta: &BufferMeta, wait: bool) -> Option<u64> {
+    let claimed = (holder.slot() as u64 + 1) << 32;
+    let mut attempts = 0u32;
+    loop {
+        let state = meta.cache.load(Ordering::SeqCst);
+        if state & CACHE_PRESENT == 0 {
+            return None;
+        }
+        let claimer = (state >> 32) as u32;
+        if claimer != 0 && holder.alive(claimer - 1) {
+            if !wait {
+                return None;
+            }
+            attempts = attempts.saturating_add(1);
+            if attempts < 64 {
+                std::thread::yield_now();
+            } else {
+                std::thread::sleep(Duration::from_micros(100));
+            }
+            continue;
+        }
+        let bits = state & BITS_MASK;
+        if meta
+            .cache
+            .compare_exchange(state, bits | claimed, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            return Some(bits);
+        }
+    }
+}
+
+/// End a claim, leaving `bits` (0 forgets the cached segment)
+pub(crate) fn unclaim(meta: &BufferMeta, bits: u64) {
+    meta.cache.store(bits & BITS_MASK, Ordering::SeqCst);
+}
+
+/// End a claim left by the process in `slot`, before a new process registers it
+pub(crate) fn forget_slot(meta: &BufferMeta, slot: u32) {
+    let state = meta.cache.load(Ordering::SeqCst);
+    if state >> 32 == slot as u64 + 1 {
+        let _ = meta.cache.compare_exchange(
+            state,
+            state & BITS_MASK,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::CACHE_TRIMMED;
+    use crate::meta_region::MetaRegion;
+
+    fn unique_name() -> String {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        format!("/xmem_cache_{}", ts)
+    }
+
+    #[test]
+    fn test_claim() {
+        let region = MetaRegion::create(&unique_name(), 4).unwrap();
+        let meta = region.get(0).unwrap();
+        let holder = region.holder().unwrap();
+        assert_eq!(claim(&holder, meta, true), None);
+
+        put(meta);
+        assert!(meta.timestamp.load(Ordering::SeqCst) > 0);
+        assert_eq!(claim(&holder, meta, false), Some(CACHE_PRESENT));
+        // Held by this process: others skip it
+        assert_eq!(claim(&holder, meta, false), None);
+        unclaim(meta, CACHE_PRESENT | CACHE_TRIMMED);
+        assert_eq!(meta.cache.load(Ordering::SeqCst), CACHE_PRESENT | CACHE_TRIMMED);
+
+        // A claim left by a process that exited is taken over
+        // (a dropped registration only looks dead with record locks)
+        #[cfg(target_os = "linux")]
+        {
+            use crate::process::tests::other_process;
+            let other = other_process(&region);
+            assert_eq!(
+                claim(&Holder::new(other.clone(), region.processes()), meta, false),
+                Some(CACHE_PRESENT | CACHE_TRIMMED)
+            );
+            assert_eq!(claim(&holder, meta, false), None);
+            drop(other);
+            assert_eq!(claim(&holder, meta, true), Some(CACHE_PRESENT | CACHE_TRIMMED));
+            unclaim(meta, 0);
+            assert_eq!(claim(&holder, meta, true), None);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Pool configuration
+//!
+//! [`PoolConfig`] 决定池的容量、共享内存名称和段的存放位置：
+//!
+//! - `namespace`: 名称前缀，加在所有 `{name}_meta` / `{name}_buf_N` 名称前，
+//!   用于在同一主机上隔离多个部署
+//! - `backing_dir`: 将段创建为该目录下的文件而非 `/dev/shm` 中的 POSIX 共享内存，
+//!   例如容器间共享的 tmpfs 卷（Docker 默认的 `/dev/shm` 只有 64MB 且不跨容器）
+//! - `cache_segments` / `trim_idle_after` / `max_cached_bytes`: 复用空闲 buffer 的数据段及其回收策略，
+//!   见 [`crate::cache`]
+//!
+//! 打开池的进程需使用相同的 `namespace` 和 `backing_dir`。
+//!
+//! # 示例
+//!
+//! ```
+//! use xmem_core::{BufferPool, PoolConfig};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = PoolConfig::new().capacity(64).namespace("cam_");
+//! let pool = BufferPool::create_with_config("/frames_doc", &config)?;
+//! assert_eq!(pool.name(), "/cam_frames_doc");
+//!
+//! let reader = BufferPool::open_with_config("/frames_doc", &config)?;
+//! assert_eq!(reader.capacity(), 64);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::memory::HugePages;
+use crate::{Error, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default metadata region capacity
+pub(crate) const DEFAULT_CAPACITY: usize = 1024;
+
+/// Longest pool name (namespace included, leading `/` excluded)
+///
+/// 留出 `_buf_4294967295` 后缀的空间，使段名称不超过 `NAME_MAX`（255）。
+pub const MAX_POOL_NAME_LEN: usize = 240;
+
+/// 缓冲池配置，见[模块文档](self)
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub(crate) capacity: usize,
+    pub(crate) namespace: Option<String>,
+    pub(crate) backing_dir: Option<PathBuf>,
+    pub(crate) huge_pages: HugePages,
+    pub(crate) prefault: bool,
+    pub(crate) mlock: bool,
+    pub(crate) numa_node: Option<u32>,
+    pub(crate) cache_segments: bool,
+    pub(crate) trim_idle_after: Option<Duration>,
+    pub(crate) max_cached_bytes: Option<u64>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            namespace: None,
+            backing_dir: None,
+            huge_pages: HugePages::Off,
+            prefault: false,
+            mlock: false,
+            numa_node: None,
+            cache_segments: false,
+            trim_idle_after: None,
+            max_cached_bytes: None,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Default configuration: 1024 buffers, no namespace, segments in `/dev/shm`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of buffers
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Set the prefix prepended to the pool name (e.g. `"cam_"` turns `/frames` into `/cam_frames`)
+    pub fn namespace(mut self, prefix: impl Into<String>) -> Self {
+        self.namespace = Some(prefix.into());
+        self
+    }
+
+    /// Create segments as files in `dir` instead of POSIX shared memory
+    pub fn backing_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.backing_dir = Some(dir.into());
+        self
+    }
+
+    /// 请求大页模式，不可用时回退，见 [`crate::memory`]
+    ///
+    /// [`HugePages::HugeTlb`] 需要 `backing_dir` 位于 hugetlbfs 挂载点。
+    pub fn huge_pages(mut self, mode: HugePages) -> Self {
+        self.huge_pages = mode;
+        self
+    }
+
+    /// Populate page tables when a buffer is allocated instead of on first touch
+    pub fn prefault(mut self, prefault: bool) -> Self {
+        self.prefault = prefault;
+        self
+    }
+
+    /// 锁定 buffer 映射（`mlock`），使其不会被换出，见 [`crate::memory`]
+    ///
+    /// 超出 `RLIMIT_MEMLOCK` 时分配或打开 buffer 会失败。
+    pub fn mlock(mut self, mlock: bool) -> Self {
+        self.mlock = mlock;
+        self
+    }
+
+    /// 将新分配的 buffer 绑定到 NUMA 节点，单节点机器上不做任何操作，见 [`crate::memory`]
+    pub fn numa_node(mut self, node: u32) -> Self {
+        self.numa_node = Some(node);
+        self
+    }
+
+    /// 释放 CPU buffer 时保留数据段供同样大小的分配复用，见 [`crate::cache`]
+    pub fn cache_segments(mut self, cache: bool) -> Self {
+        self.cache_segments = cache;
+        self
+    }
+
+    /// [`BufferPool::trim`](crate::BufferPool::trim) 释放空闲至少 `idle` 的缓存段的物理页
+    ///
+    /// 未设置时 `trim()` 释放所有缓存段的物理页。
+    pub fn trim_idle_after(mut self, idle: Duration) -> Self {
+        self.trim_idle_after = Some(idle);
+        self
+    }
+
+    /// 缓存段总字节数上限，超出时从最早释放的段开始删除，见 [`crate::cache`]
+    pub fn max_cached_bytes(mut self, bytes: u64) -> Self {
+        self.max_cached_bytes = Some(bytes);
+        self
+    }
+
+    /// 校验名称并加上命名空间前缀，返回池的完整名称
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::InvalidName`]: 名称或前缀不合法，见 [`validate_name`]
+    pub fn full_name(&self, name: &str) -> Result<String> {
+        validate_name(name)?;
+        let Some(namespace) = &self.namespace else {
+            return Ok(name.to_string());
+        };
+        if namespace.contains(['/', '\0']) {
+            return Err(invalid(namespace, "namespace must not contain '/' or NUL"));
+        }
+        let full = format!("/{}{}", namespace, name.trim_start_matches('/'));
+        validate_name(&full)?;
+        Ok(full)
+    }
+}
+
+fn invalid(name: &str, reason: &str) -> Error {
+    Error::InvalidName {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// 校验池名称
+///
+/// 名称可以 `/` 开头，其余部分须非空、不含 `/` 和 NUL，
+/// 长度不超过 [`MAX_POOL_NAME_LEN`] 字节。
+///
+/// # 错误
+///
+/// - [`Error::InvalidName`]: 名称不合法，`reason` 说明原因
+pub fn validate_name(name: &str) -> Result<()> {
+    let base = name.strip_prefix('/').unwrap_or(name);
+    if base.is_empty() {
+        return Err(invalid(name, "name is empty"));
+    }
+    if base.contains('/') {
+        return Err(invalid(name, "'/' is only allowed as the first character"));
+    }
+    if base.contains('\0') {
+        return Err(invalid(name, "name contains NUL"));
+    }
+    if base.len() > MAX_POOL_NAME_LEN {
+        return Err(invalid(name, &format!("longer than {} bytes", MAX_POOL_NAME_LEN)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("/frames").is_ok());
+        assert!(validate_name("frames").is_ok());
+        assert!(validate_name(&"x".repeat(MAX_POOL_NAME_LEN)).is_ok());
+
+        for bad in ["", "/", "/a/b", "a\0b", &"x".repeat(MAX_POOL_NAME_LEN + 1)] {
+            assert!(
+                matches!(validate_name(bad), Err(Error::InvalidName { .. })),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_name() {
+        let config = PoolConfig::new();
+        assert_eq!(config.full_name("/frames").unwrap(), "/frames");
+
+        let config = config.namespace("team_a.");
+        assert_eq!(config.full_name("/frames").unwrap(), "/team_a.frames");
+        assert_eq!(config.full_name("frames").unwrap(), "/team_a.frames");
+        assert!(config.full_name("/").is_err());
+
+        let config = PoolConfig::new().namespace("a/b");
+        assert!(config.full_name("/frames").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 //! CUDA buffer and IPC support
 
+use crate::meta::BufferMeta;
+use crate::storage::{SegmentId, StorageBackend, StorageSegment, StorageType};
 use crate::{Error, Result};
 use cudarc::driver::{CudaDevice, CudaSlice, DevicePtr};
 use std::sync::Arc;
@@ -168,3 +170,62 @@ impl Drop for CudaBuffer {
 // CUDA operations are thread-safe when using the driver API
 unsafe impl Send for CudaBuffer {}
 unsafe impl Sync for CudaBuffer {}
+
+impl StorageSegment for CudaBuffer {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn device_ptr(&self) -> Option<u64> {
+        Some(self.ptr)
+    }
+
+    fn device_id(&self) -> Option<i32> {
+        Some(self.device_id)
+    }
+
+    fn export_handle(&self, meta: &BufferMeta) -> Result<()> {
+        meta.set_storage_handle(&self.ipc_handle.reserved);
+        Ok(())
+    }
+}
+
+/// 内置 CUDA 存储后端：通过 CUDA IPC 句柄跨进程共享显存
+pub struct CudaBackend;
+
+impl StorageBackend for CudaBackend {
+    fn storage_type(&self) -> u8 {
+        StorageType::Cuda as u8
+    }
+
+    fn name(&self) -> &str {
+        "Cuda"
+    }
+
+    fn allocate(
+        &self,
+        _id: SegmentId<'_>,
+        size: usize,
+        device_id: u8,
+    ) -> Result<Box<dyn StorageSegment>> {
+        Ok(Box::new(CudaBuffer::alloc(device_id as i32, size)?))
+    }
+
+    fn import(&self, _id: SegmentId<'_>, meta: &BufferMeta) -> Result<Box<dyn StorageSegment>> {
+        use std::sync::atomic::Ordering;
+
+        let handle = CudaIpcHandle {
+            reserved: meta.storage_handle(),
+        };
+        Ok(Box::new(CudaBuffer::from_ipc_handle(
+            meta.device_id.load(Ordering::SeqCst) as i32,
+            &handle,
+            meta.size.load(Ordering::SeqCst) as usize,
+        )?))
+    }
+
+    fn free(&self, _id: SegmentId<'_>, _meta: &BufferMeta) -> Result<()> {
+        // Device memory is freed when the allocating process drops its CudaBuffer
+        Ok(())
+    }
+}
@@ -1,7 +1,14 @@
 //! Data type definitions
 
+use crate::Error;
+use std::fmt;
+use std::str::FromStr;
+
 /// Supported data types
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// 判别值写入共享内存中的 [`BufferMeta`](crate::BufferMeta)，
+/// 跨进程/跨版本保持稳定：新类型只能追加，不得修改已有值。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum DType {
     UInt8 = 0,
@@ -15,34 +22,235 @@ pub enum DType {
     Float16 = 8,
     Float32 = 9,
     Float64 = 10,
+    BFloat16 = 11,
+    Bool = 12,
+    Complex64 = 13,
+    Complex128 = 14,
+    /// 8-bit float, 4 exponent / 3 mantissa bits, finite only (`float8_e4m3fn`)
+    Float8E4M3 = 15,
+    /// 8-bit float, 5 exponent / 2 mantissa bits (`float8_e5m2`)
+    Float8E5M2 = 16,
 }
 
+/// Byte order prefix of numpy typestrs for this platform
+#[cfg(target_endian = "little")]
+const NATIVE_ORDER: char = '<';
+#[cfg(target_endian = "big")]
+const NATIVE_ORDER: char = '>';
+
 impl DType {
+    /// All data types, in discriminant order
+    pub const ALL: [DType; 17] = [
+        DType::UInt8,
+        DType::Int8,
+        DType::UInt16,
+        DType::Int16,
+        DType::UInt32,
+        DType::Int32,
+        DType::UInt64,
+        DType::Int64,
+        DType::Float16,
+        DType::Float32,
+        DType::Float64,
+        DType::BFloat16,
+        DType::Bool,
+        DType::Complex64,
+        DType::Complex128,
+        DType::Float8E4M3,
+        DType::Float8E5M2,
+    ];
+
     /// Size in bytes
     pub const fn size(&self) -> usize {
         match self {
-            DType::UInt8 | DType::Int8 => 1,
-            DType::UInt16 | DType::Int16 | DType::Float16 => 2,
+            DType::UInt8 | DType::Int8 | DType::Bool => 1,
+            DType::Float8E4M3 | DType::Float8E5M2 => 1,
+            DType::UInt16 | DType::Int16 | DType::Float16 | DType::BFloat16 => 2,
             DType::UInt32 | DType::Int32 | DType::Float32 => 4,
-            DType::UInt64 | DType::Int64 | DType::Float64 => 8,
+            DType::UInt64 | DType::Int64 | DType::Float64 | DType::Complex64 => 8,
+            DType::Complex128 => 16,
         }
     }
 
     /// Convert from u8
     pub fn from_u8(v: u8) -> Option<Self> {
-        match v {
-            0 => Some(DType::UInt8),
-            1 => Some(DType::Int8),
-            2 => Some(DType::UInt16),
-            3 => Some(DType::Int16),
-            4 => Some(DType::UInt32),
-            5 => Some(DType::Int32),
-            6 => Some(DType::UInt64),
-            7 => Some(DType::Int64),
-            8 => Some(DType::Float16),
-            9 => Some(DType::Float32),
-            10 => Some(DType::Float64),
+        Self::ALL.get(v as usize).copied()
+    }
+
+    /// numpy 风格的类型名，如 `"float32"`、`"bfloat16"`
+    ///
+    /// 扩展类型使用 `ml_dtypes` 的命名（`float8_e4m3fn`、`float8_e5m2`）。
+    pub const fn name(&self) -> &'static str {
+        match self {
+            DType::UInt8 => "uint8",
+            DType::Int8 => "int8",
+            DType::UInt16 => "uint16",
+            DType::Int16 => "int16",
+            DType::UInt32 => "uint32",
+            DType::Int32 => "int32",
+            DType::UInt64 => "uint64",
+            DType::Int64 => "int64",
+            DType::Float16 => "float16",
+            DType::Float32 => "float32",
+            DType::Float64 => "float64",
+            DType::BFloat16 => "bfloat16",
+            DType::Bool => "bool",
+            DType::Complex64 => "complex64",
+            DType::Complex128 => "complex128",
+            DType::Float8E4M3 => "float8_e4m3fn",
+            DType::Float8E5M2 => "float8_e5m2",
+        }
+    }
+
+    /// numpy `__array_interface__` 类型字符（不含字节序前缀）
+    const fn kind(&self) -> char {
+        match self {
+            DType::UInt8 | DType::UInt16 | DType::UInt32 | DType::UInt64 => 'u',
+            DType::Int8 | DType::Int16 | DType::Int32 | DType::Int64 => 'i',
+            DType::Float16 | DType::Float32 | DType::Float64 => 'f',
+            DType::Complex64 | DType::Complex128 => 'c',
+            DType::Bool => 'b',
+            // numpy 原生不支持，与 ml_dtypes 一致表示为定长 void
+            DType::BFloat16 | DType::Float8E4M3 | DType::Float8E5M2 => 'V',
+        }
+    }
+
+    /// numpy 数组接口的 typestr，如 `"<f4"`、`"|u1"`
+    ///
+    /// 单字节类型使用 `|`，其余使用本机字节序。
+    /// bfloat16 与 float8 在 numpy 中没有原生类型，返回 `"<V2"` / `"|V1"`，
+    /// 需配合 [`name()`](Self::name) 还原具体类型。
+    pub fn numpy_typestr(&self) -> String {
+        let order = if self.size() == 1 { '|' } else { NATIVE_ORDER };
+        format!("{}{}{}", order, self.kind(), self.size())
+    }
+
+    /// Whether this is a floating point type (including bf16 and fp8)
+    pub const fn is_float(&self) -> bool {
+        matches!(
+            self,
+            DType::Float16
+                | DType::Float32
+                | DType::Float64
+                | DType::BFloat16
+                | DType::Float8E4M3
+                | DType::Float8E5M2
+        )
+    }
+
+    /// Whether this is a complex type
+    pub const fn is_complex(&self) -> bool {
+        matches!(self, DType::Complex64 | DType::Complex128)
+    }
+}
+
+impl fmt::Display for DType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DType {
+    type Err = Error;
+
+    /// 解析 numpy 风格的类型名或 typestr
+    ///
+    /// 支持 `"float32"`、`"<f4"`、`"f4"`、`"|u1"`、`"bfloat16"`、`"bf16"` 等写法。
+    /// 非本机字节序的 typestr 会被拒绝。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || Error::UnknownDType(s.to_string());
+
+        let alias = match s {
+            "bool_" | "?" => Some(DType::Bool),
+            "bf16" => Some(DType::BFloat16),
+            "half" | "f16" => Some(DType::Float16),
+            "float" | "single" | "f32" => Some(DType::Float32),
+            "double" | "f64" => Some(DType::Float64),
+            "float8_e4m3" | "e4m3" => Some(DType::Float8E4M3),
+            "e5m2" => Some(DType::Float8E5M2),
             _ => None,
+        };
+        if let Some(dtype) = alias {
+            return Ok(dtype);
         }
+        if let Some(dtype) = Self::ALL.iter().find(|d| d.name() == s) {
+            return Ok(*dtype);
+        }
+
+        // typestr: optional byte order, kind char, byte size
+        let body = match s.chars().next() {
+            Some('|') | Some('=') => &s[1..],
+            Some(c) if c == NATIVE_ORDER => &s[1..],
+            Some('<') | Some('>') => return Err(unknown()),
+            _ => s,
+        };
+        let mut chars = body.chars();
+        let kind = chars.next().ok_or_else(unknown)?;
+        let size: usize = chars.as_str().parse().map_err(|_| unknown())?;
+
+        // V 类型无法确定具体类型（bf16 与 fp8 需通过名称区分）
+        if kind == 'V' {
+            return Err(unknown());
+        }
+        Self::ALL
+            .iter()
+            .find(|d| d.kind() == kind && d.size() == size)
+            .copied()
+            .ok_or_else(unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discriminants_stable() {
+        for (i, dtype) in DType::ALL.iter().enumerate() {
+            assert_eq!(*dtype as u8 as usize, i);
+            assert_eq!(DType::from_u8(i as u8), Some(*dtype));
+        }
+        assert_eq!(DType::Float64 as u8, 10);
+        assert_eq!(DType::from_u8(DType::ALL.len() as u8), None);
+    }
+
+    #[test]
+    fn test_name_roundtrip() {
+        for dtype in DType::ALL {
+            assert_eq!(dtype.to_string().parse::<DType>().unwrap(), dtype);
+        }
+    }
+
+    #[test]
+    fn test_typestr_roundtrip() {
+        for dtype in DType::ALL {
+            let typestr = dtype.numpy_typestr();
+            match dtype {
+                DType::BFloat16 | DType::Float8E4M3 | DType::Float8E5M2 => {
+                    assert!(typestr.parse::<DType>().is_err());
+                }
+                _ => assert_eq!(typestr.parse::<DType>().unwrap(), dtype),
+            }
+        }
+        assert_eq!(DType::Bool.numpy_typestr(), "|b1");
+        assert_eq!(DType::Complex128.numpy_typestr(), format!("{}c16", NATIVE_ORDER));
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        assert_eq!("float32".parse::<DType>().unwrap(), DType::Float32);
+        assert_eq!("f4".parse::<DType>().unwrap(), DType::Float32);
+        assert_eq!("=f4".parse::<DType>().unwrap(), DType::Float32);
+        assert_eq!("|u1".parse::<DType>().unwrap(), DType::UInt8);
+        assert_eq!("|b1".parse::<DType>().unwrap(), DType::Bool);
+        assert_eq!("c16".parse::<DType>().unwrap(), DType::Complex128);
+        assert_eq!("bf16".parse::<DType>().unwrap(), DType::BFloat16);
+        assert_eq!("float8_e4m3fn".parse::<DType>().unwrap(), DType::Float8E4M3);
+
+        assert!("float128".parse::<DType>().is_err());
+        assert!("f3".parse::<DType>().is_err());
+        assert!("".parse::<DType>().is_err());
+        #[cfg(target_endian = "little")]
+        assert!(">f4".parse::<DType>().is_err());
     }
 }
@@ -19,9 +19,59 @@ pub enum Error {
     #[error("buffer already forgotten")]
     AlreadyForgotten,
 
+    #[error("out of bounds: offset {offset} + len {len} exceeds size {size}")]
+    OutOfBounds {
+        offset: usize,
+        len: usize,
+        size: usize,
+    },
+
     #[error("invalid shape: {0}")]
     InvalidShape(String),
 
+    #[error("label of {len} bytes exceeds {max} bytes")]
+    LabelTooLong {
+        len: usize,
+        max: usize,
+    },
+
+    #[error("unknown dtype: {0}")]
+    UnknownDType(String),
+
+    #[error("buffer {0} is locked by another holder")]
+    Locked(u32),
+
+    #[error("too many processes using the pool (at most {0})")]
+    TooManyProcesses(usize),
+
+    #[error("buffer {0} is not in seqlock mode")]
+    SeqlockDisabled(u32),
+
+    #[error("stale handle for buffer {index}: generation {expected}, slot is at {actual}")]
+    StaleHandle {
+        index: u32,
+        expected: u32,
+        actual: u32,
+    },
+
+    #[error("unknown storage type {0}")]
+    UnknownStorage(u8),
+
+    #[error("storage type {0} is reserved or already registered")]
+    StorageTypeTaken(u8),
+
+    #[error("out of shared memory space: requested {requested} bytes, {available} available")]
+    OutOfShmSpace {
+        requested: u64,
+        available: u64,
+    },
+
+    #[error("invalid pool name {name:?}: {reason}")]
+    InvalidName {
+        name: String,
+        reason: String,
+    },
+
     #[error("Operation timed out")]
     Timeout,
 
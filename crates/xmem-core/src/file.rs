@@ -0,0 +1,270 @@
+//! 基于文件的持久化存储
+//!
+//! [`BufferPool::create_file`](crate::BufferPool::create_file) 创建的池将元数据区和
+//! buffer 数据保存为目录下的 mmap 文件（`{name}_meta`、`{name}_buf_{index}`），
+//! 而非 POSIX 共享内存名称。目录可以位于磁盘或自定义 tmpfs 上；
+//! 池销毁时文件不会被删除，进程重启后可通过
+//! [`BufferPool::open_file`](crate::BufferPool::open_file) 重新挂载。
+//!
+//! 设置了 [`PoolConfig::backing_dir`](crate::PoolConfig::backing_dir) 的池使用同样的文件布局，
+//! 但与共享内存池一样由创建者在销毁时删除所有文件。
+
+use crate::memory;
+use crate::meta::BufferMeta;
+use crate::shm::reserve_space;
+use crate::storage::{SegmentId, StorageBackend, StorageSegment, StorageType};
+use crate::{Error, Result};
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsFd, AsRawFd};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+/// Memory-mapped file (shared mapping, read-write)
+pub struct MappedFile {
+    ptr: *mut u8,
+    /// Usable size
+    size: usize,
+    /// Mapped length (file size, rounded up to the huge page size on hugetlbfs)
+    len: usize,
+    path: PathBuf,
+    owner: bool,
+}
+
+// Safety: the mapping is valid process-wide and only unmapped on drop
+unsafe impl Send for MappedFile {}
+
+fn io_error(path: &Path, err: std::io::Error) -> Error {
+    Error::SharedMemory(format!("{}: {}", path.display(), err))
+}
+
+impl MappedFile {
+    /// 创建 `size` 字节的文件并映射，内容为零
+    ///
+    /// `exclusive` 为 true 时文件已存在则失败，否则截断已有文件。
+    /// 文件默认在 drop 后保留，见 [`set_owner()`](Self::set_owner)。
+    /// 位于 hugetlbfs 时文件大小向上取整到大页大小。
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::OutOfShmSpace`]: 所在文件系统空间不足，此时不会留下 `path` 文件
+    pub fn create(path: &Path, size: usize, exclusive: bool) -> Result<Self> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).mode(0o600);
+        if exclusive {
+            options.create_new(true);
+        } else {
+            options.create(true).truncate(true);
+        }
+        let file = options.open(path).map_err(|e| io_error(path, e))?;
+        let huge_page = path.parent().and_then(memory::hugetlb_page_size);
+        let len = huge_page.map_or(size, |page| size.div_ceil(page) * page);
+        // Truncated to 0 first, so the contents read as zeros
+        let reserved = file
+            .set_len(len as u64)
+            .map_err(|e| io_error(path, e))
+            .and_then(|()| reserve_space(file.as_fd(), len, path));
+        if let Err(e) = reserved {
+            let _ = std::fs::remove_file(path);
+            return Err(e);
+        }
+        Ok(Self::map(&file, path, len)?.with_size(size))
+    }
+
+    /// Open and map an existing file
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| io_error(path, e))?;
+        let len = file.metadata().map_err(|e| io_error(path, e))?.len() as usize;
+        Self::map(&file, path, len)
+    }
+
+    /// Map the first `len` bytes of `file`; the mapping outlives the descriptor
+    fn map(file: &File, path: &Path, len: usize) -> Result<Self> {
+        // Empty mappings are not allowed; the extra byte is never accessed
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len.max(1),
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io_error(path, std::io::Error::last_os_error()));
+        }
+        Ok(Self {
+            ptr: ptr as *mut u8,
+            size: len,
+            len,
+            path: path.to_path_buf(),
+            owner: false,
+        })
+    }
+
+    /// Limit the usable size (no larger than the mapping)
+    fn with_size(mut self, size: usize) -> Self {
+        self.size = size.min(self.len);
+        self
+    }
+
+    /// Get the file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get size in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Get a raw pointer to the mapping
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Check if the file is removed on drop
+    pub fn is_owner(&self) -> bool {
+        self.owner
+    }
+
+    /// 设置是否为 owner
+    ///
+    /// owner 在 Drop 时删除文件（与共享内存的 unlink 相同，已有映射保持有效）。
+    pub fn set_owner(&mut self, owner: bool) {
+        self.owner = owner;
+    }
+
+    /// Flush the mapping to the file (`msync`)
+    pub fn sync(&self) -> Result<()> {
+        let ret = unsafe { libc::msync(self.ptr as *mut libc::c_void, self.len.max(1), libc::MS_SYNC) };
+        if ret != 0 {
+            return Err(io_error(&self.path, std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len.max(1)) };
+        if self.owner {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl StorageSegment for MappedFile {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn cpu_ptr(&self) -> Option<*mut u8> {
+        Some(self.ptr)
+    }
+
+    fn sync(&self) -> Result<()> {
+        MappedFile::sync(self)
+    }
+}
+
+/// Path of a pool file: `dir/{name}{suffix}`, without the leading `/` of shm-style names
+pub(crate) fn pool_file(dir: &Path, name: &str, suffix: &str) -> PathBuf {
+    dir.join(format!("{}{}", name.trim_start_matches('/'), suffix))
+}
+
+/// 进程对文件池的挂载标记：元数据文件上的 `flock`
+///
+/// 每个打开池的进程持有共享锁；能取得排他锁说明没有其他进程挂载，
+/// 此时修复元数据（[`BufferPool::fsck`](crate::BufferPool::fsck)）是安全的。
+pub(crate) struct AttachLock(File);
+
+impl AttachLock {
+    /// Take a shared lock, waiting while another process holds the exclusive one
+    pub(crate) fn shared(path: &Path) -> Result<Self> {
+        let lock = Self::open(path)?;
+        lock.flock(libc::LOCK_SH)?;
+        Ok(lock)
+    }
+
+    /// Take the exclusive lock, `None` if other processes are attached
+    pub(crate) fn try_exclusive(path: &Path) -> Result<Option<Self>> {
+        let lock = Self::open(path)?;
+        match lock.flock(libc::LOCK_EX | libc::LOCK_NB) {
+            Ok(()) => Ok(Some(lock)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Convert an exclusive lock to a shared one
+    pub(crate) fn downgrade(&self) -> Result<()> {
+        self.flock(libc::LOCK_SH)
+    }
+
+    fn open(path: &Path) -> Result<Self> {
+        File::open(path).map(Self).map_err(|e| io_error(path, e))
+    }
+
+    fn flock(&self, operation: libc::c_int) -> Result<()> {
+        if unsafe { libc::flock(self.0.as_raw_fd(), operation) } != 0 {
+            return Err(Error::SharedMemory(std::io::Error::last_os_error().to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 文件存储后端：每个 buffer 一个文件，位于池目录下
+pub(crate) struct FileBackend {
+    dir: PathBuf,
+}
+
+impl FileBackend {
+    pub(crate) fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    fn path(&self, id: SegmentId<'_>) -> PathBuf {
+        pool_file(&self.dir, id.pool, &format!("_buf_{}", id.index))
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn storage_type(&self) -> u8 {
+        StorageType::File as u8
+    }
+
+    fn name(&self) -> &str {
+        "File"
+    }
+
+    fn allocate(
+        &self,
+        id: SegmentId<'_>,
+        size: usize,
+        _device_id: u8,
+    ) -> Result<Box<dyn StorageSegment>> {
+        // A file left behind by a crashed process may still occupy the slot's name
+        Ok(Box::new(MappedFile::create(&self.path(id), size, false)?))
+    }
+
+    fn import(&self, id: SegmentId<'_>, meta: &BufferMeta) -> Result<Box<dyn StorageSegment>> {
+        // Files on hugetlbfs are longer than the buffer
+        let size = meta.size.load(Ordering::SeqCst) as usize;
+        Ok(Box::new(MappedFile::open(&self.path(id))?.with_size(size)))
+    }
+
+    fn free(&self, id: SegmentId<'_>, _meta: &BufferMeta) -> Result<()> {
+        // Processes still mapping the file keep their mapping
+        match std::fs::remove_file(self.path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(&self.path(id), e)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -20,9 +20,17 @@
 //! ```
 
 use crate::buffer::BufferData;
+use crate::dtype::DType;
+use crate::lock::{self, LockKind};
+use crate::meta::{BufferMeta, TensorDesc};
+use crate::pool::{BufferPool, PoolShared};
+use crate::process::Registration;
+use crate::shm::SharedMemory;
 use crate::storage::AccessMode;
 use crate::{Error, Result};
-use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::atomic::Ordering;
+use std::os::fd::OwnedFd;
+use std::sync::Arc;
 
 /// RAII 风格的缓冲区访问守卫
 ///
@@ -62,42 +70,101 @@ pub struct BufferGuard {
     meta_index: u32,
     /// Access mode
     mode: AccessMode,
-    /// Reference to ref_count in shared memory
-    ref_count: *const AtomicI32,
+    /// Pointer to metadata in shared memory
+    meta: *const BufferMeta,
     /// Whether this guard owns the release responsibility
     should_release: bool,
-    /// Pool name for recycling (optional, only set by pool)
-    pool_name: Option<String>,
+    /// Owning pool for recycling; also keeps `meta` mapped (only set by pool)
+    pool: Option<Arc<PoolShared>>,
+    /// Cross-process lock held by this guard, shared with guards derived from it
+    lock: Option<Arc<HeldLock>>,
 }
 
 // Safety: BufferGuard can be sent between threads
 // The underlying shared memory is process-wide accessible
 unsafe impl Send for BufferGuard {}
 
+/// 跨进程读写锁的持有记录
+///
+/// 由 guard 及其派生 guard（[`BufferGuard::try_clone`]、[`BufferGuard::slice`]）共享，
+/// 最后一个持有者释放时才解锁，派生 guard 因此不会在锁释放后继续写入。
+struct HeldLock {
+    /// Metadata of the locked buffer (the root of a view)
+    meta: *const BufferMeta,
+    kind: LockKind,
+    /// Process registration the lock is recorded under
+    owner: Arc<Registration>,
+    /// Keeps `meta` mapped
+    _pool: Option<Arc<PoolShared>>,
+}
+
+// Safety: `meta` points into the mapped metadata region and is only used for atomics
+unsafe impl Send for HeldLock {}
+unsafe impl Sync for HeldLock {}
+
+impl Drop for HeldLock {
+    fn drop(&mut self) {
+        lock::unlock(&self.owner, unsafe { &*self.meta }, self.kind);
+    }
+}
+
 impl BufferGuard {
     /// 创建新的缓冲区守卫（内部使用）
     pub(crate) fn new(
         data: BufferData,
         meta_index: u32,
         mode: AccessMode,
-        ref_count: *const AtomicI32,
+        meta: *const BufferMeta,
     ) -> Self {
         Self {
             data: Some(data),
             meta_index,
             mode,
-            ref_count,
+            meta,
             should_release: true,
-            pool_name: None,
+            pool: None,
+            lock: None,
         }
     }
 
-    /// Set pool name for auto-recycling on drop
-    pub(crate) fn with_pool(mut self, pool_name: String) -> Self {
-        self.pool_name = Some(pool_name);
+    /// Record a lock taken on this guard's behalf on `meta` (the buffer or its root)
+    pub(crate) fn with_lock(
+        mut self,
+        kind: LockKind,
+        meta: &BufferMeta,
+        owner: &Arc<Registration>,
+    ) -> Self {
+        self.lock = Some(Arc::new(HeldLock {
+            meta,
+            kind,
+            owner: Arc::clone(owner),
+            _pool: self.pool.clone(),
+        }));
+        self
+    }
+
+    /// Share the lock held by `other`
+    fn sharing_lock(mut self, other: &BufferGuard) -> Self {
+        self.lock = other.lock.clone();
         self
     }
 
+    /// Attach the owning pool for auto-recycling on drop
+    pub(crate) fn with_pool(mut self, pool: &BufferPool) -> Self {
+        self.pool = Some(Arc::clone(pool.shared()));
+        self
+    }
+
+    /// Name of the owning pool
+    pub(crate) fn pool_name(&self) -> Option<&str> {
+        self.pool.as_ref().map(|p| p.name())
+    }
+
+    /// Pool handle sharing this guard's pool state
+    fn pool(&self) -> Option<BufferPool> {
+        self.pool.clone().map(BufferPool::from_shared)
+    }
+
     /// 获取元数据索引
     ///
     /// 返回此 guard 管理的 buffer 的元数据索引，
@@ -106,11 +173,92 @@ impl BufferGuard {
         self.meta_index
     }
 
+    /// 获取 buffer 大小（字节）
+    pub fn size(&self) -> Result<usize> {
+        let data = self.data.as_ref().ok_or(Error::AlreadyForgotten)?;
+        Ok(data.size())
+    }
+
+    /// 获取承载数据的共享内存段名称（如 `/pool_buf_3`）
+    ///
+    /// 视图返回父 buffer 的段名称，数据位于段内 `view_offset` 处。
+    pub fn backing_name(&self) -> Result<&str> {
+        Ok(self.backing_shm()?.name())
+    }
+
+    /// 获取池的段文件目录（[`PoolConfig::backing_dir`](crate::PoolConfig::backing_dir) 或文件池），
+    /// 共享内存池返回 `None`
+    pub fn backing_dir(&self) -> Option<&std::path::Path> {
+        self.pool.as_ref().and_then(|p| p.backing_dir())
+    }
+
+    /// 打开承载数据的共享内存段，返回新的文件描述符
+    ///
+    /// 只读 guard 以只读方式打开。描述符由调用方持有，可在 guard 释放后继续使用，
+    /// 但 buffer 回收后段名称会被 unlink 并可能被新 buffer 复用。
+    pub fn backing_fd(&self) -> Result<OwnedFd> {
+        let shm = self.backing_shm()?;
+        SharedMemory::open_fd(shm.name(), self.mode == AccessMode::ReadWrite)
+    }
+
+    fn backing_shm(&self) -> Result<&SharedMemory> {
+        let data = self.data.as_ref().ok_or(Error::AlreadyForgotten)?;
+        data.shm().ok_or_else(|| Error::TypeMismatch {
+            expected: "Cpu".to_string(),
+            actual: data.backend().name().to_string(),
+        })
+    }
+
+    /// 将 buffer 数据刷写到磁盘（`msync`）
+    ///
+    /// 仅对文件池（[`BufferPool::create_file`](crate::BufferPool::create_file)）有效，
+    /// 其他存储为无操作。视图会刷写整个父 buffer。
+    pub fn sync(&self) -> Result<()> {
+        self.data.as_ref().ok_or(Error::AlreadyForgotten)?.sync()
+    }
+
     /// 获取访问模式
     pub fn mode(&self) -> AccessMode {
         self.mode
     }
 
+    /// 获取共享内存中的元数据
+    ///
+    /// 元数据字段均为原子类型，可通过共享引用读写 shape/dtype 等信息。
+    pub fn meta(&self) -> Result<&BufferMeta> {
+        if self.data.is_none() {
+            return Err(Error::AlreadyForgotten);
+        }
+        if self.meta.is_null() {
+            return Err(Error::BufferNotFound(self.meta_index));
+        }
+        Ok(unsafe { &*self.meta })
+    }
+
+    /// 记录张量 shape/dtype（C 连续 strides），需要 ReadWrite 模式
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::ReadOnly`]：guard 为只读
+    /// - [`Error::InvalidShape`]：维度数超过 [`MAX_NDIM`](crate::MAX_NDIM)，
+    ///   或张量大小超过 buffer 大小（含计算溢出）
+    ///
+    /// 出错时不修改元数据。
+    pub fn set_tensor(&self, shape: &[usize], dtype: DType) -> Result<()> {
+        if self.mode == AccessMode::ReadOnly {
+            return Err(Error::ReadOnly);
+        }
+        let size = self.size()?;
+        let desc = TensorDesc::new(shape, dtype);
+        if desc.nbytes().is_none_or(|n| n > size) {
+            return Err(Error::InvalidShape(format!(
+                "tensor {:?} of {} does not fit in {} bytes",
+                shape, dtype, size
+            )));
+        }
+        self.meta()?.set_tensor(shape, dtype)
+    }
+
     /// 检查 buffer 是否仍然有效
     ///
     /// 如果已调用 [`forget()`]，返回 `false`。
@@ -127,12 +275,18 @@ impl BufferGuard {
     /// - [`Error::AlreadyForgotten`]: guard 已被 forget
     /// - [`Error::TypeMismatch`]: buffer 不是 CPU 类型
     pub fn as_cpu_slice(&self) -> Result<&[u8]> {
+        let (ptr, size) = self.cpu_ptr()?;
+        Ok(unsafe { std::slice::from_raw_parts(ptr, size) })
+    }
+
+    /// CPU data pointer and size, for access that must not create references
+    pub(crate) fn cpu_ptr(&self) -> Result<(*const u8, usize)> {
         let data = self.data.as_ref().ok_or(Error::AlreadyForgotten)?;
         let ptr = data.as_cpu_ptr().ok_or_else(|| Error::TypeMismatch {
             expected: "Cpu".to_string(),
-            actual: "Cuda".to_string(),
+            actual: data.backend().name().to_string(),
         })?;
-        Ok(unsafe { std::slice::from_raw_parts(ptr, data.size()) })
+        Ok((ptr, data.size()))
     }
 
     /// Get CPU slice (mutable, requires ReadWrite mode)
@@ -142,9 +296,10 @@ impl BufferGuard {
         }
         let data = self.data.as_mut().ok_or(Error::AlreadyForgotten)?;
         let size = data.size();
-        let ptr = data.as_cpu_mut_ptr().ok_or_else(|| Error::TypeMismatch {
+        let actual = data.backend().name().to_string();
+        let ptr = data.as_cpu_mut_ptr().ok_or(Error::TypeMismatch {
             expected: "Cpu".to_string(),
-            actual: "Cuda".to_string(),
+            actual,
         })?;
         Ok(unsafe { std::slice::from_raw_parts_mut(ptr, size) })
     }
@@ -155,7 +310,7 @@ impl BufferGuard {
         let data = self.data.as_ref().ok_or(Error::AlreadyForgotten)?;
         data.as_cuda_ptr().ok_or_else(|| Error::TypeMismatch {
             expected: "Cuda".to_string(),
-            actual: "Cpu".to_string(),
+            actual: data.backend().name().to_string(),
         })
     }
 
@@ -168,38 +323,72 @@ impl BufferGuard {
         self.as_cuda_ptr()
     }
 
+    /// 在此 buffer 上切出 `[offset, offset + len)` 子区域视图
+    ///
+    /// 返回的 guard 访问模式与当前 guard 相同，并持有父 buffer 的引用，
+    /// 因此即使当前 guard 先被释放，视图仍然有效。
+    /// 视图与当前 guard 共享读写锁，锁在两者都释放后才解除。
+    /// 详见 [`BufferPool::create_view`]。
+    pub fn slice(&self, offset: usize, len: usize) -> Result<BufferGuard> {
+        if self.data.is_none() {
+            return Err(Error::AlreadyForgotten);
+        }
+        let pool = self.pool().ok_or(Error::BufferNotFound(self.meta_index))?;
+        let view = pool.create_view_with_mode(self.meta_index, offset, len, None, self.mode)?;
+        Ok(view.sharing_lock(self))
+    }
+
+    /// 创建引用同一 buffer 的新 guard（引用计数 +1）
+    ///
+    /// 新 guard 访问模式相同，并与当前 guard 共享读写锁：锁在所有副本都释放后才解除，
+    /// 因此可以交给生命周期不受当前 guard 控制的使用方（如 DLPack 导出的张量）。
+    pub fn try_clone(&self) -> Result<BufferGuard> {
+        if self.data.is_none() {
+            return Err(Error::AlreadyForgotten);
+        }
+        let pool = self.pool().ok_or(Error::BufferNotFound(self.meta_index))?;
+        Ok(pool.share(self.meta_index, self.mode)?.sharing_lock(self))
+    }
+
     /// Forget this guard without releasing the buffer
     /// Used when transferring ownership to another process
+    ///
+    /// 读写锁属于当前进程，无法转移，因此仍会被释放（派生 guard 仍持有时除外）。
     pub fn forget(mut self) {
+        self.unlock();
         self.should_release = false;
         self.data = None;
     }
 
+    /// Drop this guard's share of the cross-process lock, if held
+    fn unlock(&mut self) {
+        self.lock = None;
+    }
+
     /// Manually release and decrement ref count
-    fn release(&self) {
-        if !self.ref_count.is_null() {
-            let ref_count = unsafe { &*self.ref_count };
-            ref_count.fetch_sub(1, Ordering::SeqCst);
+    ///
+    /// 返回递减前的引用计数（meta 指针为空时返回 `None`）
+    fn release(&self) -> Option<i32> {
+        if self.meta.is_null() {
+            return None;
         }
+        let meta = unsafe { &*self.meta };
+        Some(meta.ref_count.fetch_sub(1, Ordering::SeqCst))
     }
 }
 
 impl Drop for BufferGuard {
     fn drop(&mut self) {
-        if self.should_release && self.data.is_some() {
-            if !self.ref_count.is_null() {
-                let ref_count = unsafe { &*self.ref_count };
-                let old = ref_count.fetch_sub(1, Ordering::SeqCst);
-
-                // If ref_count reaches 0 and we have pool info, recycle
-                if old == 1 {
-                    if let Some(pool_name) = &self.pool_name {
-                        // Try to recycle - open pool and release
-                        if let Ok(pool) = crate::BufferPool::open(pool_name) {
-                            let _ = pool.release_buffer(self.meta_index);
-                        }
-                    }
-                }
+        self.unlock();
+
+        if !self.should_release || self.data.is_none() {
+            return;
+        }
+
+        // If ref_count reaches 0 and we have pool info, recycle
+        if self.release() == Some(1) {
+            if let Some(pool) = self.pool() {
+                let _ = pool.release_buffer(self.meta_index);
             }
         }
     }
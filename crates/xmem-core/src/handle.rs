@@ -0,0 +1,195 @@
+//! 可跨进程传递的 buffer 句柄
+//!
+//! [`BufferHandle`] 记录池名称、元数据索引和槽位代数（generation），
+//! 并携带一个引用：由 [`BufferGuard::into_handle`] 转出，
+//! 在目标进程中由 [`BufferPool::adopt`] 接管，不会再增加引用计数。
+//!
+//! 槽位每次分配时 generation 递增，因此 buffer 被回收后，
+//! 旧句柄会被识别为过期（[`Error::StaleHandle`]），不会误接管新 buffer。
+//!
+//! # 示例
+//!
+//! ```
+//! use xmem_core::BufferPool;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = BufferPool::create("/my_pool_handle_doc")?;
+//! let mut buf = pool.acquire_cpu(4)?;
+//! buf.as_cpu_slice_mut()?.copy_from_slice(b"data");
+//!
+//! // 序列化 handle 后发送给其他进程 ...
+//! let handle = buf.into_handle()?;
+//!
+//! // ... 在其他进程中接管引用
+//! let pool = BufferPool::open(&handle.pool_name)?;
+//! let buf = pool.adopt(&handle)?;
+//! assert_eq!(buf.as_cpu_slice()?, b"data");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::guard::BufferGuard;
+use crate::lock::LockWait;
+use crate::pool::BufferPool;
+use crate::storage::AccessMode;
+use crate::{Error, Result};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// 携带一个引用的 buffer 句柄
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferHandle {
+    /// Name of the pool the buffer belongs to
+    pub pool_name: String,
+    /// Metadata index
+    pub meta_index: u32,
+    /// Slot generation at the time the handle was made
+    pub generation: u32,
+    /// Access mode of the adopted guard
+    pub mode: AccessMode,
+}
+
+impl BufferGuard {
+    /// 转为携带当前引用的句柄（不释放引用）
+    ///
+    /// 句柄必须恰好被 [`BufferPool::adopt`] 接管一次，否则引用会泄漏或被重复释放。
+    /// 读写锁属于当前进程，转出时会被释放，由 [`BufferPool::adopt`] 重新获取。
+    pub fn into_handle(self) -> Result<BufferHandle> {
+        let pool_name = self
+            .pool_name()
+            .ok_or(Error::BufferNotFound(self.meta_index()))?
+            .to_string();
+        let handle = BufferHandle {
+            pool_name,
+            meta_index: self.meta_index(),
+            generation: self.meta()?.generation.load(Ordering::SeqCst),
+            mode: self.mode(),
+        };
+        self.forget();
+        Ok(handle)
+    }
+
+    /// 获取槽位代数
+    pub fn generation(&self) -> Result<u32> {
+        Ok(self.meta()?.generation.load(Ordering::SeqCst))
+    }
+}
+
+impl BufferPool {
+    /// 接管句柄携带的引用
+    ///
+    /// 引用计数不变。与 [`BufferPool::get`] / [`BufferPool::get_mut`] 一样按句柄的访问模式
+    /// 重新获取读写锁（转出时已释放），其他进程持有冲突的锁时阻塞等待，
+    /// 因此接管后的读写 guard 仍是唯一的写者。
+    ///
+    /// # 错误
+    ///
+    /// - 句柄属于其他池：[`Error::BufferNotFound`]
+    /// - 槽位已被回收（generation 不一致或引用计数为 0）：[`Error::StaleHandle`]
+    ///
+    /// 出错时句柄的引用未被接管，可以重试。
+    pub fn adopt(&self, handle: &BufferHandle) -> Result<BufferGuard> {
+        self.adopt_with(handle, LockWait::Block)
+    }
+
+    /// Adopt a handle, failing with [`Error::Timeout`] if the lock is not acquired in time
+    pub fn adopt_timeout(&self, handle: &BufferHandle, timeout: Duration) -> Result<BufferGuard> {
+        self.adopt_with(handle, LockWait::Timeout(timeout))
+    }
+
+    fn adopt_with(&self, handle: &BufferHandle, wait: LockWait) -> Result<BufferGuard> {
+        if handle.pool_name != self.name() {
+            return Err(Error::BufferNotFound(handle.meta_index));
+        }
+        let meta = self.meta_region().get(handle.meta_index)?;
+        let actual = meta.generation.load(Ordering::SeqCst);
+        if actual != handle.generation || meta.ref_count.load(Ordering::SeqCst) <= 0 {
+            return Err(Error::StaleHandle {
+                index: handle.meta_index,
+                expected: handle.generation,
+                actual,
+            });
+        }
+        self.wrap_locked(handle.meta_index, handle.mode, wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_name() -> String {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        format!("/xmem_handle_test_{}", ts)
+    }
+
+    #[test]
+    fn test_handle_roundtrip() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let buf = pool.acquire_cpu(8).unwrap();
+        let idx = buf.meta_index();
+        let handle = buf.into_handle().unwrap();
+        assert_eq!(handle.pool_name, name);
+        assert_eq!(pool.ref_count(idx).unwrap(), 1);
+
+        let other = BufferPool::open(&name).unwrap();
+        let adopted = other.adopt(&handle).unwrap();
+        assert_eq!(adopted.mode(), AccessMode::ReadWrite);
+        assert_eq!(pool.ref_count(idx).unwrap(), 1);
+
+        drop(adopted);
+        assert_eq!(pool.ref_count(idx).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_stale_handle() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let handle = pool.acquire_cpu(8).unwrap().into_handle().unwrap();
+        drop(pool.adopt(&handle).unwrap());
+
+        // Slot freed, then reused by a new buffer
+        assert!(matches!(pool.adopt(&handle), Err(Error::StaleHandle { .. })));
+        let reused = pool.acquire_cpu(8).unwrap();
+        assert_eq!(reused.meta_index(), handle.meta_index);
+        assert_eq!(reused.generation().unwrap(), handle.generation + 1);
+        assert!(matches!(pool.adopt(&handle), Err(Error::StaleHandle { .. })));
+
+        let other = BufferPool::create(&unique_name()).unwrap();
+        assert!(matches!(other.adopt(&handle), Err(Error::BufferNotFound(_))));
+    }
+
+    #[test]
+    fn test_adopt_takes_lock() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        let buf = pool.acquire_cpu(8).unwrap();
+        let idx = buf.meta_index();
+
+        // A writer's handle still excludes other writers once adopted
+        let handle = pool.get_mut(idx).unwrap().into_handle().unwrap();
+        let adopted = pool.adopt(&handle).unwrap();
+        assert!(matches!(pool.try_get_mut(idx), Err(Error::Locked(_))));
+        assert!(matches!(pool.try_get(idx), Err(Error::Locked(_))));
+
+        // Adopting while another writer holds the lock fails without taking the reference
+        let handle = adopted.into_handle().unwrap();
+        let writer = pool.get_mut(idx).unwrap();
+        assert!(matches!(
+            pool.adopt_timeout(&handle, Duration::from_millis(20)),
+            Err(Error::Timeout)
+        ));
+        assert_eq!(pool.ref_count(idx).unwrap(), 3);
+        drop(writer);
+        drop(pool.adopt(&handle).unwrap());
+        assert_eq!(pool.ref_count(idx).unwrap(), 1);
+        drop(buf);
+    }
+}
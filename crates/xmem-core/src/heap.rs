@@ -0,0 +1,120 @@
+//! 进程内堆存储
+//!
+//! [`BufferPool::in_memory`](crate::BufferPool::in_memory) 创建的池将元数据区和
+//! buffer 数据都放在本进程堆上，不创建 `/dev/shm` 段，适合单元测试：
+//! 池随最后一个句柄释放，同名的池互不干扰，测试 panic 也不会遗留共享内存。
+
+use crate::meta::BufferMeta;
+use crate::storage::{SegmentId, StorageBackend, StorageSegment, StorageType};
+use crate::{Error, Result};
+use std::alloc::{self, Layout};
+use std::collections::HashMap;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
+
+/// Alignment of heap blocks, enough for any element type and SIMD loads
+const HEAP_ALIGN: usize = 64;
+
+/// Zero-initialized heap block
+pub(crate) struct HeapMemory {
+    ptr: NonNull<u8>,
+    size: usize,
+}
+
+// Safety: like shared memory, the block is accessed through raw pointers
+// handed out to guards; the allocation itself is never moved
+unsafe impl Send for HeapMemory {}
+unsafe impl Sync for HeapMemory {}
+
+impl HeapMemory {
+    /// Allocate `size` zeroed bytes
+    pub(crate) fn new(size: usize) -> Result<Self> {
+        let layout = Self::layout(size)?;
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).ok_or_else(|| {
+            Error::SharedMemory(format!("failed to allocate {} bytes", size))
+        })?;
+        Ok(Self { ptr, size })
+    }
+
+    fn layout(size: usize) -> Result<Layout> {
+        // Zero-sized allocations are not allowed, round up to one byte
+        Layout::from_size_align(size.max(1), HEAP_ALIGN)
+            .map_err(|e| Error::SharedMemory(e.to_string()))
+    }
+}
+
+impl Drop for HeapMemory {
+    fn drop(&mut self) {
+        let layout = Self::layout(self.size).expect("layout was valid at allocation");
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), layout) };
+    }
+}
+
+impl StorageSegment for HeapMemory {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn cpu_ptr(&self) -> Option<*mut u8> {
+        Some(self.ptr.as_ptr())
+    }
+}
+
+/// Data segment sharing a heap block with other guards of the same buffer
+struct HeapSegment(Arc<HeapMemory>);
+
+impl StorageSegment for HeapSegment {
+    fn size(&self) -> usize {
+        self.0.size
+    }
+
+    fn cpu_ptr(&self) -> Option<*mut u8> {
+        self.0.cpu_ptr()
+    }
+}
+
+/// 堆存储后端，每个内存池持有独立实例（不注册到全局注册表）
+#[derive(Default)]
+pub(crate) struct HeapBackend {
+    blocks: Mutex<HashMap<u32, Arc<HeapMemory>>>,
+}
+
+impl HeapBackend {
+    fn blocks(&self) -> std::sync::MutexGuard<'_, HashMap<u32, Arc<HeapMemory>>> {
+        self.blocks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl StorageBackend for HeapBackend {
+    fn storage_type(&self) -> u8 {
+        StorageType::Heap as u8
+    }
+
+    fn name(&self) -> &str {
+        "Heap"
+    }
+
+    fn allocate(
+        &self,
+        id: SegmentId<'_>,
+        size: usize,
+        _device_id: u8,
+    ) -> Result<Box<dyn StorageSegment>> {
+        let block = Arc::new(HeapMemory::new(size)?);
+        self.blocks().insert(id.index, Arc::clone(&block));
+        Ok(Box::new(HeapSegment(block)))
+    }
+
+    fn import(&self, id: SegmentId<'_>, _meta: &BufferMeta) -> Result<Box<dyn StorageSegment>> {
+        let block = self.blocks().get(&id.index).cloned();
+        let block = block.ok_or(Error::BufferNotFound(id.index))?;
+        Ok(Box::new(HeapSegment(block)))
+    }
+
+    fn free(&self, id: SegmentId<'_>, _meta: &BufferMeta) -> Result<()> {
+        // Guards still holding the block keep it alive
+        self.blocks().remove(&id.index);
+        Ok(())
+    }
+}
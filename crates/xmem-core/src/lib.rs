@@ -31,10 +31,11 @@
 //!
 //! ## 架构
 //!
-//! - [`BufferPool`]: 管理共享内存缓冲池
+//! - [`BufferPool`](BufferPool): 管理共享内存缓冲池
 //! - [`BufferGuard`]: RAII 访问守卫
 //! - [`SharedMemory`]: POSIX 共享内存封装
-//! - [`BufferMeta`]: 缓冲区元数据
+//! - [`StorageBackend`]: 可注册的 buffer 存储后端（CPU 共享内存、CUDA 显存或自定义实现）
+//! - [`BufferMeta`](BufferMeta): 缓冲区元数据
 //!
 //! ## CUDA 支持
 //!
@@ -44,27 +45,60 @@
 //! [dependencies]
 //! xmem-core = { version = "0.1", features = ["cuda"] }
 //! ```
+//!
+//! ## ndarray 集成
+//!
+//! 启用 `ndarray` feature 后，可通过 [`BufferGuard::as_array_view`] 等方法
+//! 按元数据中的 shape/strides 零拷贝访问 buffer：
+//!
+//! ```toml
+//! [dependencies]
+//! xmem-core = { version = "0.1", features = ["ndarray"] }
+//! ```
 
+#[cfg(feature = "ndarray")]
+pub mod array;
 pub mod buffer;
+pub mod cache;
+pub mod config;
 #[cfg(feature = "cuda")]
 pub mod cuda;
 pub mod dtype;
 pub mod error;
+pub mod file;
 pub mod guard;
+pub mod handle;
+pub mod heap;
+pub mod lock;
+pub mod memory;
 pub mod meta;
 pub mod meta_region;
+pub mod notify;
 pub mod pool;
+pub mod process;
+pub mod seqlock;
 pub mod shm;
 pub mod storage;
 
+#[cfg(feature = "ndarray")]
+pub use array::Element;
 pub use buffer::BufferData;
+pub use config::{validate_name, PoolConfig, MAX_POOL_NAME_LEN};
 #[cfg(feature = "cuda")]
-pub use cuda::{CudaBuffer, CudaIpcHandle};
+pub use cuda::{CudaBackend, CudaBuffer, CudaIpcHandle};
 pub use dtype::DType;
 pub use error::{Error, Result};
+pub use file::MappedFile;
 pub use guard::BufferGuard;
-pub use meta::{BufferMeta, MAX_NDIM};
+pub use handle::BufferHandle;
+pub use memory::HugePages;
+pub use meta::{BufferMeta, TensorDesc, TensorLayout, LABEL_SIZE, MAX_NDIM};
 pub use meta_region::MetaRegion;
-pub use pool::BufferPool;
-pub use shm::SharedMemory;
-pub use storage::{AccessMode, StorageType};
+pub use notify::Notifier;
+pub use pool::{BufferPool, FsckReport, TrimReport};
+pub use seqlock::SeqWriteGuard;
+pub use shm::{ShmBackend, SharedMemory};
+pub use storage::{
+    register_backend, AccessMode, SegmentId, StorageBackend, StorageSegment, StorageType,
+    FIRST_CUSTOM_STORAGE_TYPE,
+};
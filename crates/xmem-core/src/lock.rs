@@ -0,0 +1,363 @@
+//! 跨进程读写锁
+//!
+//! 每个 buffer 的锁状态保存在 [`BufferMeta`] 中，所有进程共享。持有者以其在
+//! [进程登记表](crate::process)中的槽位号记录，而不是 pid：
+//!
+//! - `lock_state`：持有写锁的进程槽位号 + 1（0 表示无写者）
+//! - `lock_readers`：读者位图，第 n 位表示槽位 n 的进程持有读锁
+//!
+//! 同一进程对同一 buffer 的多个读锁共用一位，计数保存在进程内。
+//! 读者进程数不设单独上限，最多为池的登记表容量 [`MAX_PROCESSES`]。
+//! 读者先置位再检查写者，写者先写入 `lock_state` 再检查位图，
+//! 两者同时进入时都会退出并重试，不会同时持有。
+//!
+//! 持锁进程崩溃后，其他进程在获取锁失败时会检查持有者是否存活，
+//! 并回收已死亡进程的写锁和读锁，存活检查见 [`crate::process`]。
+//!
+//! [`MAX_PROCESSES`]: crate::process::MAX_PROCESSES
+
+use crate::meta::BufferMeta;
+use crate::process::{Holder, Registration};
+use crate::{Error, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Number of 64-bit words in the reader bitmap of [`BufferMeta`]
+pub const LOCK_READER_WORDS: usize = 3;
+
+/// Lock kind held by a guard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockKind {
+    Read,
+    Write,
+}
+
+/// How long to wait for a lock
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum LockWait {
+    /// Fail immediately with [`Error::Locked`]
+    Try,
+    /// Wait indefinitely
+    Block,
+    /// Wait up to the given duration, then fail with [`Error::Timeout`]
+    Timeout(Duration),
+}
+
+/// Reset lock state for a freshly allocated slot
+pub(crate) fn reset(meta: &BufferMeta) {
+    for word in &meta.lock_readers {
+        word.store(0, Ordering::SeqCst);
+    }
+    meta.lock_state.store(0, Ordering::SeqCst);
+}
+
+/// Release locks held by dead processes, returns whether anything was released
+pub(crate) fn recover(holder: &Holder<'_>, meta: &BufferMeta) -> bool {
+    let mut recovered = false;
+    let state = meta.lock_state.load(Ordering::SeqCst);
+    if state != 0 && !holder.alive(writer_slot(state)) {
+        recovered |= holder.reclaim(writer_slot(state), || {
+            cas(&meta.lock_state, state, 0);
+        });
+    }
+    for slot in readers(meta) {
+        if !holder.alive(slot) {
+            recovered |= holder.reclaim(slot, || clear_reader(meta, slot));
+        }
+    }
+    recovered
+}
+
+/// Drop every lock recorded for `slot`, before a new process registers it
+pub(crate) fn forget_slot(meta: &BufferMeta, slot: u32) {
+    clear_reader(meta, slot);
+    cas(&meta.lock_state, slot as u64 + 1, 0);
+}
+
+/// Acquire a lock, waiting according to `wait`
+pub(crate) fn lock(
+    holder: &Holder<'_>,
+    meta: &BufferMeta,
+    index: u32,
+    kind: LockKind,
+    wait: LockWait,
+) -> Result<()> {
+    let deadline = match wait {
+        LockWait::Timeout(timeout) => Some(Instant::now() + timeout),
+        _ => None,
+    };
+
+    let mut attempts = 0u32;
+    loop {
+        if try_lock(holder, meta, kind) {
+            return Ok(());
+        }
+        match wait {
+            LockWait::Try => return Err(Error::Locked(index)),
+            _ if deadline.is_some_and(|d| Instant::now() >= d) => return Err(Error::Timeout),
+            _ => {}
+        }
+
+        attempts = attempts.saturating_add(1);
+        if attempts < 64 {
+            std::thread::yield_now();
+        } else {
+            std::thread::sleep(Duration::from_micros(100));
+        }
+    }
+}
+
+/// Try to acquire a lock once, recovering locks held by dead processes
+pub(crate) fn try_lock(holder: &Holder<'_>, meta: &BufferMeta, kind: LockKind) -> bool {
+    match kind {
+        LockKind::Read => try_read(holder, meta),
+        LockKind::Write => try_write(holder, meta),
+    }
+}
+
+fn try_read(holder: &Holder<'_>, meta: &BufferMeta) -> bool {
+    let slot = holder.slot();
+    let key = meta.id.load(Ordering::SeqCst);
+    let mut counts = holder.registration().readers.lock().unwrap_or_else(|e| e.into_inner());
+    // This process already holds the bit, so no writer can be in
+    if let Some(count) = counts.get_mut(&key) {
+        *count += 1;
+        return true;
+    }
+    loop {
+        let state = meta.lock_state.load(Ordering::SeqCst);
+        if state != 0 {
+            if !reclaim_writer(holder, meta, state) {
+                return false;
+            }
+            continue;
+        }
+        set_reader(meta, slot);
+        // A writer that got in meanwhile wins; it backs out if it sees our bit first
+        if meta.lock_state.load(Ordering::SeqCst) != 0 {
+            clear_reader(meta, slot);
+            continue;
+        }
+        counts.insert(key, 1);
+        return true;
+    }
+}
+
+fn try_write(holder: &Holder<'_>, meta: &BufferMeta) -> bool {
+    let slot = holder.slot();
+    loop {
+        let state = meta.lock_state.load(Ordering::SeqCst);
+        if state != 0 {
+            if !reclaim_writer(holder, meta, state) {
+                return false;
+            }
+            continue;
+        }
+        // Live readers (this process included) keep the writer out
+        for reader in readers(meta) {
+            if holder.alive(reader) || !holder.reclaim(reader, || clear_reader(meta, reader)) {
+                return false;
+            }
+        }
+        if !cas(&meta.lock_state, 0, slot as u64 + 1) {
+            continue;
+        }
+        // A reader that set its bit meanwhile wins; the next round decides
+        if readers(meta).next().is_some() {
+            cas(&meta.lock_state, slot as u64 + 1, 0);
+            continue;
+        }
+        return true;
+    }
+}
+
+/// Release a lock taken with [`lock`] by this registration
+///
+/// fork 出的子进程继承的锁属于父进程，子进程中的释放不生效。
+pub(crate) fn unlock(reg: &Registration, meta: &BufferMeta, kind: LockKind) {
+    if !reg.is_current() {
+        return;
+    }
+    match kind {
+        LockKind::Read => {
+            let key = meta.id.load(Ordering::SeqCst);
+            let mut counts = reg.readers.lock().unwrap_or_else(|e| e.into_inner());
+            match counts.get_mut(&key) {
+                Some(count) if *count > 1 => *count -= 1,
+                Some(_) => {
+                    counts.remove(&key);
+                    clear_reader(meta, reg.slot());
+                }
+                None => {}
+            }
+        }
+        LockKind::Write => {
+            cas(&meta.lock_state, reg.slot() as u64 + 1, 0);
+        }
+    }
+}
+
+fn writer_slot(state: u64) -> u32 {
+    (state - 1).min(u32::MAX as u64) as u32
+}
+
+/// Clear the write lock `state` if its holder is dead, returns whether it was cleared
+fn reclaim_writer(holder: &Holder<'_>, meta: &BufferMeta, state: u64) -> bool {
+    let writer = writer_slot(state);
+    !holder.alive(writer)
+        && holder.reclaim(writer, || {
+            cas(&meta.lock_state, state, 0);
+        })
+}
+
+/// Slots whose bit is set in the reader bitmap
+fn readers(meta: &BufferMeta) -> impl Iterator<Item = u32> + '_ {
+    (0u32..).zip(&meta.lock_readers).flat_map(|(word, bits)| {
+        let bits = bits.load(Ordering::SeqCst);
+        (0..64).filter(move |bit| bits & (1 << bit) != 0).map(move |bit| word * 64 + bit)
+    })
+}
+
+fn set_reader(meta: &BufferMeta, slot: u32) {
+    let (word, bit) = (slot as usize / 64, slot % 64);
+    meta.lock_readers[word].fetch_or(1 << bit, Ordering::SeqCst);
+}
+
+fn clear_reader(meta: &BufferMeta, slot: u32) {
+    let (word, bit) = (slot as usize / 64, slot % 64);
+    if let Some(word) = meta.lock_readers.get(word) {
+        word.fetch_and(!(1 << bit), Ordering::SeqCst);
+    }
+}
+
+fn cas(word: &AtomicU64, current: u64, new: u64) -> bool {
+    word.compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta_region::MetaRegion;
+    use crate::process::tests::other_process;
+    use crate::process::MAX_PROCESSES;
+
+    fn unique_name() -> String {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        format!("/xmem_lock_{}", ts)
+    }
+
+    /// A region with slot 0 allocated
+    fn new_region() -> MetaRegion {
+        let region = MetaRegion::create(&unique_name(), 4).unwrap();
+        assert_eq!(region.alloc().unwrap(), 0);
+        region
+    }
+
+    #[test]
+    fn test_read_write_exclusion() {
+        let region = new_region();
+        let meta = region.get(0).unwrap();
+        let holder = region.holder().unwrap();
+        let reg = holder.registration();
+
+        assert!(try_lock(&holder, meta, LockKind::Read));
+        assert!(try_lock(&holder, meta, LockKind::Read));
+        assert!(!try_lock(&holder, meta, LockKind::Write));
+
+        unlock(reg, meta, LockKind::Read);
+        assert!(!try_lock(&holder, meta, LockKind::Write));
+        unlock(reg, meta, LockKind::Read);
+        assert_eq!(meta.lock_state.load(Ordering::SeqCst), 0);
+        assert!(meta.lock_readers.iter().all(|w| w.load(Ordering::SeqCst) == 0));
+
+        assert!(try_lock(&holder, meta, LockKind::Write));
+        assert!(!try_lock(&holder, meta, LockKind::Read));
+        assert!(!try_lock(&holder, meta, LockKind::Write));
+        unlock(reg, meta, LockKind::Write);
+        assert!(try_lock(&holder, meta, LockKind::Read));
+    }
+
+    #[test]
+    fn test_lock_timeout() {
+        let region = new_region();
+        let meta = region.get(0).unwrap();
+        let holder = region.holder().unwrap();
+        assert!(try_lock(&holder, meta, LockKind::Write));
+
+        let start = Instant::now();
+        let res = lock(&holder, meta, 0, LockKind::Read, LockWait::Timeout(Duration::from_millis(20)));
+        assert!(matches!(res, Err(Error::Timeout)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        assert!(matches!(
+            lock(&holder, meta, 7, LockKind::Write, LockWait::Try),
+            Err(Error::Locked(7))
+        ));
+    }
+
+    #[test]
+    fn test_many_reader_processes() {
+        let region = new_region();
+        let meta = region.get(0).unwrap();
+        let holder = region.holder().unwrap();
+
+        // Sixteen reader processes at once, no per-buffer cap
+        let others: Vec<_> = (0..16).map(|_| other_process(&region)).collect();
+        for other in &others {
+            let other = Holder::new(other.clone(), region.processes());
+            assert!(try_lock(&other, meta, LockKind::Read));
+        }
+        assert!(try_lock(&holder, meta, LockKind::Read));
+        assert_eq!(readers(meta).count(), 17);
+        assert!(!try_lock(&holder, meta, LockKind::Write));
+
+        for other in &others {
+            unlock(other, meta, LockKind::Read);
+        }
+        unlock(holder.registration(), meta, LockKind::Read);
+        assert!(try_lock(&holder, meta, LockKind::Write));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_recover_dead_holders() {
+        let region = new_region();
+        let meta = region.get(0).unwrap();
+        let holder = region.holder().unwrap();
+
+        // Writer died holding the lock
+        let writer = other_process(&region);
+        assert!(try_lock(&Holder::new(writer.clone(), region.processes()), meta, LockKind::Write));
+        assert!(!try_lock(&holder, meta, LockKind::Read));
+        drop(writer);
+        assert!(try_lock(&holder, meta, LockKind::Read));
+        unlock(holder.registration(), meta, LockKind::Read);
+
+        // A dead reader and a live one; recover() only drops the dead one
+        let dead = other_process(&region);
+        let live = other_process(&region);
+        for reg in [&dead, &live] {
+            assert!(try_lock(&Holder::new(reg.clone(), region.processes()), meta, LockKind::Read));
+        }
+        let dead_slot = dead.slot();
+        drop(dead);
+        assert!(recover(&holder, meta));
+        assert!(!recover(&holder, meta));
+        assert_eq!(readers(meta).collect::<Vec<_>>(), vec![live.slot()]);
+        assert!(!try_lock(&holder, meta, LockKind::Write));
+
+        // Out-of-range slots from a corrupted word are never alive
+        unlock(&live, meta, LockKind::Read);
+        meta.lock_state.store(MAX_PROCESSES as u64 + 1, Ordering::SeqCst);
+        assert!(try_lock(&holder, meta, LockKind::Write));
+        unlock(holder.registration(), meta, LockKind::Write);
+
+        // A new process in the dead slot starts without the old locks
+        set_reader(meta, dead_slot);
+        let next = other_process(&region);
+        assert_eq!(next.slot(), dead_slot);
+        assert_eq!(readers(meta).count(), 0);
+    }
+}
@@ -0,0 +1,427 @@
+//! 大页、预缺页、内存锁定与 NUMA 放置
+//!
+//! 大帧（如 4K 图像，每帧约 24MB）使用 4KB 页时 TLB 压力大，首次写入还会触发大量缺页。
+//! [`PoolConfig::huge_pages`](crate::PoolConfig::huge_pages) 和
+//! [`PoolConfig::prefault`](crate::PoolConfig::prefault) 可缓解这两点：
+//!
+//! - [`HugePages::HugeTlb`]: `backing_dir` 位于 hugetlbfs 挂载点（如 `/dev/hugepages`）时，
+//!   段为 hugetlbfs 文件，大小向上取整到大页大小
+//! - [`HugePages::Transparent`]: 对每个映射调用 `madvise(MADV_HUGEPAGE)`，
+//!   需要内核开启 tmpfs 透明大页（`/sys/kernel/mm/transparent_hugepage/shmem_enabled`）
+//! - prefault: 分配 buffer 时预先建立页表（`MADV_POPULATE_WRITE`，旧内核逐页写入）
+//!
+//! 不满足条件时依次回退（hugetlbfs → 透明大页 → 普通页），
+//! 实际生效的模式由 [`BufferPool::huge_pages`](crate::BufferPool::huge_pages) 报告。
+//! 大页只在 Linux 上可用，其他平台总是回退到普通页；prefault 在其他平台逐页写入。
+//!
+//! 对延迟敏感的场景还可以：
+//!
+//! - [`PoolConfig::mlock`](crate::PoolConfig::mlock): 用 `mlock` 锁定 buffer 映射，避免被换出。
+//!   锁定随映射存在，分配和打开 buffer 的进程都会锁定自己的映射，
+//!   受 `RLIMIT_MEMLOCK` 限制（`ulimit -l`）
+//! - [`PoolConfig::numa_node`](crate::PoolConfig::numa_node): 分配 buffer 时以
+//!   `set_mempolicy(MPOL_BIND)` 将页面分配在指定节点上，并对映射调用 `mbind`；
+//!   节点记录在 [`BufferMeta::numa_node`](crate::BufferMeta::numa_node)，消费者可据此就近调度。
+//!   单节点机器或内核不支持 NUMA 时不做任何操作，
+//!   [`BufferPool::numa_node`](crate::BufferPool::numa_node) 返回 `None`；
+//!   其他平台同样不绑定
+
+#[cfg(target_os = "linux")]
+use crate::meta::NUMA_NODE_NONE;
+#[cfg(target_os = "linux")]
+use crate::shm::SHM_DIR;
+#[cfg(target_os = "linux")]
+use std::ffi::CString;
+#[cfg(target_os = "linux")]
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Huge page mode of a pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HugePages {
+    /// Regular pages
+    #[default]
+    Off,
+    /// Transparent huge pages via `madvise(MADV_HUGEPAGE)`
+    Transparent,
+    /// Segments are files on a hugetlbfs `backing_dir`
+    HugeTlb,
+}
+
+/// Sysfs switch for transparent huge pages on tmpfs / shared memory
+#[cfg(target_os = "linux")]
+const THP_SHMEM_ENABLED: &str = "/sys/kernel/mm/transparent_hugepage/shmem_enabled";
+
+/// Filesystem magic and block size of the filesystem holding `path`
+///
+/// `f_type` differs in width between targets; the magics all fit in 32 bits.
+#[cfg(target_os = "linux")]
+fn statfs(path: &Path) -> Option<(u32, usize)> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some((stat.f_type as u32, stat.f_bsize as usize))
+}
+
+/// Huge page size if `dir` is on hugetlbfs
+#[cfg(target_os = "linux")]
+pub(crate) fn hugetlb_page_size(dir: &Path) -> Option<usize> {
+    match statfs(dir)? {
+        (fs, page_size) if fs == libc::HUGETLBFS_MAGIC as u32 => Some(page_size),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn hugetlb_page_size(_dir: &Path) -> Option<usize> {
+    None
+}
+
+/// Check if the kernel backs tmpfs mappings with huge pages on `madvise`
+#[cfg(target_os = "linux")]
+fn thp_shmem_enabled() -> bool {
+    let Ok(setting) = std::fs::read_to_string(THP_SHMEM_ENABLED) else {
+        return false;
+    };
+    // The active value is bracketed: "always within_size advise [never] deny force"
+    let active = setting
+        .split_whitespace()
+        .find_map(|v| v.strip_prefix('[')?.strip_suffix(']'));
+    matches!(active, Some("always" | "within_size" | "advise" | "force"))
+}
+
+/// 按环境确定实际生效的大页模式
+///
+/// `dir` 为段所在目录，`None` 表示 `/dev/shm`。
+#[cfg(target_os = "linux")]
+pub(crate) fn resolve(requested: HugePages, dir: Option<&Path>) -> HugePages {
+    let dir = dir.unwrap_or(Path::new(SHM_DIR));
+    if requested == HugePages::HugeTlb && hugetlb_page_size(dir).is_some() {
+        return HugePages::HugeTlb;
+    }
+    let on_tmpfs = statfs(dir).is_some_and(|(fs, _)| fs == libc::TMPFS_MAGIC as u32);
+    if requested != HugePages::Off && on_tmpfs && thp_shmem_enabled() {
+        return HugePages::Transparent;
+    }
+    HugePages::Off
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn resolve(_requested: HugePages, _dir: Option<&Path>) -> HugePages {
+    HugePages::Off
+}
+
+/// Apply the huge page mode to a mapping (hugetlbfs mappings need nothing)
+#[cfg(target_os = "linux")]
+pub(crate) fn advise(ptr: *mut u8, len: usize, mode: HugePages) {
+    if mode == HugePages::Transparent && len > 0 {
+        // Best effort: a failure only means regular pages
+        unsafe { libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_HUGEPAGE) };
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn advise(_ptr: *mut u8, _len: usize, _mode: HugePages) {}
+
+/// 为新分配、内容为零的映射预先建立页表
+///
+/// 只能用于刚分配的 buffer：旧内核上的回退路径会逐页写入 0。
+pub(crate) fn prefault(ptr: *mut u8, len: usize) {
+    if len == 0 {
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    if unsafe { libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_POPULATE_WRITE) } == 0 {
+        return;
+    }
+    // Before Linux 5.14, and elsewhere: touch every page
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    for offset in (0..len).step_by(page) {
+        unsafe { std::ptr::write_volatile(ptr.add(offset), 0) };
+    }
+}
+
+/// 释放映射背后的物理页，返回是否释放成功
+///
+/// 共享映射（共享内存、tmpfs / hugetlbfs 及支持打洞的文件）用 `MADV_REMOVE`
+/// 释放底层页面，之后读到零；私有内存（`shared` 为 false）对整页部分用 `MADV_DONTNEED`。
+/// 其他平台不释放，返回 false。
+#[cfg(target_os = "linux")]
+pub(crate) fn release(ptr: *mut u8, len: usize, shared: bool) -> bool {
+    if len == 0 {
+        return true;
+    }
+    if shared {
+        return unsafe { libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_REMOVE) } == 0;
+    }
+    // Heap blocks need not start on a page boundary
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (ptr as usize).next_multiple_of(page);
+    let end = (ptr as usize + len) / page * page;
+    end > start
+        && unsafe { libc::madvise(start as *mut libc::c_void, end - start, libc::MADV_DONTNEED) }
+            == 0
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn release(_ptr: *mut u8, _len: usize, _shared: bool) -> bool {
+    false
+}
+
+/// 锁定映射，使其页面常驻内存
+///
+/// 失败通常是超出了 `RLIMIT_MEMLOCK`。
+pub(crate) fn lock(ptr: *mut u8, len: usize) -> std::io::Result<()> {
+    if len > 0 && unsafe { libc::mlock(ptr as *const libc::c_void, len) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// List of online NUMA nodes
+const NODE_ONLINE: &str = "/sys/devices/system/node/online";
+
+/// Bits in a [`NodeMask`]
+#[cfg(target_os = "linux")]
+const MAX_NODES: usize = 1024;
+
+/// `MPOL_DEFAULT` (not exported by libc)
+#[cfg(target_os = "linux")]
+const MPOL_DEFAULT: libc::c_int = 0;
+
+/// Node mask as passed to the mempolicy syscalls
+#[cfg(target_os = "linux")]
+type NodeMask = [libc::c_ulong; MAX_NODES / libc::c_ulong::BITS as usize];
+
+/// Parse a node list such as `"0"` or `"0-1,4"`
+fn parse_node_list(list: &str) -> Vec<u32> {
+    let mut nodes = Vec::new();
+    for range in list.trim().split(',').filter(|r| !r.is_empty()) {
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        if let (Ok(first), Ok(last)) = (first.parse::<u32>(), last.parse::<u32>()) {
+            nodes.extend(first..=last);
+        }
+    }
+    nodes
+}
+
+/// Online NUMA nodes, empty if the kernel has no NUMA support
+pub fn online_nodes() -> Vec<u32> {
+    std::fs::read_to_string(NODE_ONLINE)
+        .map(|list| parse_node_list(&list))
+        .unwrap_or_default()
+}
+
+/// 确定实际绑定的 NUMA 节点
+///
+/// 只有多节点机器上存在的节点才会绑定；单节点机器、内核不支持 mempolicy
+/// 或节点号无法记录在 [`BufferMeta::numa_node`](crate::BufferMeta::numa_node) 时返回 `None`。
+#[cfg(target_os = "linux")]
+pub(crate) fn resolve_node(requested: Option<u32>) -> Option<u32> {
+    let node = requested?;
+    let nodes = online_nodes();
+    let supported = unsafe {
+        libc::syscall(
+            libc::SYS_get_mempolicy,
+            std::ptr::null_mut::<libc::c_int>(),
+            std::ptr::null_mut::<libc::c_ulong>(),
+            0,
+            std::ptr::null_mut::<libc::c_void>(),
+            0,
+        )
+    } == 0;
+    let usable = nodes.len() > 1 && nodes.contains(&node) && node < NUMA_NODE_NONE as u32;
+    (supported && usable).then_some(node)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn resolve_node(_requested: Option<u32>) -> Option<u32> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn node_mask(node: u32) -> NodeMask {
+    let mut mask: NodeMask = [0; MAX_NODES / libc::c_ulong::BITS as usize];
+    let bits = libc::c_ulong::BITS;
+    mask[(node / bits) as usize] |= 1 << (node % bits);
+    mask
+}
+
+/// 在当前线程的内存策略绑定到 `node` 的情况下执行 `f`，之后恢复原策略
+///
+/// `f` 中分配的页面（如 `posix_fallocate` 预留的共享内存）位于该节点。
+#[cfg(target_os = "linux")]
+pub(crate) fn with_node<T>(node: Option<u32>, f: impl FnOnce() -> T) -> T {
+    let Some(node) = node else {
+        return f();
+    };
+    // The kernel reads one bit less than maxnode
+    let maxnode = MAX_NODES as libc::c_ulong + 1;
+    let mut old_mode: libc::c_int = MPOL_DEFAULT;
+    let mut old_mask: NodeMask = [0; MAX_NODES / libc::c_ulong::BITS as usize];
+    let saved = unsafe {
+        libc::syscall(
+            libc::SYS_get_mempolicy,
+            &mut old_mode,
+            old_mask.as_mut_ptr(),
+            maxnode,
+            std::ptr::null_mut::<libc::c_void>(),
+            0,
+        )
+    } == 0;
+    let mask = node_mask(node);
+    let bound = saved
+        && unsafe { libc::syscall(libc::SYS_set_mempolicy, libc::MPOL_BIND, mask.as_ptr(), maxnode) }
+            == 0;
+
+    let result = f();
+
+    if bound {
+        let old_mask = match old_mode {
+            MPOL_DEFAULT => std::ptr::null(),
+            _ => old_mask.as_ptr(),
+        };
+        unsafe { libc::syscall(libc::SYS_set_mempolicy, old_mode, old_mask, maxnode) };
+    }
+    result
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn with_node<T>(_node: Option<u32>, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+/// 将映射的内存策略绑定到 `node`，之后缺页分配的页面（如透明大页）也位于该节点
+#[cfg(target_os = "linux")]
+pub(crate) fn bind(ptr: *mut u8, len: usize, node: Option<u32>) {
+    let Some(node) = node else {
+        return;
+    };
+    if len == 0 {
+        return;
+    }
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = ptr as usize & !(page - 1);
+    let mask = node_mask(node);
+    // Best effort: pages already allocated stay where they are
+    unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            start,
+            len + (ptr as usize - start),
+            libc::MPOL_BIND,
+            mask.as_ptr(),
+            MAX_NODES as libc::c_ulong + 1,
+            0,
+        )
+    };
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn bind(_ptr: *mut u8, _len: usize, _node: Option<u32>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_resolve_fallback() {
+        // The temp dir is not hugetlbfs, so huge TLB pages fall back
+        let dir = std::env::temp_dir();
+        assert_ne!(resolve(HugePages::HugeTlb, Some(&dir)), HugePages::HugeTlb);
+        assert_eq!(resolve(HugePages::Off, None), HugePages::Off);
+
+        let expected = if thp_shmem_enabled() {
+            HugePages::Transparent
+        } else {
+            HugePages::Off
+        };
+        assert_eq!(resolve(HugePages::Transparent, None), expected);
+        assert_eq!(resolve(HugePages::HugeTlb, None), expected);
+    }
+
+    #[test]
+    fn test_prefault() {
+        let len = 3 * 4096;
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(ptr, libc::MAP_FAILED);
+        prefault(ptr as *mut u8, len);
+
+        // All pages are resident afterwards
+        let mut resident = [0u8; 3];
+        assert_eq!(unsafe { libc::mincore(ptr, len, resident.as_mut_ptr()) }, 0);
+        assert!(resident.iter().all(|&r| r & 1 == 1));
+        unsafe { libc::munmap(ptr, len) };
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_release() {
+        let len = 2 * 4096;
+        for flags in [libc::MAP_SHARED, libc::MAP_PRIVATE] {
+            let ptr = unsafe {
+                libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    flags | libc::MAP_ANONYMOUS,
+                    -1,
+                    0,
+                )
+            };
+            assert_ne!(ptr, libc::MAP_FAILED);
+            let data = ptr as *mut u8;
+            unsafe { std::ptr::write_bytes(data, 7, len) };
+
+            // Shared memory is removed, private memory dropped: both read back as zero
+            assert!(release(data, len, flags == libc::MAP_SHARED));
+            let mut resident = [0u8; 2];
+            assert_eq!(unsafe { libc::mincore(ptr, len, resident.as_mut_ptr()) }, 0);
+            assert!(resident.iter().all(|&r| r & 1 == 0));
+            assert_eq!(unsafe { *data.add(4096) }, 0);
+            unsafe { libc::munmap(ptr, len) };
+        }
+    }
+
+    #[test]
+    fn test_parse_node_list() {
+        assert_eq!(parse_node_list("0\n"), vec![0]);
+        assert_eq!(parse_node_list("0-2,5"), vec![0, 1, 2, 5]);
+        assert!(parse_node_list("").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_node() {
+        assert_eq!(resolve_node(None), None);
+        assert_eq!(resolve_node(Some(crate::meta::NUMA_NODE_NONE as u32)), None);
+        // Binding is a no-op unless the machine has several nodes
+        let nodes = online_nodes();
+        if nodes.len() <= 1 {
+            assert_eq!(resolve_node(Some(0)), None);
+        }
+
+        // Without a binding the closure just runs
+        assert_eq!(with_node(resolve_node(Some(0)), || 7), 7);
+    }
+
+    #[test]
+    fn test_lock() {
+        let mut data = vec![0u8; 4096];
+        // RLIMIT_MEMLOCK is at least 64KB on Linux unless lowered explicitly
+        lock(data.as_mut_ptr(), data.len()).unwrap();
+        assert!(lock(data.as_mut_ptr(), 0).is_ok());
+        unsafe { libc::munlock(data.as_ptr() as *const libc::c_void, data.len()) };
+    }
+}
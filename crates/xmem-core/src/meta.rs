@@ -1,23 +1,174 @@
 //! Buffer metadata structure
 
-use std::sync::atomic::{AtomicI32, AtomicU8, AtomicU32, AtomicU64};
+use crate::dtype::DType;
+use crate::lock::LOCK_READER_WORDS;
+use crate::{Error, Result};
+use std::sync::atomic::{AtomicI32, AtomicU8, AtomicU32, AtomicU64, Ordering};
 
 /// Maximum number of dimensions
 pub const MAX_NDIM: usize = 8;
 
+/// [`BufferMeta::flags`] bit: buffer is in seqlock overwrite mode, see [`crate::seqlock`]
+pub const FLAG_SEQLOCK: u8 = 1 << 0;
+
+/// [`BufferMeta::flags`] bit: buffer is published and waiting to be received
+pub const FLAG_PUBLISHED: u8 = 1 << 1;
+
+/// [`BufferMeta::cache`] bit: the free slot still holds its data segment, see [`crate::cache`]
+pub const CACHE_PRESENT: u64 = 1 << 0;
+
+/// [`BufferMeta::cache`] bit: the cached segment's pages were released by a trim
+pub const CACHE_TRIMMED: u64 = 1 << 1;
+
+/// [`BufferMeta::numa_node`] value of a buffer not bound to a NUMA node
+pub const NUMA_NODE_NONE: u8 = u8::MAX;
+
+/// Size of the [`BufferMeta::content_type`] / [`BufferMeta::producer`] label fields
+pub const LABEL_SIZE: usize = 32;
+
 /// CUDA IPC handle size (预留，即使 CPU-only 也保留以保证跨进程兼容性)
 pub const CUDA_IPC_HANDLE_SIZE: usize = 64;
 
+/// 张量描述（shape + dtype），用于创建 buffer 时写入元数据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorDesc {
+    /// Shape (at most [`MAX_NDIM`] dimensions)
+    pub shape: Vec<usize>,
+    /// Element data type
+    pub dtype: DType,
+}
+
+impl TensorDesc {
+    /// Create a tensor description
+    pub fn new(shape: impl Into<Vec<usize>>, dtype: DType) -> Self {
+        Self {
+            shape: shape.into(),
+            dtype,
+        }
+    }
+
+    /// Total size in bytes (`None` on overflow)
+    pub fn nbytes(&self) -> Option<usize> {
+        self.shape
+            .iter()
+            .try_fold(self.dtype.size(), |acc, &d| acc.checked_mul(d))
+    }
+}
+
+/// 经过校验的张量布局（strides 以元素为单位）
+///
+/// 元数据位于共享内存，可能被其他进程写入任意值，
+/// 按布局访问数据前须用 [`TensorLayout::from_meta`] 校验。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorLayout {
+    /// Element data type
+    pub dtype: DType,
+    /// Shape
+    pub shape: Vec<usize>,
+    /// Strides in elements
+    pub strides: Vec<usize>,
+}
+
+impl TensorLayout {
+    /// 从元数据读取并校验 `size` 字节 buffer 的布局
+    ///
+    /// - 必须记录了 dtype 和 shape，各维度不超过 `isize::MAX`
+    /// - 字节 strides 必须是元素大小的整数倍，且不超过 `isize::MAX`
+    /// - 所有元素必须落在 buffer 范围内（计算溢出同样视为越界）
+    /// - `exclusive` 为 true 时，不同索引不得指向同一元素
+    ///
+    /// 校验失败返回 [`Error::InvalidShape`]。
+    pub fn from_meta(meta: &BufferMeta, size: usize, exclusive: bool) -> Result<Self> {
+        let Some(dtype) = meta.dtype() else {
+            return Err(Error::InvalidShape("buffer has no dtype metadata".to_string()));
+        };
+        let shape = meta.shape();
+        if shape.is_empty() {
+            return Err(Error::InvalidShape("buffer has no shape metadata".to_string()));
+        }
+        if let Some(d) = shape.iter().find(|&&d| d > isize::MAX as usize) {
+            return Err(Error::InvalidShape(format!("dimension {} is too large", d)));
+        }
+
+        let elem_size = dtype.size();
+        let strides = meta
+            .strides()
+            .into_iter()
+            .map(|s| match s % elem_size {
+                0 if s <= isize::MAX as usize => Ok(s / elem_size),
+                0 => Err(Error::InvalidShape(format!("byte stride {} is too large", s))),
+                _ => Err(Error::InvalidShape(format!(
+                    "byte stride {} is not a multiple of element size {}",
+                    s, elem_size
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Bytes spanned from the first to one past the last element
+        if !shape.contains(&0) {
+            let last = shape
+                .iter()
+                .zip(&strides)
+                .try_fold(0usize, |acc, (&d, &s)| {
+                    (d - 1).checked_mul(s).and_then(|o| acc.checked_add(o))
+                })
+                .and_then(|o| o.checked_add(1))
+                .and_then(|n| n.checked_mul(elem_size));
+            match last {
+                Some(end) if end <= size => {}
+                _ => {
+                    return Err(Error::InvalidShape(format!(
+                        "shape {:?} with strides {:?} exceeds buffer size {}",
+                        shape,
+                        meta.strides(),
+                        size
+                    )))
+                }
+            }
+        }
+
+        if exclusive && !Self::is_non_overlapping(&shape, &strides) {
+            return Err(Error::InvalidShape(format!(
+                "strides {:?} alias elements, cannot create mutable view",
+                meta.strides()
+            )));
+        }
+
+        Ok(Self { dtype, shape, strides })
+    }
+
+    /// 检查布局是否无重叠（每个索引对应唯一元素）
+    fn is_non_overlapping(shape: &[usize], strides: &[usize]) -> bool {
+        let mut axes: Vec<(usize, usize)> = shape
+            .iter()
+            .copied()
+            .zip(strides.iter().copied())
+            .filter(|&(d, _)| d > 1)
+            .collect();
+        axes.sort_by_key(|&(_, s)| s);
+
+        let mut extent = 1;
+        for (d, s) in axes {
+            if s < extent {
+                return false;
+            }
+            extent = s * d;
+        }
+        true
+    }
+}
+
 /// Buffer metadata stored in shared memory
 ///
-/// 所有字段使用原子类型，支持跨进程并发访问
+/// 所有字段使用原子类型，支持跨进程并发访问。
+/// C 语言描述见 `include/xmem_layout.h`，修改布局时需同步更新并提升 region 版本号。
 #[repr(C)]
 pub struct BufferMeta {
     /// Unique buffer ID
     pub id: AtomicU32,
     /// Reference count (atomic)
     pub ref_count: AtomicI32,
-    /// Storage type: 0=cpu, 1=cuda
+    /// Storage type id: 0=cpu, 1=cuda, 2=view, 3=heap, custom backends from 128 (see [`crate::storage`])
     pub storage_type: AtomicU8,
     /// GPU device ID (for CUDA)
     pub device_id: AtomicU8,
@@ -25,31 +176,230 @@ pub struct BufferMeta {
     pub dtype: AtomicU8,
     /// Number of dimensions
     pub ndim: AtomicU8,
+    /// Mode flags ([`FLAG_SEQLOCK`], ...)
+    pub flags: AtomicU8,
+    /// NUMA node the data was bound to, [`NUMA_NODE_NONE`] if unbound (see [`Self::numa_node`])
+    pub numa_node: AtomicU8,
     /// Shape (up to 8 dimensions)
     pub shape: [AtomicU64; MAX_NDIM],
     /// Strides in bytes
     pub strides: [AtomicU64; MAX_NDIM],
     /// Total size in bytes
     pub size: AtomicU64,
-    /// Timestamp (milliseconds since epoch)
+    /// Timestamp (milliseconds since epoch); time of release for a cached free slot
     pub timestamp: AtomicU64,
-    /// Sequence number
+    /// Sequence number (seqlock 模式下作为序列计数器)
     pub seq: AtomicU64,
     /// Content type string (null-terminated, 不需要原子操作)
-    pub content_type: [u8; 32],
+    pub content_type: [u8; LABEL_SIZE],
     /// Producer name (null-terminated, 不需要原子操作)
-    pub producer: [u8; 32],
-    /// CUDA IPC handle (预留，仅 storage_type == 1 时有效)
+    pub producer: [u8; LABEL_SIZE],
+    /// Storage handle: CUDA IPC handle, or defined by a custom backend (see [`Self::storage_handle`])
     pub cuda_ipc_handle: [u8; CUDA_IPC_HANDLE_SIZE],
     /// Next free buffer index (for free list, u32::MAX = end)
     pub next_free: AtomicU32,
-    /// Reserved for future use
-    pub reserved: [u8; 60],
+    /// Parent buffer index (仅 storage_type == 2 即视图时有效，u32::MAX = 无)
+    pub parent_index: AtomicU32,
+    /// Byte offset of a view within its parent
+    pub view_offset: AtomicU64,
+    /// Cross-process RW lock: writer's process slot + 1, 0 if none, see [`crate::lock`]
+    pub lock_state: AtomicU64,
+    /// Bitmap of process slots holding a read lock
+    pub lock_readers: [AtomicU64; LOCK_READER_WORDS],
+    /// Publish order, valid while [`FLAG_PUBLISHED`] is set
+    pub publish_seq: AtomicU32,
+    /// Incremented each time the slot is allocated, detects stale handles
+    pub generation: AtomicU32,
+    /// Cached segment state of a free slot ([`CACHE_PRESENT`], ...) | claiming process slot + 1 << 32
+    pub cache: AtomicU64,
 }
 
+// 共享内存布局，与 `include/xmem_layout.h` 保持一致，任何改动都会导致编译失败
+const _: () = {
+    use std::mem::{offset_of, size_of};
+    assert!(size_of::<BufferMeta>() == 360);
+    assert!(offset_of!(BufferMeta, id) == 0);
+    assert!(offset_of!(BufferMeta, ref_count) == 4);
+    assert!(offset_of!(BufferMeta, storage_type) == 8);
+    assert!(offset_of!(BufferMeta, device_id) == 9);
+    assert!(offset_of!(BufferMeta, dtype) == 10);
+    assert!(offset_of!(BufferMeta, ndim) == 11);
+    assert!(offset_of!(BufferMeta, flags) == 12);
+    assert!(offset_of!(BufferMeta, numa_node) == 13);
+    assert!(offset_of!(BufferMeta, shape) == 16);
+    assert!(offset_of!(BufferMeta, strides) == 80);
+    assert!(offset_of!(BufferMeta, size) == 144);
+    assert!(offset_of!(BufferMeta, timestamp) == 152);
+    assert!(offset_of!(BufferMeta, seq) == 160);
+    assert!(offset_of!(BufferMeta, content_type) == 168);
+    assert!(offset_of!(BufferMeta, producer) == 200);
+    assert!(offset_of!(BufferMeta, cuda_ipc_handle) == 232);
+    assert!(offset_of!(BufferMeta, next_free) == 296);
+    assert!(offset_of!(BufferMeta, parent_index) == 300);
+    assert!(offset_of!(BufferMeta, view_offset) == 304);
+    assert!(offset_of!(BufferMeta, lock_state) == 312);
+    assert!(offset_of!(BufferMeta, lock_readers) == 320);
+    assert!(offset_of!(BufferMeta, publish_seq) == 344);
+    assert!(offset_of!(BufferMeta, generation) == 348);
+    assert!(offset_of!(BufferMeta, cache) == 352);
+};
+
 impl BufferMeta {
     /// Size of BufferMeta in bytes
     pub const SIZE: usize = std::mem::size_of::<Self>();
+
+    /// 写入张量元数据（dtype、shape 以及 C 连续布局的字节 strides）
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::InvalidShape`]: 维度数超过 [`MAX_NDIM`]
+    pub fn set_tensor(&self, shape: &[usize], dtype: DType) -> Result<()> {
+        if shape.len() > MAX_NDIM {
+            return Err(Error::InvalidShape(format!(
+                "ndim {} exceeds maximum {}",
+                shape.len(),
+                MAX_NDIM
+            )));
+        }
+
+        let strides = contiguous_strides(shape, dtype.size());
+        for i in 0..MAX_NDIM {
+            let (dim, stride) = if i < shape.len() {
+                (shape[i] as u64, strides[i] as u64)
+            } else {
+                (0, 0)
+            };
+            self.shape[i].store(dim, Ordering::SeqCst);
+            self.strides[i].store(stride, Ordering::SeqCst);
+        }
+        self.dtype.store(dtype as u8, Ordering::SeqCst);
+        self.ndim.store(shape.len() as u8, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 清除张量元数据（ndim 置 0）
+    pub fn clear_tensor(&self) {
+        self.ndim.store(0, Ordering::SeqCst);
+    }
+
+    /// 清除时间戳、序号和标签（分配新 buffer 时调用）
+    pub fn clear_info(&self) {
+        self.timestamp.store(0, Ordering::SeqCst);
+        self.seq.store(0, Ordering::SeqCst);
+        write_label(&self.content_type, "");
+        write_label(&self.producer, "");
+    }
+
+    /// NUMA node the data was bound to, `None` if unbound (see [`crate::memory`])
+    pub fn numa_node(&self) -> Option<u32> {
+        match self.numa_node.load(Ordering::SeqCst) {
+            NUMA_NODE_NONE => None,
+            node => Some(node as u32),
+        }
+    }
+
+    /// Get content type (e.g. a MIME type), empty if unset
+    pub fn content_type(&self) -> String {
+        read_label(&self.content_type)
+    }
+
+    /// 写入内容类型
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::LabelTooLong`]: UTF-8 编码超过 [`LABEL_SIZE`] 字节
+    pub fn set_content_type(&self, value: &str) -> Result<()> {
+        check_label(value)?;
+        write_label(&self.content_type, value);
+        Ok(())
+    }
+
+    /// Get producer name, empty if unset
+    pub fn producer(&self) -> String {
+        read_label(&self.producer)
+    }
+
+    /// 写入生产者名称
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::LabelTooLong`]: UTF-8 编码超过 [`LABEL_SIZE`] 字节
+    pub fn set_producer(&self, value: &str) -> Result<()> {
+        check_label(value)?;
+        write_label(&self.producer, value);
+        Ok(())
+    }
+
+    /// Get the storage handle written by the buffer's backend
+    pub fn storage_handle(&self) -> [u8; CUDA_IPC_HANDLE_SIZE] {
+        unsafe { std::ptr::read_volatile(&self.cuda_ipc_handle) }
+    }
+
+    /// 写入存储句柄（由 [`StorageSegment::export_handle`](crate::StorageSegment::export_handle) 调用）
+    pub fn set_storage_handle(&self, handle: &[u8; CUDA_IPC_HANDLE_SIZE]) {
+        // Shared memory field, written in place like the labels
+        let dst = self.cuda_ipc_handle.as_ptr() as *mut [u8; CUDA_IPC_HANDLE_SIZE];
+        unsafe { std::ptr::write_volatile(dst, *handle) };
+    }
+
+    /// Get data type (`None` if the stored value is unknown)
+    pub fn dtype(&self) -> Option<DType> {
+        DType::from_u8(self.dtype.load(Ordering::SeqCst))
+    }
+
+    /// Get shape (`ndim` entries)
+    pub fn shape(&self) -> Vec<usize> {
+        let ndim = (self.ndim.load(Ordering::SeqCst) as usize).min(MAX_NDIM);
+        self.shape[..ndim]
+            .iter()
+            .map(|d| d.load(Ordering::SeqCst) as usize)
+            .collect()
+    }
+
+    /// Get strides in bytes (`ndim` entries)
+    pub fn strides(&self) -> Vec<usize> {
+        let ndim = (self.ndim.load(Ordering::SeqCst) as usize).min(MAX_NDIM);
+        self.strides[..ndim]
+            .iter()
+            .map(|s| s.load(Ordering::SeqCst) as usize)
+            .collect()
+    }
+}
+
+fn check_label(value: &str) -> Result<()> {
+    if value.len() > LABEL_SIZE {
+        return Err(Error::LabelTooLong {
+            len: value.len(),
+            max: LABEL_SIZE,
+        });
+    }
+    Ok(())
+}
+
+/// Read a NUL-padded label
+fn read_label(field: &[u8; LABEL_SIZE]) -> String {
+    let bytes = unsafe { std::ptr::read_volatile(field) };
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(LABEL_SIZE);
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Write a label, padding with NUL (caller checks the length)
+fn write_label(field: &[u8; LABEL_SIZE], value: &str) {
+    let mut bytes = [0u8; LABEL_SIZE];
+    bytes[..value.len()].copy_from_slice(value.as_bytes());
+    // Shared memory field, written in place like the storage handle
+    unsafe { std::ptr::write_volatile(field.as_ptr() as *mut [u8; LABEL_SIZE], bytes) };
+}
+
+/// 计算 C 连续布局的字节 strides
+pub fn contiguous_strides(shape: &[usize], elem_size: usize) -> Vec<usize> {
+    let mut strides = vec![0; shape.len()];
+    let mut acc = elem_size;
+    for i in (0..shape.len()).rev() {
+        strides[i] = acc;
+        acc *= shape[i].max(1);
+    }
+    strides
 }
 
 #[cfg(test)]
@@ -57,9 +407,79 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_meta_size() {
-        // Ensure struct size is stable for cross-process compatibility
-        assert!(BufferMeta::SIZE > 0);
-        println!("BufferMeta size: {} bytes", BufferMeta::SIZE);
+    fn test_set_tensor() {
+        let meta: BufferMeta = unsafe { std::mem::zeroed() };
+        meta.set_tensor(&[2, 3, 4], DType::Float32).unwrap();
+
+        assert_eq!(meta.dtype(), Some(DType::Float32));
+        assert_eq!(meta.shape(), vec![2, 3, 4]);
+        assert_eq!(meta.strides(), vec![48, 16, 4]);
+
+        meta.clear_tensor();
+        assert!(meta.shape().is_empty());
+
+        let too_many = [1; MAX_NDIM + 1];
+        assert!(matches!(
+            meta.set_tensor(&too_many, DType::UInt8),
+            Err(Error::InvalidShape(_))
+        ));
+    }
+
+    #[test]
+    fn test_tensor_layout() {
+        let meta: BufferMeta = unsafe { std::mem::zeroed() };
+        assert!(TensorLayout::from_meta(&meta, 24, false).is_err());
+
+        meta.set_tensor(&[2, 3], DType::Float32).unwrap();
+        let layout = TensorLayout::from_meta(&meta, 24, true).unwrap();
+        assert_eq!(layout.shape, vec![2, 3]);
+        assert_eq!(layout.strides, vec![3, 1]);
+        let invalid = |size, exclusive| {
+            matches!(TensorLayout::from_meta(&meta, size, exclusive), Err(Error::InvalidShape(_)))
+        };
+        assert!(invalid(23, false));
+
+        // Values another process may have written
+        meta.strides[1].store(2, Ordering::SeqCst);
+        assert!(invalid(24, false));
+        meta.strides[1].store(0, Ordering::SeqCst);
+        assert!(TensorLayout::from_meta(&meta, 24, false).is_ok());
+        assert!(invalid(24, true));
+        meta.strides[0].store(u64::MAX - 3, Ordering::SeqCst);
+        assert!(invalid(24, false));
+        meta.shape[0].store(1, Ordering::SeqCst);
+        assert!(invalid(24, false));
+        meta.strides[0].store(4 << 60, Ordering::SeqCst);
+        meta.shape[0].store(1 << 62, Ordering::SeqCst);
+        assert!(invalid(usize::MAX, false));
+
+        // Empty tensors skip the bounds check but not the extent check
+        meta.shape[1].store(0, Ordering::SeqCst);
+        assert!(TensorLayout::from_meta(&meta, 0, false).is_ok());
+        meta.shape[0].store(u64::MAX, Ordering::SeqCst);
+        assert!(invalid(0, false));
+    }
+
+    #[test]
+    fn test_labels() {
+        let meta: BufferMeta = unsafe { std::mem::zeroed() };
+        assert_eq!(meta.content_type(), "");
+
+        meta.set_content_type("image/jpeg").unwrap();
+        meta.set_producer(&"p".repeat(LABEL_SIZE)).unwrap();
+        assert_eq!(meta.content_type(), "image/jpeg");
+        assert_eq!(meta.producer(), "p".repeat(LABEL_SIZE));
+
+        // Shorter values overwrite the tail
+        meta.set_content_type("raw").unwrap();
+        assert_eq!(meta.content_type(), "raw");
+        assert!(matches!(
+            meta.set_producer(&"p".repeat(LABEL_SIZE + 1)),
+            Err(Error::LabelTooLong { len: 33, max: 32 })
+        ));
+
+        meta.clear_info();
+        assert_eq!(meta.content_type(), "");
+        assert_eq!(meta.producer(), "");
     }
 }
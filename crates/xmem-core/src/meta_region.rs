@@ -1,9 +1,21 @@
 //! Shared metadata region management
 
+use crate::cache;
+use crate::file::MappedFile;
+use crate::heap::HeapMemory;
+use crate::lock;
 use crate::meta::BufferMeta;
+use crate::notify;
+use crate::process::{self, Holder, ProcessTable, Registration};
 use crate::shm::SharedMemory;
+use crate::storage::StorageSegment;
 use crate::{Error, Result};
+use std::fs::OpenOptions;
+use std::os::fd::OwnedFd;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Header for metadata region
 #[repr(C)]
@@ -20,21 +32,55 @@ struct MetaRegionHeader {
     allocated: AtomicU32,
     /// Free list head index (u32::MAX = empty)
     free_head: AtomicU32,
-    /// Waiter count for backpressure
+    /// Number of processes blocked in [`MetaRegion::wait_event`]
     waiters: AtomicU32,
-    /// Reserved for future use
-    _reserved: [u32; 1],
+    /// Next publish sequence number
+    publish_seq: AtomicU32,
+    /// Pid of the process registered in each slot (0 = free), see [`crate::process`]
+    processes: ProcessTable,
 }
 
+// 共享内存布局，与 `include/xmem_layout.h` 保持一致，任何改动都会导致编译失败
+const _: () = {
+    use std::mem::{offset_of, size_of};
+    assert!(size_of::<MetaRegionHeader>() == 800);
+    assert!(offset_of!(MetaRegionHeader, magic) == 0);
+    assert!(offset_of!(MetaRegionHeader, version) == 4);
+    assert!(offset_of!(MetaRegionHeader, capacity) == 8);
+    assert!(offset_of!(MetaRegionHeader, next_id) == 12);
+    assert!(offset_of!(MetaRegionHeader, allocated) == 16);
+    assert!(offset_of!(MetaRegionHeader, free_head) == 20);
+    assert!(offset_of!(MetaRegionHeader, waiters) == 24);
+    assert!(offset_of!(MetaRegionHeader, publish_seq) == 28);
+    assert!(offset_of!(MetaRegionHeader, processes) == 32);
+};
+
 const MAGIC: u32 = 0x584D454D; // "XMEM"
-const VERSION: u32 = 2;
+const VERSION: u32 = 5;
+
+/// Where the region memory lives, to open it again for [`crate::process`]
+enum RegionSource {
+    Shm(String),
+    File(PathBuf),
+    Heap,
+}
 
 /// Shared metadata region
 pub struct MetaRegion {
-    shm: SharedMemory,
+    /// Mapped region memory (shared memory, or heap for in-memory pools)
+    mem: Box<dyn StorageSegment>,
     capacity: usize,
+    owner: bool,
+    source: RegionSource,
+    /// This process's slot in the process table, taken on first use
+    registration: Mutex<Option<Arc<Registration>>>,
 }
 
+// Safety: shared access only hands out `&BufferMeta`, whose fields are atomics
+// (or plain bytes that are only written through `&mut self`)
+unsafe impl Send for MetaRegion {}
+unsafe impl Sync for MetaRegion {}
+
 impl MetaRegion {
     /// Calculate required size for given capacity
     fn calc_size(capacity: usize) -> usize {
@@ -43,11 +89,37 @@ impl MetaRegion {
 
     /// Create a new metadata region
     pub fn create(name: &str, capacity: usize) -> Result<Self> {
-        let size = Self::calc_size(capacity);
-        let mut shm = SharedMemory::create(name, size)?;
+        let shm = SharedMemory::create(name, Self::calc_size(capacity))?;
+        let source = RegionSource::Shm(name.to_string());
+        Ok(Self::init(Box::new(shm), capacity, true, source))
+    }
 
-        // Initialize header
-        let header = unsafe { &mut *(shm.as_mut_ptr() as *mut MetaRegionHeader) };
+    /// 以 mmap 文件创建元数据区，文件已存在时失败
+    ///
+    /// `owner` 为 true 时与共享内存相同，drop 时删除文件；
+    /// 为 false 时文件保留，可通过 [`MetaRegion::open_file`] 重新打开。
+    pub fn create_file(path: &Path, capacity: usize, owner: bool) -> Result<Self> {
+        let mut file = MappedFile::create(path, Self::calc_size(capacity), true)?;
+        file.set_owner(owner);
+        let source = RegionSource::File(path.to_path_buf());
+        Ok(Self::init(Box::new(file), capacity, owner, source))
+    }
+
+    /// Create a process-local metadata region on the heap
+    pub fn in_memory(capacity: usize) -> Result<Self> {
+        let mem = HeapMemory::new(Self::calc_size(capacity))?;
+        Ok(Self::init(Box::new(mem), capacity, true, RegionSource::Heap))
+    }
+
+    /// Initialize the header of zeroed region memory
+    fn init(
+        mem: Box<dyn StorageSegment>,
+        capacity: usize,
+        owner: bool,
+        source: RegionSource,
+    ) -> Self {
+        let base = mem.cpu_ptr().expect("region memory is host memory");
+        let header = unsafe { &mut *(base as *mut MetaRegionHeader) };
         header.magic = MAGIC;
         header.version = VERSION;
         header.capacity = capacity as u32;
@@ -55,17 +127,40 @@ impl MetaRegion {
         header.allocated = AtomicU32::new(0);
         header.free_head = AtomicU32::new(u32::MAX); // Empty free list
         header.waiters = AtomicU32::new(0);
-        header._reserved = [0; 1];
+        header.publish_seq = AtomicU32::new(0);
+        for entry in &header.processes {
+            entry.store(0, Ordering::Relaxed);
+        }
 
-        Ok(Self { shm, capacity })
+        Self {
+            mem,
+            capacity,
+            owner,
+            source,
+            registration: Mutex::new(None),
+        }
     }
 
     /// Open an existing metadata region
     pub fn open(name: &str) -> Result<Self> {
-        let shm = SharedMemory::open(name)?;
+        let source = RegionSource::Shm(name.to_string());
+        Self::validate(Box::new(SharedMemory::open(name)?), source)
+    }
+
+    /// Open a metadata region file created by [`MetaRegion::create_file`]
+    pub fn open_file(path: &Path) -> Result<Self> {
+        let source = RegionSource::File(path.to_path_buf());
+        Self::validate(Box::new(MappedFile::open(path)?), source)
+    }
 
-        // Validate header
-        let header = unsafe { &*(shm.as_ptr() as *const MetaRegionHeader) };
+    /// Validate the header of an existing region
+    fn validate(mem: Box<dyn StorageSegment>, source: RegionSource) -> Result<Self> {
+        let size = mem.size();
+        if size < std::mem::size_of::<MetaRegionHeader>() {
+            return Err(Error::SharedMemory(format!("region too small: {} bytes", size)));
+        }
+        let base = mem.cpu_ptr().expect("region memory is host memory");
+        let header = unsafe { &*(base as *const MetaRegionHeader) };
         if header.magic != MAGIC {
             return Err(Error::SharedMemory("invalid magic number".to_string()));
         }
@@ -77,7 +172,24 @@ impl MetaRegion {
         }
 
         let capacity = header.capacity as usize;
-        Ok(Self { shm, capacity })
+        if size < Self::calc_size(capacity) {
+            return Err(Error::SharedMemory(format!(
+                "region truncated: {} bytes for capacity {}",
+                size, capacity
+            )));
+        }
+        Ok(Self {
+            mem,
+            capacity,
+            owner: false,
+            source,
+            registration: Mutex::new(None),
+        })
+    }
+
+    /// Flush the region to its backing file (no-op for shared memory)
+    pub fn sync(&self) -> Result<()> {
+        self.mem.sync()
     }
 
     /// Get capacity
@@ -85,9 +197,113 @@ impl MetaRegion {
         self.capacity
     }
 
+    /// Check if this process created (and will unlink) the region
+    pub fn is_owner(&self) -> bool {
+        self.owner
+    }
+
+    /// Number of slots ever handed out (free-list slots included)
+    pub fn high_water(&self) -> u32 {
+        self.header().next_id.load(Ordering::SeqCst).min(self.capacity as u32)
+    }
+
+    /// 本进程在登记表中的身份，第一次调用时登记，见 [`crate::process`]
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::TooManyProcesses`]：登记表已满
+    pub(crate) fn holder(&self) -> Result<Holder<'_>> {
+        let mut registration = self.registration.lock().unwrap_or_else(|e| e.into_inner());
+        let reg = match registration.as_ref().filter(|reg| reg.is_current()) {
+            Some(reg) => Arc::clone(reg),
+            // First use, or the registration was inherited across fork
+            None => {
+                let reg = process::register(self.processes(), self.open_fd()?, |slot| {
+                    self.sweep(slot)
+                })?;
+                *registration = Some(Arc::clone(&reg));
+                reg
+            }
+        };
+        Ok(Holder::new(reg, self.processes()))
+    }
+
+    /// Process table in the header
+    pub(crate) fn processes(&self) -> &ProcessTable {
+        &self.header().processes
+    }
+
+    /// Open a new descriptor of the region, `None` for in-memory regions
+    pub(crate) fn open_fd(&self) -> Result<Option<OwnedFd>> {
+        match &self.source {
+            RegionSource::Shm(name) => SharedMemory::open_fd(name, true).map(Some),
+            RegionSource::File(path) => OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .map(|file| Some(file.into()))
+                .map_err(|e| Error::SharedMemory(format!("{}: {}", path.display(), e))),
+            RegionSource::Heap => Ok(None),
+        }
+    }
+
+    /// Drop the locks and cache claims a dead process left in `slot`
+    pub(crate) fn sweep(&self, slot: u32) {
+        for index in 0..self.high_water() {
+            let meta = self.get(index).expect("index below capacity");
+            lock::forget_slot(meta, slot);
+            cache::forget_slot(meta, slot);
+        }
+    }
+
+    /// Current publish sequence number (the next one to be taken)
+    pub fn publish_seq(&self) -> u32 {
+        self.header().publish_seq.load(Ordering::SeqCst)
+    }
+
+    /// Take the next publish sequence number
+    pub fn next_publish_seq(&self) -> u32 {
+        self.header().publish_seq.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Wake cross-process waiters after a buffer is marked published
+    ///
+    /// 跳过一个序列号，使在标记前读取计数器的等待方不会错过唤醒。
+    pub(crate) fn notify_published(&self) {
+        let header = self.header();
+        header.publish_seq.fetch_add(1, Ordering::SeqCst);
+        self.wake(&header.publish_seq);
+    }
+
+    /// Register as a waiter for [`RegionEvent`]s until the returned guard drops
+    ///
+    /// 必须在检查等待条件之前注册，否则可能错过唤醒。
+    pub(crate) fn waiter(&self) -> EventWaiter<'_> {
+        self.header().waiters.fetch_add(1, Ordering::SeqCst);
+        EventWaiter { region: self }
+    }
+
+    fn event_word(&self, event: RegionEvent) -> &AtomicU32 {
+        match event {
+            RegionEvent::Freed => &self.header().allocated,
+            RegionEvent::Published => &self.header().publish_seq,
+        }
+    }
+
+    fn wake(&self, word: &AtomicU32) {
+        if self.header().waiters.load(Ordering::SeqCst) > 0 {
+            notify::wake_word(word);
+        }
+    }
+
+    /// Start of the region memory
+    fn base(&self) -> *mut u8 {
+        self.mem.cpu_ptr().expect("region memory is host memory")
+    }
+
     /// Get header reference
     fn header(&self) -> &MetaRegionHeader {
-        unsafe { &*(self.shm.as_ptr() as *const MetaRegionHeader) }
+        unsafe { &*(self.base() as *const MetaRegionHeader) }
     }
 
     /// Allocate a buffer slot, returns meta_index
@@ -109,6 +325,7 @@ impl MetaRegion {
                 .is_ok()
             {
                 header.allocated.fetch_add(1, Ordering::SeqCst);
+                meta.generation.fetch_add(1, Ordering::SeqCst);
                 return Ok(head);
             }
         }
@@ -122,6 +339,7 @@ impl MetaRegion {
         }
 
         header.allocated.fetch_add(1, Ordering::SeqCst);
+        self.get(id)?.generation.fetch_add(1, Ordering::SeqCst);
         Ok(id)
     }
 
@@ -148,9 +366,55 @@ impl MetaRegion {
         }
 
         header.allocated.fetch_sub(1, Ordering::SeqCst);
+        self.wake(&header.allocated);
         Ok(())
     }
 
+    /// 检查空闲链表是否恰好包含 `high_water` 以下所有空闲槽
+    ///
+    /// 需在没有其他进程分配或释放时调用（见 [`BufferPool::fsck`](crate::BufferPool::fsck)）。
+    pub(crate) fn free_list_consistent(&self, is_free: impl Fn(u32) -> bool) -> bool {
+        let header = self.header();
+        let high = self.high_water();
+        if header.next_id.load(Ordering::SeqCst) > self.capacity as u32 {
+            return false;
+        }
+        let mut on_list = vec![false; high as usize];
+        let mut index = header.free_head.load(Ordering::SeqCst);
+        while index != u32::MAX {
+            // Out of range, cycle, or a live slot on the list
+            if index >= high || on_list[index as usize] || !is_free(index) {
+                return false;
+            }
+            on_list[index as usize] = true;
+            index = self.get(index).expect("index checked").next_free.load(Ordering::SeqCst);
+        }
+        let live = (0..high).filter(|&i| !on_list[i as usize]).count() as u32;
+        (0..high).all(|i| on_list[i as usize] || !is_free(i))
+            && header.allocated.load(Ordering::SeqCst) == live
+    }
+
+    /// 按 `is_free` 重建空闲链表和分配计数，同样要求独占访问
+    pub(crate) fn rebuild_free_list(&self, is_free: impl Fn(u32) -> bool) {
+        let header = self.header();
+        let high = self.high_water();
+        header.next_id.store(high, Ordering::SeqCst);
+        let mut head = u32::MAX;
+        let mut live = 0;
+        for index in (0..high).rev() {
+            if is_free(index) {
+                let meta = self.get(index).expect("index below capacity");
+                meta.next_free.store(head, Ordering::SeqCst);
+                head = index;
+            } else {
+                live += 1;
+            }
+        }
+        header.free_head.store(head, Ordering::SeqCst);
+        header.allocated.store(live, Ordering::SeqCst);
+        header.waiters.store(0, Ordering::SeqCst);
+    }
+
     /// Get metadata by index
     pub fn get(&self, index: u32) -> Result<&BufferMeta> {
         if index >= self.capacity as u32 {
@@ -158,7 +422,7 @@ impl MetaRegion {
         }
 
         let offset = std::mem::size_of::<MetaRegionHeader>() + (index as usize) * BufferMeta::SIZE;
-        let ptr = unsafe { self.shm.as_ptr().add(offset) as *const BufferMeta };
+        let ptr = unsafe { self.base().add(offset) as *const BufferMeta };
         Ok(unsafe { &*ptr })
     }
 
@@ -169,11 +433,43 @@ impl MetaRegion {
         }
 
         let offset = std::mem::size_of::<MetaRegionHeader>() + (index as usize) * BufferMeta::SIZE;
-        let ptr = unsafe { self.shm.as_mut_ptr().add(offset) as *mut BufferMeta };
+        let ptr = unsafe { self.base().add(offset) as *mut BufferMeta };
         Ok(unsafe { &mut *ptr })
     }
 }
 
+/// 可跨进程等待的元数据区事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RegionEvent {
+    /// A slot went back on the free list
+    Freed,
+    /// A buffer was published
+    Published,
+}
+
+/// Registered waiter, see [`MetaRegion::waiter`]
+pub(crate) struct EventWaiter<'a> {
+    region: &'a MetaRegion,
+}
+
+impl EventWaiter<'_> {
+    /// Current counter of `event`, to pass to [`wait`](Self::wait) after checking the condition
+    pub(crate) fn seq(&self, event: RegionEvent) -> u32 {
+        self.region.event_word(event).load(Ordering::SeqCst)
+    }
+
+    /// Sleep until `event` fires after `seen` was read, or `timeout`
+    pub(crate) fn wait(&self, event: RegionEvent, seen: u32, timeout: Duration) {
+        notify::wait_word(self.region.event_word(event), seen, timeout);
+    }
+}
+
+impl Drop for EventWaiter<'_> {
+    fn drop(&mut self) {
+        self.region.header().waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,8 +561,11 @@ mod tests {
         region.free(idx1).unwrap();
 
         // Next alloc should reuse idx1
+        let generation = region.get(idx1).unwrap().generation.load(Ordering::SeqCst);
         let idx3 = region.alloc().unwrap();
         assert_eq!(idx3, 1); // Reused!
+        let meta = region.get(idx3).unwrap();
+        assert_eq!(meta.generation.load(Ordering::SeqCst), generation + 1);
 
         // Next alloc should be new
         let idx4 = region.alloc().unwrap();
@@ -297,4 +596,76 @@ mod tests {
         assert_eq!(new1, 1);
         assert_eq!(new2, 0);
     }
+
+    #[test]
+    fn test_layout_header() {
+        use crate::lock::LOCK_READER_WORDS;
+        use crate::process::MAX_PROCESSES;
+        use crate::storage::StorageType;
+        use crate::meta::{
+            CACHE_PRESENT, CACHE_TRIMMED, CUDA_IPC_HANDLE_SIZE, FLAG_PUBLISHED, FLAG_SEQLOCK,
+            LABEL_SIZE, MAX_NDIM, NUMA_NODE_NONE,
+        };
+        use std::collections::BTreeMap;
+        use std::mem::{offset_of, size_of};
+
+        // The C header must describe exactly the Rust layout
+        let header = include_str!("../include/xmem_layout.h");
+        let mut checks = BTreeMap::new();
+        let mut defines = BTreeMap::new();
+        for line in header.lines() {
+            let args = |prefix: &str| {
+                line.strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(");"))
+                    .map(|rest| rest.split(", ").map(str::to_string).collect::<Vec<_>>())
+            };
+            if let Some(args) = args("XMEM_CHECK_SIZE(") {
+                checks.insert((args[0].clone(), "sizeof".to_string()), args[1].parse().unwrap());
+            } else if let Some(args) = args("XMEM_CHECK_OFFSET(") {
+                checks.insert((args[0].clone(), args[1].clone()), args[2].parse().unwrap());
+            } else if let Some(rest) = line.strip_prefix("#define XMEM_") {
+                if let Some((name, value)) = rest.split_once(' ') {
+                    let value = value.split("/*").next().unwrap().trim();
+                    defines.insert(name.to_string(), value.trim_end_matches('u').to_string());
+                }
+            }
+        }
+
+        macro_rules! layout {
+            ($map:ident, $cname:literal, $ty:ty { $($field:ident),* }) => {
+                $map.insert(($cname.to_string(), "sizeof".to_string()), size_of::<$ty>());
+                $($map.insert(($cname.to_string(), stringify!($field).to_string()), offset_of!($ty, $field));)*
+            };
+        }
+        let mut expected = BTreeMap::new();
+        layout!(expected, "xmem_region_header_t", MetaRegionHeader {
+            magic, version, capacity, next_id, allocated, free_head, waiters, publish_seq, processes
+        });
+        layout!(expected, "xmem_buffer_meta_t", BufferMeta {
+            id, ref_count, storage_type, device_id, dtype, ndim, flags, numa_node, shape, strides, size,
+            timestamp, seq, content_type, producer, cuda_ipc_handle, next_free, parent_index,
+            view_offset, lock_state, lock_readers, publish_seq, generation, cache
+        });
+        assert_eq!(checks, expected);
+
+        let define = |name: &str| defines[name].as_str();
+        assert_eq!(define("MAGIC"), format!("0x{:X}", MAGIC));
+        assert_eq!(define("LAYOUT_VERSION"), VERSION.to_string());
+        assert_eq!(define("MAX_NDIM"), MAX_NDIM.to_string());
+        assert_eq!(define("LABEL_SIZE"), LABEL_SIZE.to_string());
+        assert_eq!(define("CUDA_IPC_HANDLE_SIZE"), CUDA_IPC_HANDLE_SIZE.to_string());
+        assert_eq!(define("LOCK_READER_WORDS"), LOCK_READER_WORDS.to_string());
+        assert_eq!(define("MAX_PROCESSES"), MAX_PROCESSES.to_string());
+        assert_eq!(define("STORAGE_CPU"), (StorageType::Cpu as u8).to_string());
+        assert_eq!(define("STORAGE_VIEW"), (StorageType::View as u8).to_string());
+        assert_eq!(define("STORAGE_HEAP"), (StorageType::Heap as u8).to_string());
+        assert_eq!(define("STORAGE_FILE"), (StorageType::File as u8).to_string());
+        let flag = |bit: u8| format!("(1u << {})", bit.trailing_zeros());
+        assert_eq!(define("FLAG_SEQLOCK"), flag(FLAG_SEQLOCK));
+        assert_eq!(define("FLAG_PUBLISHED"), flag(FLAG_PUBLISHED));
+        assert_eq!(define("NUMA_NODE_NONE"), format!("0x{:X}", NUMA_NODE_NONE));
+        let bit = |bit: u64| format!("(1u << {})", bit.trailing_zeros());
+        assert_eq!(define("CACHE_PRESENT"), bit(CACHE_PRESENT));
+        assert_eq!(define("CACHE_TRIMMED"), bit(CACHE_TRIMMED));
+    }
 }
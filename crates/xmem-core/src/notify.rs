@@ -0,0 +1,276 @@
+//! 释放/发布唤醒通知
+//!
+//! 有两种等待方式：
+//!
+//! - 阻塞等待（[`BufferPool::receive_timeout`](crate::BufferPool::receive_timeout)、
+//!   [`BufferPool::acquire_cpu_blocking`](crate::BufferPool::acquire_cpu_blocking)）
+//!   在共享元数据区头部的计数器上使用 futex，任何进程的释放/发布都会立即唤醒等待方。
+//!   非 Linux 平台没有跨进程 futex，退化为按 [`POLL_INTERVAL`] 轮询。
+//! - [`Notifier`] 封装一个非阻塞的可读 fd（Linux 上为 eventfd，其他平台为管道），
+//!   可注册到事件循环（如 asyncio 的 `loop.add_reader`），等待方的线程不被占用。
+//!   本进程通过同一 [`BufferPool`](crate::BufferPool) 释放或发布 buffer 时直接置为可读。
+//!   Linux 上存在订阅者期间，池为每种事件各启动一个后台线程在上述 futex 上等待，
+//!   并把其他进程的事件转发到 fd；最后一个订阅者释放后线程在
+//!   [`WATCH_SLICE`] 内退出。**其他平台只反映本进程内的事件**，
+//!   等待方必须同时按 [`POLL_INTERVAL`] 定时重试，跨进程事件最多延迟一个间隔。
+
+use crate::{Error, Result};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::sync::atomic::AtomicU32;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Re-check interval for events from other processes when waiting on a [`Notifier`] off Linux
+pub const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Longest a watcher thread outlives the last [`Notifier`] of its pool
+pub const WATCH_SLICE: Duration = Duration::from_millis(100);
+
+/// Sleep until `word` may differ from `seen`, a wake-up, or `timeout`
+///
+/// `word` 位于共享内存中时，可被任何映射它的进程通过 [`wake_word`] 唤醒。
+#[cfg(target_os = "linux")]
+pub(crate) fn wait_word(word: &AtomicU32, seen: u32, timeout: Duration) {
+    let ts = libc::timespec {
+        tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: timeout.subsec_nanos() as libc::c_long,
+    };
+    // Not FUTEX_PRIVATE: waiters and wakers live in different processes.
+    // EAGAIN (value changed), EINTR and ETIMEDOUT all just return to the caller.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            word.as_ptr(),
+            libc::FUTEX_WAIT,
+            seen,
+            &ts as *const libc::timespec,
+        )
+    };
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn wait_word(_word: &AtomicU32, _seen: u32, timeout: Duration) {
+    std::thread::sleep(timeout.min(POLL_INTERVAL));
+}
+
+/// Wake every process waiting on `word`
+#[cfg(target_os = "linux")]
+pub(crate) fn wake_word(word: &AtomicU32) {
+    unsafe { libc::syscall(libc::SYS_futex, word.as_ptr(), libc::FUTEX_WAKE, i32::MAX) };
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn wake_word(_word: &AtomicU32) {}
+
+/// Readable/writable fd pair (the same fd for eventfd)
+struct WakeFd {
+    read: OwnedFd,
+    #[cfg(not(target_os = "linux"))]
+    write: OwnedFd,
+}
+
+impl WakeFd {
+    #[cfg(target_os = "linux")]
+    fn new() -> Result<Self> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(Error::SharedMemory(std::io::Error::last_os_error().to_string()));
+        }
+        Ok(Self {
+            read: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new() -> Result<Self> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(Error::SharedMemory(std::io::Error::last_os_error().to_string()));
+        }
+        let (read, write) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        for fd in fds {
+            unsafe {
+                libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK);
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+        }
+        Ok(Self { read, write })
+    }
+
+    fn write_fd(&self) -> RawFd {
+        #[cfg(target_os = "linux")]
+        return self.read.as_raw_fd();
+        #[cfg(not(target_os = "linux"))]
+        return self.write.as_raw_fd();
+    }
+
+    fn signal(&self) {
+        // A full counter/pipe already means "readable", so errors are ignored
+        let one = 1u64;
+        unsafe { libc::write(self.write_fd(), &one as *const u64 as *const libc::c_void, 8) };
+    }
+
+    fn drain(&self) -> bool {
+        let mut buf = [0u8; 64];
+        let mut signalled = false;
+        // eventfd resets in one read; a pipe may need several
+        while unsafe {
+            libc::read(self.read.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len())
+        } > 0
+        {
+            signalled = true;
+        }
+        signalled
+    }
+}
+
+/// 唤醒通知订阅
+///
+/// 由 [`BufferPool::notifier`](crate::BufferPool::notifier) 创建，Drop 时自动取消订阅。
+/// 非 Linux 平台只反映本进程内的事件，跨进程事件需按 [`POLL_INTERVAL`] 轮询，见[模块文档](self)。
+pub struct Notifier {
+    fd: Arc<WakeFd>,
+}
+
+impl Notifier {
+    /// Clear pending notifications, returns whether any were pending
+    pub fn drain(&self) -> bool {
+        self.fd.drain()
+    }
+
+    /// 等待通知，最多 `timeout`
+    ///
+    /// 返回是否收到通知（收到时已清除）。
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let mut pfd = libc::pollfd {
+            fd: self.fd.read.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ms = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        unsafe { libc::poll(&mut pfd, 1, ms) };
+        self.drain()
+    }
+}
+
+impl AsRawFd for Notifier {
+    /// Readable fd to register with an event loop
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.read.as_raw_fd()
+    }
+}
+
+/// Subscribers of one pool handle
+#[derive(Default)]
+pub(crate) struct Wakers {
+    subscribers: Mutex<Subscribers>,
+}
+
+#[derive(Default)]
+struct Subscribers {
+    fds: Vec<Weak<WakeFd>>,
+    /// Bit `id` set while watcher `id` runs, see [`Wakers::start_watch`]
+    watching: u32,
+}
+
+impl Wakers {
+    /// Register a new subscriber
+    pub(crate) fn subscribe(&self) -> Result<Notifier> {
+        let fd = Arc::new(WakeFd::new()?);
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        subscribers.fds.retain(|w| w.strong_count() > 0);
+        subscribers.fds.push(Arc::downgrade(&fd));
+        Ok(Notifier { fd })
+    }
+
+    /// Wake all live subscribers
+    pub(crate) fn wake(&self) {
+        let subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        for fd in subscribers.fds.iter().filter_map(Weak::upgrade) {
+            fd.signal();
+        }
+    }
+
+    /// Mark watcher `id` (below 32) running, returns whether the caller must start it
+    ///
+    /// 与 [`stop_watch`](Self::stop_watch) 在同一把锁下判断，
+    /// 订阅后调用即可保证有 watcher 为该订阅者运行。
+    pub(crate) fn start_watch(&self, id: u32) -> bool {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        let running = subscribers.watching & (1 << id) != 0;
+        subscribers.watching |= 1 << id;
+        !running
+    }
+
+    /// Mark watcher `id` stopped if no subscriber is left (or `force`), returns whether it must exit
+    pub(crate) fn stop_watch(&self, id: u32, force: bool) -> bool {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        subscribers.fds.retain(|w| w.strong_count() > 0);
+        let stop = force || subscribers.fds.is_empty();
+        if stop {
+            subscribers.watching &= !(1 << id);
+        }
+        stop
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_wait_word() {
+        let word = Arc::new(AtomicU32::new(0));
+
+        // A stale value returns at once, an unchanged one sleeps until timeout
+        let start = Instant::now();
+        wait_word(&word, 1, Duration::from_secs(5));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        let start = Instant::now();
+        wait_word(&word, 0, Duration::from_millis(20));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let waker = Arc::clone(&word);
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            waker.store(1, std::sync::atomic::Ordering::SeqCst);
+            wake_word(&waker);
+        });
+        let start = Instant::now();
+        wait_word(&word, 0, Duration::from_secs(5));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_wake_and_drain() {
+        let wakers = Wakers::default();
+        let a = wakers.subscribe().unwrap();
+        let b = wakers.subscribe().unwrap();
+        assert!(!a.drain());
+
+        wakers.wake();
+        wakers.wake();
+        assert!(a.drain());
+        assert!(!a.drain());
+        assert!(b.wait(Duration::from_secs(1)));
+
+        let start = Instant::now();
+        assert!(!b.wait(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        // Dropped subscribers are skipped
+        drop(a);
+        wakers.wake();
+        assert!(b.drain());
+
+        // A watcher runs until its last subscriber is gone
+        assert!(wakers.start_watch(1));
+        assert!(!wakers.start_watch(1));
+        assert!(!wakers.stop_watch(1, false));
+        drop(b);
+        assert!(wakers.stop_watch(1, false));
+        assert!(wakers.start_watch(1));
+    }
+}
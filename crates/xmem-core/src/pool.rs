@@ -22,22 +22,50 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # 数据段生命周期
+//!
+//! buffer 的数据段（CPU 后端为 `{pool}_buf_{index}` 共享内存）跟随引用计数，
+//! 而不是跟随某个进程的映射：
+//!
+//! - 创建方的 guard drop 不会删除数据段，只要引用计数仍大于 0，其他进程就能打开它
+//! - 引用计数归 0（[`BufferPool::release_buffer`]）时释放数据段；
+//!   仍在映射它的进程保留各自的映射，新的打开会失败
+//! - 创建池的进程最后一个句柄 drop 时释放所有数据段，元数据区随之消失，
+//!   残留的数据段已无法按名称找到
+//!
+//! 默认情况下槽位回收后不复用旧数据段，下次分配总是创建新段；
+//! 启用 [`PoolConfig::cache_segments`] 后，释放时保留数据段供同样大小的分配复用，
+//! 其内存由 [`BufferPool::trim`] 和字节预算回收，见 [`crate::cache`]。
 
 use crate::buffer::BufferData;
+use crate::cache;
+use crate::config::{self, PoolConfig, DEFAULT_CAPACITY};
+use crate::dtype::DType;
+use crate::file::{self, AttachLock, FileBackend};
 use crate::guard::BufferGuard;
-use crate::meta_region::MetaRegion;
-use crate::shm::SharedMemory;
-use crate::storage::{AccessMode, StorageType};
+use crate::heap::HeapBackend;
+use crate::lock::{self, LockKind, LockWait};
+use crate::meta::{
+    BufferMeta, TensorDesc, CACHE_PRESENT, CACHE_TRIMMED, CUDA_IPC_HANDLE_SIZE, FLAG_PUBLISHED,
+    FLAG_SEQLOCK, MAX_NDIM, NUMA_NODE_NONE,
+};
+use crate::memory::{self, HugePages};
+use crate::meta_region::{MetaRegion, RegionEvent};
+use crate::notify::{Notifier, Wakers};
+use crate::process::Registration;
+use crate::shm::ShmBackend;
+use crate::storage::{self, AccessMode, SegmentId, StorageBackend, StorageSegment, StorageType};
 use crate::{Error, Result};
+use std::path::Path;
 use std::sync::atomic::Ordering;
-use std::time::Duration;
-
-/// Default metadata region capacity
-const DEFAULT_CAPACITY: usize = 1024;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// 跨进程共享内存缓冲池
 ///
 /// 管理共享内存缓冲区的分配、访问和生命周期。
+/// `BufferPool` 是 `Send + Sync` 的，可通过引用或 clone 在线程间共享。
 ///
 /// # 示例
 ///
@@ -57,11 +85,136 @@ const DEFAULT_CAPACITY: usize = 1024;
 /// # Ok(())
 /// # }
 /// ```
+#[derive(Clone)]
 pub struct BufferPool {
+    /// State shared with guards (keeps metadata mapped while they live)
+    shared: Arc<PoolShared>,
+}
+
+/// Pool state shared by [`BufferPool`] handles and the guards they hand out
+pub(crate) struct PoolShared {
     /// Pool name
     name: String,
     /// Metadata region
     meta_region: MetaRegion,
+    /// Backend for CPU buffers (shared memory, or heap for in-memory pools)
+    cpu_backend: Arc<dyn StorageBackend>,
+    /// In-process subscribers woken on free and publish
+    wakers: Wakers,
+    /// Configuration the pool was created or opened with
+    config: PoolConfig,
+    /// Huge page mode in effect (see [`crate::memory`])
+    huge_pages: HugePages,
+    /// NUMA node CPU buffers are bound to (see [`crate::memory`])
+    numa_node: Option<u32>,
+    /// Attach lock on the metadata file (persistent file pools)
+    _attach: Option<AttachLock>,
+}
+
+impl PoolShared {
+    /// Pool name
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Directory holding the segment files (file-backed pools)
+    pub(crate) fn backing_dir(&self) -> Option<&Path> {
+        self.config.backing_dir.as_deref()
+    }
+
+    /// Forward `event` from every process to the [`Notifier`]s until none is left
+    ///
+    /// 每种事件最多一个 watcher 线程。起始计数在返回前读取，
+    /// 因此调用方随后检查条件时已发生的事件不会被漏掉。
+    #[cfg(target_os = "linux")]
+    fn watch(self: &Arc<Self>, event: RegionEvent) -> Result<()> {
+        let id = event as u32;
+        if !self.wakers.start_watch(id) {
+            return Ok(());
+        }
+        let seen = self.meta_region.waiter().seq(event);
+        let shared = Arc::clone(self);
+        let spawned = std::thread::Builder::new()
+            .name("xmem-watch".to_string())
+            .spawn(move || {
+                let waiter = shared.meta_region.waiter();
+                let mut seen = seen;
+                loop {
+                    waiter.wait(event, seen, crate::notify::WATCH_SLICE);
+                    let now = waiter.seq(event);
+                    if now != seen {
+                        seen = now;
+                        shared.wakers.wake();
+                    }
+                    if shared.wakers.stop_watch(id, false) {
+                        break;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            self.wakers.stop_watch(id, true);
+            return Err(Error::SharedMemory(format!("cannot start watcher thread: {}", e)));
+        }
+        Ok(())
+    }
+
+    /// Backend for a storage type: the pool's CPU backend, else the registry
+    fn backend(&self, storage_type: u8) -> Result<Arc<dyn StorageBackend>> {
+        if storage_type == self.cpu_backend.storage_type() {
+            return Ok(Arc::clone(&self.cpu_backend));
+        }
+        storage::backend(storage_type)
+    }
+}
+
+impl Drop for PoolShared {
+    fn drop(&mut self) {
+        // The creator frees the whole pool: once the metadata region is gone
+        // the buffer segments can no longer be found. Processes that still map
+        // them keep their mappings.
+        if self.meta_region.is_owner() {
+            for index in 0..self.meta_region.high_water() {
+                let Ok(meta) = self.meta_region.get(index) else {
+                    continue;
+                };
+                // Views have no backend of their own
+                if let Ok(backend) = self.backend(meta.storage_type.load(Ordering::SeqCst)) {
+                    let id = SegmentId {
+                        pool: &self.name,
+                        index,
+                    };
+                    let _ = backend.free(id, meta);
+                }
+            }
+        }
+    }
+}
+
+/// Cross-process lock taken for a guard that is not built yet
+struct RootLock<'a> {
+    kind: LockKind,
+    /// Buffer owning the data (the view's root for views)
+    root: &'a BufferMeta,
+    owner: Arc<Registration>,
+}
+
+impl RootLock<'_> {
+    /// Give up the lock when the guard could not be built
+    fn release(self) {
+        lock::unlock(&self.owner, self.root, self.kind);
+    }
+
+    /// Hand the lock to `guard`, which releases it on drop
+    fn attach(self, guard: BufferGuard) -> BufferGuard {
+        guard.with_lock(self.kind, self.root, &self.owner)
+    }
+}
+
+/// Take a reference only while the buffer is live (ref count above 0)
+fn add_ref_if_live(meta: &BufferMeta) -> bool {
+    meta.ref_count
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| (n > 0).then_some(n + 1))
+        .is_ok()
 }
 
 impl BufferPool {
@@ -92,173 +245,970 @@ impl BufferPool {
     /// - `name`: 池名称
     /// - `capacity`: 最大 buffer 数量
     pub fn create_with_capacity(name: &str, capacity: usize) -> Result<Self> {
-        let meta_name = format!("{}_meta", name);
-        let meta_region = MetaRegion::create(&meta_name, capacity)?;
+        Self::create_with_config(name, &PoolConfig::new().capacity(capacity))
+    }
 
-        Ok(Self {
-            name: name.to_string(),
-            meta_region,
-        })
+    /// 按 [`PoolConfig`] 创建缓冲池
+    ///
+    /// 池的名称为加上命名空间前缀后的完整名称；设置了 `backing_dir` 时，
+    /// 段为该目录下的文件 `{name}_meta`、`{name}_buf_{index}`，池销毁时删除。
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::InvalidName`]: 名称或 `namespace` 不合法
+    /// - [`Error::SharedMemory`]: 同名池已存在，或 `backing_dir` 不可写
+    pub fn create_with_config(name: &str, config: &PoolConfig) -> Result<Self> {
+        let name = config.full_name(name)?;
+        let Some(dir) = &config.backing_dir else {
+            let meta_region = MetaRegion::create(&format!("{}_meta", name), config.capacity)?;
+            return Ok(Self::from_region(&name, meta_region, Arc::new(ShmBackend), config, None));
+        };
+
+        let meta_path = file::pool_file(dir, &name, "_meta");
+        let meta_region = MetaRegion::create_file(&meta_path, config.capacity, true)?;
+        let backend = Arc::new(FileBackend::new(dir));
+        Ok(Self::from_region(&name, meta_region, backend, config, None))
+    }
+
+    /// 按 [`PoolConfig`] 打开已存在的缓冲池，`namespace` 和 `backing_dir` 需与创建时一致
+    ///
+    /// `capacity` 被忽略，以元数据区中记录的为准。
+    pub fn open_with_config(name: &str, config: &PoolConfig) -> Result<Self> {
+        let name = config.full_name(name)?;
+        let Some(dir) = &config.backing_dir else {
+            let meta_region = MetaRegion::open(&format!("{}_meta", name))?;
+            return Ok(Self::from_region(&name, meta_region, Arc::new(ShmBackend), config, None));
+        };
+
+        let meta_region = MetaRegion::open_file(&file::pool_file(dir, &name, "_meta"))?;
+        let backend = Arc::new(FileBackend::new(dir));
+        Ok(Self::from_region(&name, meta_region, backend, config, None))
+    }
+
+    /// 创建进程内缓冲池，元数据和数据都在堆上
+    ///
+    /// API 与共享内存池相同，但不创建 `/dev/shm` 段，其他进程也无法打开，
+    /// 适合下游代码的单元测试：可并行运行、同名互不干扰，panic 时不会遗留共享内存。
+    /// 通过 clone 在线程间共享；最后一个句柄及其 guard 释放后内存即被回收。
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use xmem_core::BufferPool;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let pool = BufferPool::in_memory("test_pool")?;
+    /// let mut buf = pool.acquire_cpu(4)?;
+    /// buf.as_cpu_slice_mut()?.copy_from_slice(b"data");
+    ///
+    /// let idx = buf.meta_index();
+    /// buf.forget();
+    /// assert_eq!(pool.get(idx)?.as_cpu_slice()?, b"data");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn in_memory(name: &str) -> Result<Self> {
+        Self::in_memory_with_capacity(name, DEFAULT_CAPACITY)
+    }
+
+    /// 创建指定容量的进程内缓冲池，见 [`in_memory()`](Self::in_memory)
+    pub fn in_memory_with_capacity(name: &str, capacity: usize) -> Result<Self> {
+        config::validate_name(name)?;
+        let meta_region = MetaRegion::in_memory(capacity)?;
+
+        let config = PoolConfig::new().capacity(capacity);
+        let backend = Arc::new(HeapBackend::default());
+        Ok(Self::from_region(name, meta_region, backend, &config, None))
+    }
+
+    /// 在目录 `dir` 下创建持久化文件池
+    ///
+    /// 元数据区和 buffer 数据为 mmap 文件 `{dir}/{name}_meta`、`{dir}/{name}_buf_{index}`，
+    /// 池销毁后文件保留，进程重启后可用 [`open_file()`](Self::open_file) 重新挂载，
+    /// 已发布但未被接收的 buffer 仍可通过 [`try_receive()`](Self::try_receive) 取回。
+    /// 数据何时落盘由内核决定，需要持久性保证时调用 [`sync()`](Self::sync)。
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::SharedMemory`]: 元数据文件已存在，或目录 `dir` 不存在、不可写
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use xmem_core::BufferPool;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let dir = std::env::temp_dir().join(format!("xmem_doc_{}", std::process::id()));
+    /// # std::fs::create_dir_all(&dir)?;
+    /// let pool = BufferPool::create_file(&dir, "frames")?;
+    /// let mut buf = pool.acquire_cpu(5)?;
+    /// buf.as_cpu_slice_mut()?.copy_from_slice(b"frame");
+    /// pool.publish(buf)?;
+    /// pool.sync()?;
+    /// drop(pool);
+    ///
+    /// // 进程重启后
+    /// let pool = BufferPool::open_file(&dir, "frames")?;
+    /// let buf = pool.try_receive()?.unwrap();
+    /// assert_eq!(buf.as_cpu_slice()?, b"frame");
+    /// # drop(buf);
+    /// # std::fs::remove_dir_all(&dir)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_file(dir: impl AsRef<Path>, name: &str) -> Result<Self> {
+        Self::create_file_with_capacity(dir, name, DEFAULT_CAPACITY)
+    }
+
+    /// 创建指定容量的文件池，见 [`create_file()`](Self::create_file)
+    pub fn create_file_with_capacity(
+        dir: impl AsRef<Path>,
+        name: &str,
+        capacity: usize,
+    ) -> Result<Self> {
+        config::validate_name(name)?;
+        let dir = dir.as_ref();
+        let meta_path = file::pool_file(dir, name, "_meta");
+        let meta_region = MetaRegion::create_file(&meta_path, capacity, false)?;
+        let attach = AttachLock::shared(&meta_path)?;
+
+        let config = PoolConfig::new().capacity(capacity).backing_dir(dir);
+        let backend = Arc::new(FileBackend::new(dir));
+        Ok(Self::from_region(name, meta_region, backend, &config, Some(attach)))
+    }
+
+    /// 打开目录 `dir` 下的文件池
+    ///
+    /// 校验元数据区的 magic、版本和大小；若没有其他进程挂载该池（例如上次的进程已退出），
+    /// 还会执行 [`fsck()`](Self::fsck) 修复崩溃遗留的不一致状态。
+    pub fn open_file(dir: impl AsRef<Path>, name: &str) -> Result<Self> {
+        config::validate_name(name)?;
+        let dir = dir.as_ref();
+        let meta_path = file::pool_file(dir, name, "_meta");
+        let exclusive = AttachLock::try_exclusive(&meta_path)?;
+        let meta_region = MetaRegion::open_file(&meta_path)?;
+
+        let repair = exclusive.is_some();
+        let attach = match exclusive {
+            Some(attach) => attach,
+            None => AttachLock::shared(&meta_path)?,
+        };
+        let config = PoolConfig::new().backing_dir(dir);
+        let backend = Arc::new(FileBackend::new(dir));
+        let pool = Self::from_region(name, meta_region, backend, &config, Some(attach));
+        if repair {
+            pool.fsck()?;
+            if let Some(attach) = &pool.shared._attach {
+                attach.downgrade()?;
+            }
+        }
+        Ok(pool)
     }
 
     /// Open an existing buffer pool
     pub fn open(name: &str) -> Result<Self> {
-        let meta_name = format!("{}_meta", name);
-        let meta_region = MetaRegion::open(&meta_name)?;
+        Self::open_with_config(name, &PoolConfig::default())
+    }
 
-        Ok(Self {
-            name: name.to_string(),
-            meta_region,
-        })
+    fn from_region(
+        name: &str,
+        meta_region: MetaRegion,
+        cpu_backend: Arc<dyn StorageBackend>,
+        config: &PoolConfig,
+        attach: Option<AttachLock>,
+    ) -> Self {
+        // Heap memory has no huge page or NUMA support
+        let (huge_pages, numa_node) = match cpu_backend.storage_type() {
+            t if t == StorageType::Heap as u8 => (HugePages::Off, None),
+            _ => (
+                memory::resolve(config.huge_pages, config.backing_dir.as_deref()),
+                memory::resolve_node(config.numa_node),
+            ),
+        };
+        Self {
+            shared: Arc::new(PoolShared {
+                name: name.to_string(),
+                meta_region,
+                cpu_backend,
+                wakers: Wakers::default(),
+                config: config.clone(),
+                huge_pages,
+                numa_node,
+                _attach: attach,
+            }),
+        }
+    }
+
+    /// Create a pool handle from shared state held by a guard
+    pub(crate) fn from_shared(shared: Arc<PoolShared>) -> Self {
+        Self { shared }
+    }
+
+    /// Get the shared pool state
+    pub(crate) fn shared(&self) -> &Arc<PoolShared> {
+        &self.shared
     }
 
-    /// Get pool name
+    /// Get pool name (including the [`PoolConfig`] namespace)
     pub fn name(&self) -> &str {
-        &self.name
+        &self.shared.name
+    }
+
+    /// 实际生效的大页模式，可能低于 [`PoolConfig::huge_pages`] 请求的模式，见 [`crate::memory`]
+    pub fn huge_pages(&self) -> HugePages {
+        self.shared.huge_pages
+    }
+
+    /// 实际绑定的 NUMA 节点，单节点机器或未设置 [`PoolConfig::numa_node`] 时为 `None`
+    pub fn numa_node(&self) -> Option<u32> {
+        self.shared.numa_node
+    }
+
+    /// Check if buffer mappings are locked in memory ([`PoolConfig::mlock`])
+    pub fn is_mlocked(&self) -> bool {
+        self.shared.config.mlock
+    }
+
+    /// Get the directory holding the segment files, `None` for shared memory and in-memory pools
+    pub fn backing_dir(&self) -> Option<&Path> {
+        self.shared.backing_dir()
     }
 
     /// Get capacity
     pub fn capacity(&self) -> usize {
-        self.meta_region.capacity()
+        self.shared.meta_region.capacity()
     }
 
-    /// Generate buffer shm name
-    fn buffer_shm_name(&self, meta_index: u32) -> String {
-        format!("{}_buf_{}", self.name, meta_index)
+    /// Identify the data segment of a slot
+    fn segment_id(&self, meta_index: u32) -> SegmentId<'_> {
+        SegmentId {
+            pool: self.name(),
+            index: meta_index,
+        }
     }
 
     /// Acquire a buffer, blocking if pool is full
+    ///
+    /// 任何进程释放槽位都会立即唤醒等待方，见 [`crate::notify`]。
     pub fn acquire_cpu_blocking(&self, size: usize, timeout: Duration) -> Result<BufferGuard> {
-        let start = std::time::Instant::now();
+        let deadline = Instant::now() + timeout;
+        // Register before checking so a release in between is not missed
+        let waiter = self.shared.meta_region.waiter();
 
         loop {
-            match self.acquire_cpu(size) {
-                Ok(buf) => return Ok(buf),
-                Err(Error::SharedMemory(msg)) if msg.contains("full") => {
-                    if start.elapsed() >= timeout {
-                        return Err(Error::Timeout);
-                    }
-                    std::thread::sleep(Duration::from_millis(1));
-                }
-                Err(e) => return Err(e),
+            let seen = waiter.seq(RegionEvent::Freed);
+            if let Some(buf) = self.try_acquire_cpu(size)? {
+                return Ok(buf);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout);
             }
+            waiter.wait(RegionEvent::Freed, seen, deadline - now);
+        }
+    }
+
+    /// Acquire a CPU buffer, returning `None` if the pool is full
+    pub fn try_acquire_cpu(&self, size: usize) -> Result<Option<BufferGuard>> {
+        match self.acquire_cpu(size) {
+            Ok(buf) => Ok(Some(buf)),
+            Err(Error::SharedMemory(msg)) if msg.contains("full") => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
     /// Acquire a new CPU buffer
     pub fn acquire_cpu(&self, size: usize) -> Result<BufferGuard> {
-        // Allocate metadata slot
-        let meta_index = self.meta_region.alloc()?;
+        self.acquire_with(Arc::clone(&self.shared.cpu_backend), size, 0)
+    }
+
+    /// 使用已注册的存储后端分配 buffer，见 [`storage`]
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::UnknownStorage`]: `storage_type` 没有注册后端
+    pub fn acquire_storage(&self, storage_type: u8, size: usize) -> Result<BufferGuard> {
+        self.acquire_with(self.shared.backend(storage_type)?, size, 0)
+    }
 
-        // Create shared memory for buffer data
-        let shm_name = self.buffer_shm_name(meta_index);
-        let shm = SharedMemory::create(&shm_name, size)?;
+    fn acquire_with(
+        &self,
+        backend: Arc<dyn StorageBackend>,
+        size: usize,
+        device_id: u8,
+    ) -> Result<BufferGuard> {
+        // Allocate metadata slot
+        let meta_index = self.shared.meta_region.alloc()?;
+        let id = self.segment_id(meta_index);
+        let meta = self.shared.meta_region.get(meta_index)?;
+
+        // Reuse the slot's cached segment, else allocate the data, handing the slot back on failure
+        let is_cpu = backend.storage_type() == self.shared.cpu_backend.storage_type();
+        let numa_node = self.shared.numa_node.filter(|_| is_cpu);
+        let data = self.take_cached(meta, id, &backend, size).and_then(|cached| match cached {
+            Some(cached) => Ok(cached),
+            None => memory::with_node(numa_node, || backend.allocate(id, size, device_id))
+                .map(|segment| (segment, true)),
+        });
+        let (segment, fresh) = match data {
+            Ok(data) => data,
+            Err(e) => {
+                self.discard_slot(meta_index);
+                return Err(e);
+            }
+        };
 
         // Initialize metadata
-        let meta = self.meta_region.get(meta_index)?;
         meta.id.store(meta_index, Ordering::SeqCst);
         meta.ref_count.store(1, Ordering::SeqCst);
-        meta.storage_type.store(StorageType::Cpu as u8, Ordering::SeqCst);
-        meta.device_id.store(0, Ordering::SeqCst);
+        meta.storage_type.store(backend.storage_type(), Ordering::SeqCst);
+        meta.device_id.store(device_id, Ordering::SeqCst);
         meta.size.store(size as u64, Ordering::SeqCst);
+        meta.parent_index.store(u32::MAX, Ordering::SeqCst);
+        meta.view_offset.store(0, Ordering::SeqCst);
+        meta.clear_tensor();
+        meta.clear_info();
+        meta.flags.store(0, Ordering::SeqCst);
+        meta.numa_node
+            .store(numa_node.map_or(NUMA_NODE_NONE, |n| n as u8), Ordering::SeqCst);
+        meta.set_storage_handle(&[0; CUDA_IPC_HANDLE_SIZE]);
+        lock::reset(meta);
+
+        let prepared = segment.export_handle(meta).and_then(|()| {
+            match segment.cpu_ptr().filter(|_| is_cpu) {
+                Some(ptr) => self.prepare_cpu(ptr, segment.size(), fresh),
+                None => Ok(()),
+            }
+        });
+        if let Err(e) = prepared {
+            drop(segment);
+            let _ = backend.free(id, meta);
+            self.discard_slot(meta_index);
+            return Err(e);
+        }
 
-        let ref_count_ptr = &meta.ref_count as *const _;
-
-        // Create buffer data
-        let data = BufferData::Cpu(shm);
+        let meta_ptr = meta as *const _;
+        let data = BufferData::new(backend, segment);
 
         Ok(BufferGuard::new(
             data,
             meta_index,
             AccessMode::ReadWrite,
-            ref_count_ptr,
-        ).with_pool(self.name.clone()))
+            meta_ptr,
+        ).with_pool(self))
+    }
+
+    /// 接管新分配槽位上缓存的数据段，见 [`crate::cache`]
+    ///
+    /// 存储类型和大小一致时返回该段，以及其物理页是否已被回收（内容为零）；
+    /// 否则释放缓存段，返回 `None`。
+    fn take_cached(
+        &self,
+        meta: &BufferMeta,
+        id: SegmentId<'_>,
+        backend: &Arc<dyn StorageBackend>,
+        size: usize,
+    ) -> Result<Option<(Box<dyn StorageSegment>, bool)>> {
+        if meta.cache.load(Ordering::SeqCst) & CACHE_PRESENT == 0 {
+            return Ok(None);
+        }
+        let holder = self.shared.meta_region.holder()?;
+        let Some(bits) = cache::claim(&holder, meta, true) else {
+            return Ok(None);
+        };
+        let storage_type = meta.storage_type.load(Ordering::SeqCst);
+        let reusable = storage_type == backend.storage_type()
+            && meta.size.load(Ordering::SeqCst) == size as u64;
+        let segment = reusable.then(|| backend.import(id, meta).ok()).flatten();
+        if segment.is_none() {
+            if let Ok(cached) = self.shared.backend(storage_type) {
+                let _ = cached.free(id, meta);
+            }
+        }
+        cache::unclaim(meta, 0);
+        Ok(segment.map(|segment| (segment, bits & CACHE_TRIMMED != 0)))
+    }
+
+    /// 对 CPU buffer 的映射应用池的内存选项（大页、NUMA、预缺页、mlock）
+    ///
+    /// `fresh` 为 true 表示刚分配或刚回收、内容为零，只有这时才预缺页。
+    fn prepare_cpu(&self, ptr: *mut u8, len: usize, fresh: bool) -> Result<()> {
+        memory::advise(ptr, len, self.shared.huge_pages);
+        memory::bind(ptr, len, self.shared.numa_node);
+        if fresh && self.shared.config.prefault {
+            memory::prefault(ptr, len);
+        }
+        if self.shared.config.mlock {
+            memory::lock(ptr, len).map_err(|e| {
+                Error::SharedMemory(format!("mlock of {} bytes failed (RLIMIT_MEMLOCK?): {}", len, e))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Return a slot whose buffer could not be set up
+    fn discard_slot(&self, meta_index: u32) {
+        let _ = self.shared.meta_region.free(meta_index);
+        self.shared.wakers.wake();
+    }
+
+    /// 分配 CPU buffer 并写入张量元数据
+    ///
+    /// buffer 大小为 `shape` 各维乘积乘以 `dtype` 元素大小，
+    /// strides 按 C 连续布局写入 [`BufferMeta`](crate::BufferMeta)。
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use xmem_core::{BufferPool, DType};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let pool = BufferPool::create("/my_pool_array_doc")?;
+    /// let buf = pool.acquire_cpu_array(&[480, 640, 3], DType::UInt8)?;
+    /// assert_eq!(buf.meta()?.shape(), vec![480, 640, 3]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn acquire_cpu_array(&self, shape: &[usize], dtype: DType) -> Result<BufferGuard> {
+        let size = shape
+            .iter()
+            .try_fold(dtype.size(), |acc, &d| acc.checked_mul(d))
+            .ok_or_else(|| Error::InvalidShape(format!("{:?} overflows usize", shape)))?;
+
+        let buf = self.acquire_cpu(size)?;
+        buf.set_tensor(shape, dtype)?;
+        Ok(buf)
     }
 
     /// Get an existing buffer (read-only)
+    ///
+    /// 获取共享读锁，若其他进程持有写锁则阻塞等待。
+    /// 新分配（`acquire_*`）的 buffer 不持有锁。
+    ///
+    /// 同时持有读锁的进程数没有单独上限，但每个池最多有
+    /// [`MAX_PROCESSES`](crate::process::MAX_PROCESSES) 个进程加锁，
+    /// 超出时返回 [`Error::TooManyProcesses`]。
     pub fn get(&self, meta_index: u32) -> Result<BufferGuard> {
-        self.get_with_mode(meta_index, AccessMode::ReadOnly)
+        self.get_with_mode(meta_index, AccessMode::ReadOnly, LockWait::Block)
     }
 
     /// Get an existing buffer (read-write)
+    ///
+    /// 获取独占写锁，若其他进程持有读锁或写锁则阻塞等待。
     pub fn get_mut(&self, meta_index: u32) -> Result<BufferGuard> {
-        self.get_with_mode(meta_index, AccessMode::ReadWrite)
+        self.get_with_mode(meta_index, AccessMode::ReadWrite, LockWait::Block)
+    }
+
+    /// Try to get a buffer (read-only), failing with [`Error::Locked`] if write-locked
+    ///
+    /// 进程数上限同 [`BufferPool::get`]。
+    pub fn try_get(&self, meta_index: u32) -> Result<BufferGuard> {
+        self.get_with_mode(meta_index, AccessMode::ReadOnly, LockWait::Try)
     }
 
-    fn get_with_mode(&self, meta_index: u32, mode: AccessMode) -> Result<BufferGuard> {
+    /// Try to get a buffer (read-write), failing with [`Error::Locked`] if locked
+    pub fn try_get_mut(&self, meta_index: u32) -> Result<BufferGuard> {
+        self.get_with_mode(meta_index, AccessMode::ReadWrite, LockWait::Try)
+    }
+
+    /// Get a buffer (read-only), failing with [`Error::Timeout`] if the lock is not acquired in time
+    pub fn get_timeout(&self, meta_index: u32, timeout: Duration) -> Result<BufferGuard> {
+        self.get_with_mode(meta_index, AccessMode::ReadOnly, LockWait::Timeout(timeout))
+    }
+
+    /// Get a buffer (read-write), failing with [`Error::Timeout`] if the lock is not acquired in time
+    pub fn get_mut_timeout(&self, meta_index: u32, timeout: Duration) -> Result<BufferGuard> {
+        self.get_with_mode(meta_index, AccessMode::ReadWrite, LockWait::Timeout(timeout))
+    }
+
+    fn get_with_mode(&self, meta_index: u32, mode: AccessMode, wait: LockWait) -> Result<BufferGuard> {
         // Get metadata
-        let meta = self.meta_region.get(meta_index)?;
-        let ref_count_ptr = &meta.ref_count as *const _;
+        let meta = self.shared.meta_region.get(meta_index)?;
+        let meta_ptr = meta as *const _;
+
+        // Take the cross-process lock before touching the ref count
+        let lock = self.lock_root(meta_index, mode, wait)?;
+
+        // Take a reference only while the buffer is live: a slot released
+        // since the lookup must not be brought back to life
+        let data = self.open_data(meta_index).and_then(|data| {
+            add_ref_if_live(meta)
+                .then_some(data)
+                .ok_or(Error::BufferNotFound(meta_index))
+        });
+        let data = match data {
+            Ok(data) => data,
+            Err(e) => {
+                if let Some(lock) = lock {
+                    lock.release();
+                }
+                return Err(e);
+            }
+        };
+
+        let guard = BufferGuard::new(data, meta_index, mode, meta_ptr).with_pool(self);
+        Ok(match lock {
+            Some(lock) => lock.attach(guard),
+            None => guard,
+        })
+    }
+
+    /// Take the cross-process lock `mode` needs for `meta_index`
+    ///
+    /// 视图与根 buffer 共享数据，锁加在根 buffer 上；
+    /// seqlock 读者用序列号校验读取，不加锁（返回 `None`）。
+    fn lock_root(&self, meta_index: u32, mode: AccessMode, wait: LockWait) -> Result<Option<RootLock<'_>>> {
+        let meta = self.shared.meta_region.get(meta_index)?;
+        let root = self.root_meta(meta_index)?;
+        let seqlock = (meta.flags.load(Ordering::SeqCst) | root.flags.load(Ordering::SeqCst))
+            & FLAG_SEQLOCK
+            != 0;
+        let kind = match mode {
+            AccessMode::ReadOnly if seqlock => return Ok(None),
+            AccessMode::ReadOnly => LockKind::Read,
+            AccessMode::ReadWrite => LockKind::Write,
+        };
+        let holder = self.shared.meta_region.holder()?;
+        lock::lock(&holder, root, meta_index, kind, wait)?;
+        Ok(Some(RootLock {
+            kind,
+            root,
+            owner: Arc::clone(holder.registration()),
+        }))
+    }
+
+    /// Metadata of the buffer owning the data of `meta_index` (itself unless a view)
+    fn root_meta(&self, meta_index: u32) -> Result<&BufferMeta> {
+        let region = &self.shared.meta_region;
+        let mut meta = region.get(meta_index)?;
+        // Bounded walk: a corrupted parent chain must not loop forever
+        for _ in 0..region.capacity() {
+            if meta.storage_type.load(Ordering::SeqCst) != StorageType::View as u8 {
+                return Ok(meta);
+            }
+            meta = region.get(meta.parent_index.load(Ordering::SeqCst))?;
+        }
+        Err(Error::SharedMemory("view parent chain is cyclic".to_string()))
+    }
+
+    /// Open another guard on a buffer this process already holds
+    ///
+    /// 不获取读写锁：调用方持有的 guard 已具备相应访问权。
+    pub(crate) fn share(&self, meta_index: u32, mode: AccessMode) -> Result<BufferGuard> {
+        let guard = self.wrap_existing(meta_index, mode)?;
+        guard.meta()?.ref_count.fetch_add(1, Ordering::SeqCst);
+        Ok(guard)
+    }
+
+    /// Wrap a reference handed over from elsewhere, taking the lock `mode` needs
+    ///
+    /// 引用计数不变；加锁失败时引用仍归调用方所有。
+    pub(crate) fn wrap_locked(&self, meta_index: u32, mode: AccessMode, wait: LockWait) -> Result<BufferGuard> {
+        let lock = self.lock_root(meta_index, mode, wait)?;
+        match self.wrap_existing(meta_index, mode) {
+            Ok(guard) => Ok(match lock {
+                Some(lock) => lock.attach(guard),
+                None => guard,
+            }),
+            Err(e) => {
+                if let Some(lock) = lock {
+                    lock.release();
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Wrap a reference this process already owns in a guard (ref count unchanged)
+    pub(crate) fn wrap_existing(&self, meta_index: u32, mode: AccessMode) -> Result<BufferGuard> {
+        let meta = self.shared.meta_region.get(meta_index)?;
+        let data = self.open_data(meta_index)?;
+        Ok(BufferGuard::new(data, meta_index, mode, meta as *const _).with_pool(self))
+    }
+
+    /// Get the metadata region
+    pub(crate) fn meta_region(&self) -> &MetaRegion {
+        &self.shared.meta_region
+    }
+
+    /// Open buffer data through the backend registered for its storage type
+    fn open_data(&self, meta_index: u32) -> Result<BufferData> {
+        let meta = self.shared.meta_region.get(meta_index)?;
+        let storage_type = meta.storage_type.load(Ordering::SeqCst);
+
+        if storage_type == StorageType::View as u8 {
+            let parent = meta.parent_index.load(Ordering::SeqCst);
+            if parent == meta_index {
+                return Err(Error::SharedMemory("view refers to itself".to_string()));
+            }
+            let offset = meta.view_offset.load(Ordering::SeqCst) as usize;
+            let len = meta.size.load(Ordering::SeqCst) as usize;
+            return Ok(self.open_data(parent)?.into_view(offset, len));
+        }
+
+        let backend = self.shared.backend(storage_type)?;
+        let segment = backend.import(self.segment_id(meta_index), meta)?;
+        if storage_type == self.shared.cpu_backend.storage_type() {
+            if let Some(ptr) = segment.cpu_ptr() {
+                self.prepare_cpu(ptr, segment.size(), false)?;
+            }
+        }
+        Ok(BufferData::new(backend, segment))
+    }
 
-        // Increment ref count
-        meta.ref_count.fetch_add(1, Ordering::SeqCst);
+    /// 在父 buffer 上创建子区域视图
+    ///
+    /// 分配一个视图类型的元数据槽，记录父 buffer 索引和偏移，并持有父 buffer 的一个引用。
+    /// 视图被回收时会释放该引用。对视图调用 [`get()`](Self::get) 只映射 `[offset, offset + len)`。
+    ///
+    /// # 参数
+    ///
+    /// - `parent`: 父 buffer 的元数据索引（可以是另一个视图）
+    /// - `offset`/`len`: 子区域在父 buffer 中的字节范围
+    /// - `desc`: 可选的张量描述，写入视图的元数据
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use xmem_core::BufferPool;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let pool = BufferPool::create("/my_pool_view_doc")?;
+    /// let frame = pool.acquire_cpu(640 * 480 * 3 / 2)?;
+    ///
+    /// // NV12: Y 平面与 UV 平面
+    /// let y = pool.create_view(frame.meta_index(), 0, 640 * 480, None)?;
+    /// let uv = pool.create_view(frame.meta_index(), 640 * 480, 640 * 480 / 2, None)?;
+    /// assert_eq!(uv.as_cpu_slice()?.len(), 640 * 480 / 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_view(
+        &self,
+        parent: u32,
+        offset: usize,
+        len: usize,
+        desc: Option<&TensorDesc>,
+    ) -> Result<BufferGuard> {
+        self.create_view_with_mode(parent, offset, len, desc, AccessMode::ReadWrite)
+    }
 
-        // Open buffer data based on storage type
-        let storage_type_val = meta.storage_type.load(Ordering::SeqCst);
-        let storage_type = StorageType::from_u8(storage_type_val)
-            .ok_or_else(|| Error::SharedMemory("invalid storage type".to_string()))?;
+    pub(crate) fn create_view_with_mode(
+        &self,
+        parent: u32,
+        offset: usize,
+        len: usize,
+        desc: Option<&TensorDesc>,
+        mode: AccessMode,
+    ) -> Result<BufferGuard> {
+        let parent_meta = self.shared.meta_region.get(parent)?;
+        if parent_meta.ref_count.load(Ordering::SeqCst) <= 0 {
+            return Err(Error::BufferNotFound(parent));
+        }
 
-        let data = match storage_type {
-            StorageType::Cpu => {
-                let shm_name = self.buffer_shm_name(meta_index);
-                let shm = SharedMemory::open(&shm_name)?;
-                BufferData::Cpu(shm)
+        let parent_size = parent_meta.size.load(Ordering::SeqCst) as usize;
+        match offset.checked_add(len) {
+            Some(end) if end <= parent_size => {}
+            _ => {
+                return Err(Error::OutOfBounds {
+                    offset,
+                    len,
+                    size: parent_size,
+                })
             }
-            #[cfg(feature = "cuda")]
-            StorageType::Cuda => {
-                use crate::cuda::{CudaBuffer, CudaIpcHandle};
+        }
 
-                let mut handle = CudaIpcHandle::default();
-                handle.reserved.copy_from_slice(&meta.cuda_ipc_handle);
+        if let Some(desc) = desc {
+            if desc.shape.len() > MAX_NDIM {
+                return Err(Error::InvalidShape(format!(
+                    "ndim {} exceeds maximum {}",
+                    desc.shape.len(),
+                    MAX_NDIM
+                )));
+            }
+            match desc.nbytes() {
+                Some(n) if n <= len => {}
+                _ => {
+                    return Err(Error::InvalidShape(format!(
+                        "{:?} {} does not fit in {} bytes",
+                        desc.shape, desc.dtype, len
+                    )))
+                }
+            }
+        }
+
+        // Map the parent before touching any shared state
+        let parent_data = self.open_data(parent)?;
+        let meta_index = self.shared.meta_region.alloc()?;
 
-                let cuda_buf = CudaBuffer::from_ipc_handle(
-                    meta.device_id.load(Ordering::SeqCst) as i32,
-                    &handle,
-                    meta.size.load(Ordering::SeqCst) as usize,
-                )?;
-                BufferData::Cuda(cuda_buf)
+        // The view keeps its parent alive until it is recycled. The parent may
+        // have been released since the check above, so never revive a count of 0.
+        if !add_ref_if_live(parent_meta) {
+            self.discard_slot(meta_index);
+            return Err(Error::BufferNotFound(parent));
+        }
+
+        let meta = self.shared.meta_region.get(meta_index)?;
+        meta.id.store(meta_index, Ordering::SeqCst);
+        meta.ref_count.store(1, Ordering::SeqCst);
+        meta.storage_type.store(StorageType::View as u8, Ordering::SeqCst);
+        meta.device_id
+            .store(parent_meta.device_id.load(Ordering::SeqCst), Ordering::SeqCst);
+        meta.size.store(len as u64, Ordering::SeqCst);
+        meta.parent_index.store(parent, Ordering::SeqCst);
+        meta.view_offset.store(offset as u64, Ordering::SeqCst);
+        meta.flags.store(0, Ordering::SeqCst);
+        meta.numa_node
+            .store(parent_meta.numa_node.load(Ordering::SeqCst), Ordering::SeqCst);
+        meta.clear_info();
+        lock::reset(meta);
+        let tensor = match desc {
+            Some(desc) => meta.set_tensor(&desc.shape, desc.dtype),
+            None => {
+                meta.clear_tensor();
+                Ok(())
             }
         };
+        if let Err(e) = tensor {
+            self.discard_slot(meta_index);
+            if self.release(parent)? <= 0 {
+                self.release_buffer(parent)?;
+            }
+            return Err(e);
+        }
 
-        Ok(BufferGuard::new(data, meta_index, mode, ref_count_ptr)
-            .with_pool(self.name.clone()))
+        let meta_ptr = meta as *const _;
+        let data = parent_data.into_view(offset, len);
+
+        Ok(BufferGuard::new(data, meta_index, mode, meta_ptr).with_pool(self))
     }
 
     /// Set reference count for a buffer
     pub fn set_ref_count(&self, meta_index: u32, count: i32) -> Result<()> {
-        let meta = self.meta_region.get(meta_index)?;
+        let meta = self.shared.meta_region.get(meta_index)?;
         meta.ref_count.store(count, Ordering::SeqCst);
         Ok(())
     }
 
     /// Add reference to a buffer
     pub fn add_ref(&self, meta_index: u32) -> Result<i32> {
-        let meta = self.meta_region.get(meta_index)?;
+        let meta = self.shared.meta_region.get(meta_index)?;
         Ok(meta.ref_count.fetch_add(1, Ordering::SeqCst) + 1)
     }
 
-    /// Release a buffer (decrement ref count)
-    pub fn release(&self, meta_index: u32) -> Result<i32> {
-        let meta = self.meta_region.get(meta_index)?;
-        Ok(meta.ref_count.fetch_sub(1, Ordering::SeqCst) - 1)
+    /// Release a buffer (decrement ref count)
+    pub fn release(&self, meta_index: u32) -> Result<i32> {
+        let meta = self.shared.meta_region.get(meta_index)?;
+        Ok(meta.ref_count.fetch_sub(1, Ordering::SeqCst) - 1)
+    }
+
+    /// Get current reference count
+    pub fn ref_count(&self, meta_index: u32) -> Result<i32> {
+        let meta = self.shared.meta_region.get(meta_index)?;
+        Ok(meta.ref_count.load(Ordering::SeqCst))
+    }
+
+    /// Release a buffer back to the pool (called when ref_count reaches 0)
+    pub fn release_buffer(&self, meta_index: u32) -> Result<()> {
+        let meta = self.shared.meta_region.get(meta_index)?;
+        let storage_type = meta.storage_type.load(Ordering::SeqCst);
+        let parent = match storage_type {
+            v if v == StorageType::View as u8 => Some(meta.parent_index.load(Ordering::SeqCst)),
+            _ => None,
+        };
+
+        // Free the data, or keep it for reuse; processes still mapping it keep their mapping
+        let cached = parent.is_none()
+            && self.shared.config.cache_segments
+            && storage_type == self.shared.cpu_backend.storage_type();
+        if cached {
+            cache::put(meta);
+        } else if parent.is_none() {
+            if let Ok(backend) = self.shared.backend(storage_type) {
+                let _ = backend.free(self.segment_id(meta_index), meta);
+            }
+        }
+        self.shared.meta_region.free(meta_index)?;
+        self.shared.wakers.wake();
+        if cached && self.shared.config.max_cached_bytes.is_some() {
+            self.trim_with(None, self.shared.config.max_cached_bytes)?;
+        }
+
+        // Drop the reference a view holds on its parent
+        if let Some(parent) = parent {
+            if self.release(parent)? <= 0 {
+                self.release_buffer(parent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 回收空闲 buffer 缓存段占用的内存，见 [`crate::cache`]
+    ///
+    /// 释放空闲至少 [`PoolConfig::trim_idle_after`] 的缓存段的物理页，
+    /// 并在缓存总量超过 [`PoolConfig::max_cached_bytes`] 时从最早释放的段开始删除。
+    /// 其他进程正在复用或回收的段会被跳过。
+    ///
+    /// 未设置 `trim_idle_after` 时不看空闲时间，所有尚未回收的缓存段都会释放物理页
+    /// （段本身保留）。只在 Linux 上释放物理页，其他平台只按字节预算删除段。
+    pub fn trim(&self) -> Result<TrimReport> {
+        let config = &self.shared.config;
+        let idle = config.trim_idle_after.unwrap_or(Duration::ZERO);
+        self.trim_with(Some(idle), config.max_cached_bytes)
+    }
+
+    /// Release pages of segments idle for `idle`, then delete the oldest beyond `budget`
+    fn trim_with(&self, idle: Option<Duration>, budget: Option<u64>) -> Result<TrimReport> {
+        let region = &self.shared.meta_region;
+        let mut report = TrimReport::default();
+
+        // Cached slots, oldest release first
+        let mut cached = Vec::new();
+        for index in 0..region.high_water() {
+            let meta = region.get(index)?;
+            let state = meta.cache.load(Ordering::SeqCst);
+            if state & CACHE_PRESENT != 0 {
+                let released_at = meta.timestamp.load(Ordering::SeqCst);
+                cached.push((index, meta, state, released_at, meta.size.load(Ordering::SeqCst)));
+            }
+        }
+        if cached.is_empty() {
+            return Ok(report);
+        }
+        cached.sort_by_key(|&(_, _, _, released_at, _)| released_at);
+        report.cached_bytes = cached.iter().map(|&(.., size)| size).sum();
+        let holder = region.holder()?;
+
+        let now = cache::now_ms();
+        for (index, meta, state, released_at, size) in cached {
+            let over_budget = budget.is_some_and(|budget| report.cached_bytes > budget);
+            let idle_for = Duration::from_millis(now.saturating_sub(released_at));
+            let stale = state & CACHE_TRIMMED == 0 && idle.is_some_and(|idle| idle_for >= idle);
+            if !over_budget && !stale {
+                continue;
+            }
+            // Being reused or trimmed by someone else
+            let Some(bits) = cache::claim(&holder, meta, false) else {
+                continue;
+            };
+            let id = self.segment_id(index);
+            let storage_type = meta.storage_type.load(Ordering::SeqCst);
+            let backend = self.shared.backend(storage_type);
+            if over_budget {
+                if let Ok(backend) = backend {
+                    let _ = backend.free(id, meta);
+                }
+                cache::unclaim(meta, 0);
+                report.cached_bytes -= size;
+                report.unlinked.push(index);
+                continue;
+            }
+
+            let shared = storage_type != StorageType::Heap as u8;
+            let released = backend
+                .and_then(|backend| backend.import(id, meta))
+                .is_ok_and(|segment| {
+                    segment
+                        .cpu_ptr()
+                        .is_some_and(|ptr| memory::release(ptr, segment.size(), shared))
+                });
+            if released {
+                cache::unclaim(meta, bits | CACHE_TRIMMED);
+                report.released.push(index);
+            } else {
+                cache::unclaim(meta, bits);
+            }
+        }
+        Ok(report)
+    }
+
+    /// 订阅释放/发布通知
+    ///
+    /// 返回的 [`Notifier`] 可注册到事件循环，详见 [`crate::notify`]。
+    /// Linux 上任何进程的释放/发布都会触发它（由后台线程转发）；
+    /// 其他平台只反映本进程内的事件，等待方需另外按
+    /// [`POLL_INTERVAL`](crate::notify::POLL_INTERVAL) 轮询。
+    pub fn notifier(&self) -> Result<Notifier> {
+        let notifier = self.shared.wakers.subscribe()?;
+        #[cfg(target_os = "linux")]
+        for event in [RegionEvent::Freed, RegionEvent::Published] {
+            PoolShared::watch(&self.shared, event)?;
+        }
+        Ok(notifier)
+    }
+
+    /// 发布 buffer，交由一个接收方处理
+    ///
+    /// guard 持有的引用转移给接收方（[`try_receive`](Self::try_receive)），
+    /// 并唤醒所有进程中阻塞的接收方及本进程内的 [`Notifier`] 订阅者。发布时间写入 [`BufferMeta::timestamp`](crate::BufferMeta)。
+    pub fn publish(&self, guard: BufferGuard) -> Result<()> {
+        if guard.pool_name() != Some(self.name()) {
+            return Err(Error::BufferNotFound(guard.meta_index()));
+        }
+        let meta = guard.meta()?;
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        meta.timestamp.store(now_ms, Ordering::SeqCst);
+        meta.publish_seq
+            .store(self.shared.meta_region.next_publish_seq(), Ordering::SeqCst);
+        meta.flags.fetch_or(FLAG_PUBLISHED, Ordering::SeqCst);
+
+        guard.forget();
+        self.shared.meta_region.notify_published();
+        self.shared.wakers.wake();
+        Ok(())
     }
 
-    /// Get current reference count
-    pub fn ref_count(&self, meta_index: u32) -> Result<i32> {
-        let meta = self.meta_region.get(meta_index)?;
-        Ok(meta.ref_count.load(Ordering::SeqCst))
+    /// 接收最早发布的 buffer（非阻塞）
+    ///
+    /// 每个发布的 buffer 只会被一个接收方取走，返回的 guard 接管发布者的引用，
+    /// 为 ReadWrite 模式且不持有读写锁。没有待接收的 buffer 时返回 `None`。
+    pub fn try_receive(&self) -> Result<Option<BufferGuard>> {
+        let region = &self.shared.meta_region;
+        loop {
+            let head = region.publish_seq();
+            let oldest = (0..region.high_water())
+                .filter_map(|i| region.get(i).ok().map(|meta| (i, meta)))
+                .filter(|(_, meta)| meta.flags.load(Ordering::SeqCst) & FLAG_PUBLISHED != 0)
+                .max_by_key(|(_, meta)| head.wrapping_sub(meta.publish_seq.load(Ordering::SeqCst)));
+            let Some((index, meta)) = oldest else {
+                return Ok(None);
+            };
+
+            // Another receiver may claim it first
+            if meta.flags.fetch_and(!FLAG_PUBLISHED, Ordering::SeqCst) & FLAG_PUBLISHED == 0 {
+                continue;
+            }
+            return self.wrap_existing(index, AccessMode::ReadWrite).map(Some);
+        }
     }
 
-    /// Release a buffer back to the pool (called when ref_count reaches 0)
-    pub fn release_buffer(&self, meta_index: u32) -> Result<()> {
-        // Note: SharedMemory for buffer data is NOT unlinked
-        // It will be reused when this meta_index is allocated again
-        self.meta_region.free(meta_index)
+    /// 接收发布的 buffer，最多等待 `timeout`
+    ///
+    /// 任何进程的发布都会立即唤醒等待方（非 Linux 平台按
+    /// [`POLL_INTERVAL`](crate::notify::POLL_INTERVAL) 轮询），见 [`crate::notify`]。
+    pub fn receive_timeout(&self, timeout: Duration) -> Result<BufferGuard> {
+        let deadline = Instant::now() + timeout;
+        // Register before checking so a publish in between is not missed
+        let waiter = self.shared.meta_region.waiter();
+        loop {
+            let seen = waiter.seq(RegionEvent::Published);
+            if let Some(guard) = self.try_receive()? {
+                return Ok(guard);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout);
+            }
+            waiter.wait(RegionEvent::Published, seen, deadline - now);
+        }
     }
 
     /// Check if a buffer should be released (ref_count == 0)
     pub fn try_release(&self, meta_index: u32) -> Result<bool> {
-        let meta = self.meta_region.get(meta_index)?;
+        let meta = self.shared.meta_region.get(meta_index)?;
         let ref_count = meta.ref_count.load(Ordering::SeqCst);
 
         if ref_count <= 0 {
@@ -283,46 +1233,144 @@ impl BufferPool {
         Ok(indices)
     }
 
-    /// Acquire a new CUDA buffer
-    #[cfg(feature = "cuda")]
-    pub fn acquire_cuda(&self, size: usize, device_id: i32) -> Result<BufferGuard> {
-        use crate::cuda::CudaBuffer;
+    /// 将元数据区和所有存活 buffer 的数据刷写到磁盘
+    ///
+    /// 仅对文件池有意义，其他池为无操作。单个 buffer 可用 [`BufferGuard::sync`]。
+    pub fn sync(&self) -> Result<()> {
+        let region = &self.shared.meta_region;
+        let cpu_type = self.shared.cpu_backend.storage_type();
+        for index in 0..region.high_water() {
+            let meta = region.get(index)?;
+            if meta.ref_count.load(Ordering::SeqCst) > 0
+                && meta.storage_type.load(Ordering::SeqCst) == cpu_type
+            {
+                let segment = self.shared.cpu_backend.import(self.segment_id(index), meta)?;
+                segment.sync()?;
+            }
+        }
+        region.sync()
+    }
 
-        // Allocate metadata slot
-        let meta_index = self.meta_region.alloc()?;
+    /// 检查并修复元数据区
+    ///
+    /// - 存储缺失或被截断的 buffer（只检查本池 CPU 后端的存储）及父 buffer 已失效的视图被回收
+    /// - 释放已退出进程持有的读写锁
+    /// - 写入中断的 seqlock buffer 序列号恢复为偶数（内容可能不完整）
+    /// - 空闲槽上的发布标记被清除，已不存在的缓存段被遗忘
+    /// - 空闲链表和分配计数与存活 buffer 不一致时重建
+    ///
+    /// [`open_file()`](Self::open_file) 在独占挂载时会自动调用。
+    /// 修复过程假设没有其他进程同时分配或释放 buffer。
+    pub fn fsck(&self) -> Result<FsckReport> {
+        let region = &self.shared.meta_region;
+        let holder = region.holder()?;
+        let high = region.high_water();
+        let mut report = FsckReport::default();
+        let metas = (0..high)
+            .map(|index| region.get(index))
+            .collect::<Result<Vec<_>>>()?;
+        let mut live: Vec<bool> = metas
+            .iter()
+            .map(|meta| meta.ref_count.load(Ordering::SeqCst) > 0)
+            .collect();
+
+        // Buffers whose storage is gone or shorter than recorded
+        let cpu_type = self.shared.cpu_backend.storage_type();
+        for (index, meta) in (0..high).zip(&metas) {
+            if !live[index as usize] || meta.storage_type.load(Ordering::SeqCst) != cpu_type {
+                continue;
+            }
+            let size = meta.size.load(Ordering::SeqCst) as usize;
+            let intact = self
+                .shared
+                .cpu_backend
+                .import(self.segment_id(index), meta)
+                .is_ok_and(|segment| segment.size() >= size);
+            if !intact {
+                live[index as usize] = false;
+                report.reclaimed.push(index);
+            }
+        }
 
-        // Allocate CUDA buffer
-        let cuda_buf = CudaBuffer::alloc(device_id, size)?;
-        let ipc_handle = cuda_buf.ipc_handle();
+        // Views on dead parents, repeated for views of views
+        let view = StorageType::View as u8;
+        loop {
+            let orphans: Vec<u32> = (0..high)
+                .filter(|&index| {
+                    let meta = metas[index as usize];
+                    let parent = meta.parent_index.load(Ordering::SeqCst);
+                    live[index as usize]
+                        && meta.storage_type.load(Ordering::SeqCst) == view
+                        && !(parent < high && live[parent as usize])
+                })
+                .collect();
+            if orphans.is_empty() {
+                break;
+            }
+            for index in orphans {
+                live[index as usize] = false;
+                report.reclaimed.push(index);
+            }
+        }
 
-        // Initialize metadata
-        let meta = self.meta_region.get(meta_index)?;
-        meta.id.store(meta_index, Ordering::SeqCst);
-        meta.ref_count.store(1, Ordering::SeqCst);
-        meta.storage_type.store(StorageType::Cuda as u8, Ordering::SeqCst);
-        meta.device_id.store(device_id as u8, Ordering::SeqCst);
-        meta.size.store(size as u64, Ordering::SeqCst);
+        for &index in &report.reclaimed {
+            let meta = metas[index as usize];
+            if meta.storage_type.load(Ordering::SeqCst) == cpu_type {
+                let _ = self.shared.cpu_backend.free(self.segment_id(index), meta);
+            }
+            meta.ref_count.store(0, Ordering::SeqCst);
+        }
 
-        // Copy IPC handle to metadata
-        unsafe {
-            std::ptr::copy_nonoverlapping(
-                ipc_handle.reserved.as_ptr(),
-                meta.cuda_ipc_handle.as_ptr() as *mut u8,
-                64,
-            );
+        // Cached segments of free slots that are gone or truncated
+        for (index, meta) in (0..high).zip(&metas) {
+            if live[index as usize] || meta.cache.load(Ordering::SeqCst) & CACHE_PRESENT == 0 {
+                continue;
+            }
+            let size = meta.size.load(Ordering::SeqCst) as usize;
+            let intact = meta.storage_type.load(Ordering::SeqCst) == cpu_type
+                && self
+                    .shared
+                    .cpu_backend
+                    .import(self.segment_id(index), meta)
+                    .is_ok_and(|segment| segment.size() >= size);
+            if !intact {
+                meta.cache.store(0, Ordering::SeqCst);
+                report.cache_dropped.push(index);
+            }
         }
 
-        let ref_count_ptr = &meta.ref_count as *const _;
+        for (index, meta) in (0..high).zip(&metas) {
+            if !live[index as usize] {
+                meta.flags.fetch_and(!FLAG_PUBLISHED, Ordering::SeqCst);
+                continue;
+            }
+            if lock::recover(&holder, meta) {
+                report.locks_recovered.push(index);
+            }
+            if meta.flags.load(Ordering::SeqCst) & FLAG_SEQLOCK != 0
+                && meta.seq.load(Ordering::SeqCst) % 2 == 1
+            {
+                meta.seq.fetch_add(1, Ordering::SeqCst);
+                report.torn_writes.push(index);
+            }
+        }
 
-        // Create buffer data
-        let data = BufferData::Cuda(cuda_buf);
+        let is_free = |index: u32| !live[index as usize];
+        if !report.reclaimed.is_empty() || !region.free_list_consistent(is_free) {
+            region.rebuild_free_list(is_free);
+            report.free_list_rebuilt = true;
+        }
+        Ok(report)
+    }
 
-        Ok(BufferGuard::new(
-            data,
-            meta_index,
-            AccessMode::ReadWrite,
-            ref_count_ptr,
-        ).with_pool(self.name.clone()))
+    /// Acquire a new CUDA buffer
+    #[cfg(feature = "cuda")]
+    pub fn acquire_cuda(&self, size: usize, device_id: i32) -> Result<BufferGuard> {
+        self.acquire_with(
+            self.shared.backend(StorageType::Cuda as u8)?,
+            size,
+            device_id as u8,
+        )
     }
 
     /// Preallocate CUDA buffers
@@ -341,9 +1389,43 @@ impl BufferPool {
     }
 }
 
+/// [`BufferPool::trim`] 的结果，各列表为元数据索引
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrimReport {
+    /// Cached segments whose pages were released
+    pub released: Vec<u32>,
+    /// Cached segments deleted to stay within the byte budget
+    pub unlinked: Vec<u32>,
+    /// Bytes of segments still cached, released ones included
+    pub cached_bytes: u64,
+}
+
+/// [`BufferPool::fsck`] 的检查结果，各列表为元数据索引
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsckReport {
+    /// Slots freed because their storage was missing or truncated, or their parent was gone
+    pub reclaimed: Vec<u32>,
+    /// Slots whose locks were held by processes that have exited
+    pub locks_recovered: Vec<u32>,
+    /// Seqlock buffers whose writer died mid-write
+    pub torn_writes: Vec<u32>,
+    /// Free slots whose cached segment (see [`crate::cache`]) was missing or truncated
+    pub cache_dropped: Vec<u32>,
+    /// Whether the free list and allocation count were rebuilt
+    pub free_list_rebuilt: bool,
+}
+
+impl FsckReport {
+    /// Check if nothing needed repair
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shm::SharedMemory;
 
     fn unique_name() -> String {
         use std::time::{SystemTime, UNIX_EPOCH};
@@ -509,6 +1591,459 @@ mod tests {
         assert!(pool.try_release(idx).unwrap());
     }
 
+    #[test]
+    fn test_segment_lifetime() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        let shm_name = |index: u32| format!("{}_buf_{}", name, index);
+
+        // Dropping the creator guard keeps the segment while references remain
+        let mut buf = pool.acquire_cpu(4).unwrap();
+        buf.as_cpu_slice_mut().unwrap().copy_from_slice(b"live");
+        let idx = buf.meta_index();
+        pool.add_ref(idx).unwrap();
+        drop(buf);
+        assert!(SharedMemory::open(&shm_name(idx)).is_ok());
+        assert_eq!(pool.get(idx).unwrap().as_cpu_slice().unwrap(), b"live");
+
+        // Handles that only opened the pool do not unlink anything
+        drop(BufferPool::open(&name).unwrap());
+        assert!(SharedMemory::open(&shm_name(idx)).is_ok());
+
+        // The last reference unlinks it; existing mappings stay valid
+        let mapped = SharedMemory::open(&shm_name(idx)).unwrap();
+        assert_eq!(pool.release(idx).unwrap(), 0);
+        pool.release_buffer(idx).unwrap();
+        assert!(SharedMemory::open(&shm_name(idx)).is_err());
+        assert_eq!(&mapped.as_slice()[..4], b"live");
+
+        // Dropping the creator unlinks segments that are still referenced
+        let kept = pool.acquire_cpu(4).unwrap();
+        let kept_idx = kept.meta_index();
+        kept.forget();
+        assert!(SharedMemory::open(&shm_name(kept_idx)).is_ok());
+        drop(pool);
+        assert!(SharedMemory::open(&shm_name(kept_idx)).is_err());
+    }
+
+    #[test]
+    fn test_segment_cache_reuse() {
+        let name = unique_name();
+        let pool =
+            BufferPool::create_with_config(&name, &PoolConfig::new().cache_segments(true)).unwrap();
+        let shm_name = |index: u32| format!("{}_buf_{}", name, index);
+
+        let mut buf = pool.acquire_cpu(4096).unwrap();
+        buf.as_cpu_slice_mut().unwrap()[..4].copy_from_slice(b"kept");
+        let idx = buf.meta_index();
+        drop(buf);
+
+        // The released segment stays and the next allocation of that size reuses it
+        assert_eq!(pool.ref_count(idx).unwrap(), 0);
+        assert!(SharedMemory::open(&shm_name(idx)).is_ok());
+        let buf = pool.acquire_cpu(4096).unwrap();
+        assert_eq!(buf.meta_index(), idx);
+        assert_eq!(&buf.as_cpu_slice().unwrap()[..4], b"kept");
+        assert_eq!(buf.meta().unwrap().cache.load(Ordering::SeqCst), 0);
+        drop(buf);
+
+        // A different size replaces it
+        let buf = pool.acquire_cpu(8192).unwrap();
+        assert_eq!(buf.meta_index(), idx);
+        assert_eq!(buf.size().unwrap(), 8192);
+        assert_eq!(&buf.as_cpu_slice().unwrap()[..4], &[0; 4]);
+        drop(buf);
+
+        // The creator deletes cached segments with the pool
+        assert!(SharedMemory::open(&shm_name(idx)).is_ok());
+        drop(pool);
+        assert!(SharedMemory::open(&shm_name(idx)).is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_trim_idle_segments() {
+        let name = unique_name();
+        let config = PoolConfig::new()
+            .cache_segments(true)
+            .trim_idle_after(Duration::from_secs(3600));
+        let pool = BufferPool::create_with_config(&name, &config).unwrap();
+
+        let mut buf = pool.acquire_cpu(8192).unwrap();
+        buf.as_cpu_slice_mut().unwrap().fill(7);
+        let idx = buf.meta_index();
+        drop(buf);
+
+        // Not idle for long enough yet
+        let report = pool.trim().unwrap();
+        assert!(report.released.is_empty() && report.unlinked.is_empty());
+        assert_eq!(report.cached_bytes, 8192);
+
+        // Without a threshold every cached segment gives its pages back, once
+        let trimmer = BufferPool::open(&name).unwrap();
+        assert_eq!(
+            trimmer.trim().unwrap(),
+            TrimReport {
+                released: vec![idx],
+                unlinked: vec![],
+                cached_bytes: 8192,
+            }
+        );
+        assert!(trimmer.trim().unwrap().released.is_empty());
+
+        // The segment is still reused, now with its pages removed
+        let buf = pool.acquire_cpu(8192).unwrap();
+        assert_eq!(buf.meta_index(), idx);
+        assert!(buf.as_cpu_slice().unwrap().iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_trim_byte_budget() {
+        let name = unique_name();
+        let config = PoolConfig::new().cache_segments(true).max_cached_bytes(8192);
+        let pool = BufferPool::create_with_config(&name, &config).unwrap();
+        let shm_name = |index: u32| format!("{}_buf_{}", name, index);
+
+        let bufs: Vec<_> = (0..3).map(|_| pool.acquire_cpu(4096).unwrap()).collect();
+        let indices: Vec<u32> = bufs.iter().map(|b| b.meta_index()).collect();
+        for buf in bufs {
+            drop(buf);
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        // The third release went over budget and deleted the oldest segment
+        assert!(SharedMemory::open(&shm_name(indices[0])).is_err());
+        assert!(SharedMemory::open(&shm_name(indices[1])).is_ok());
+        assert!(SharedMemory::open(&shm_name(indices[2])).is_ok());
+
+        let report = pool.trim().unwrap();
+        assert_eq!(report.released, indices[1..]);
+        assert!(report.unlinked.is_empty());
+        assert_eq!(report.cached_bytes, 8192);
+
+        // A slot whose segment was deleted allocates a new one
+        let buf = pool.acquire_cpu(4096).unwrap();
+        assert_eq!(buf.meta_index(), indices[2]);
+        let bufs: Vec<_> = (0..2).map(|_| pool.acquire_cpu(4096).unwrap()).collect();
+        assert!(bufs.iter().any(|b| b.meta_index() == indices[0]));
+    }
+
+    #[test]
+    fn test_acquire_cpu_array() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let buf = pool.acquire_cpu_array(&[4, 8], DType::Float32).unwrap();
+        assert_eq!(buf.as_cpu_slice().unwrap().len(), 4 * 8 * 4);
+
+        let meta = buf.meta().unwrap();
+        assert_eq!(meta.dtype(), Some(DType::Float32));
+        assert_eq!(meta.shape(), vec![4, 8]);
+        assert_eq!(meta.strides(), vec![32, 4]);
+
+        // The tensor must fit the buffer; read-only guards cannot set it
+        assert!(matches!(buf.set_tensor(&[4, 9], DType::Float32), Err(Error::InvalidShape(_))));
+        assert!(matches!(buf.set_tensor(&[usize::MAX, 2], DType::UInt8), Err(Error::InvalidShape(_))));
+        assert_eq!(meta.shape(), vec![4, 8]);
+        buf.set_tensor(&[128], DType::UInt8).unwrap();
+        assert_eq!(meta.shape(), vec![128]);
+        let reader = pool.get(buf.meta_index()).unwrap();
+        assert!(matches!(reader.set_tensor(&[1], DType::UInt8), Err(Error::ReadOnly)));
+        drop(reader);
+
+        // A plain acquire on a recycled slot must not inherit stale metadata
+        let idx = buf.meta_index();
+        drop(buf);
+        let buf = pool.acquire_cpu(16).unwrap();
+        assert_eq!(buf.meta_index(), idx);
+        assert!(buf.meta().unwrap().shape().is_empty());
+    }
+
+    #[test]
+    fn test_create_view() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let mut parent = pool.acquire_cpu(16).unwrap();
+        parent.as_cpu_slice_mut().unwrap().copy_from_slice(b"0123456789abcdef");
+        let parent_idx = parent.meta_index();
+
+        let desc = TensorDesc::new([2, 2], DType::UInt8);
+        let view = pool.create_view(parent_idx, 4, 4, Some(&desc)).unwrap();
+        assert_eq!(view.as_cpu_slice().unwrap(), b"4567");
+        assert_eq!(view.meta().unwrap().shape(), vec![2, 2]);
+        assert_eq!(pool.ref_count(parent_idx).unwrap(), 2);
+
+        // get() on the view maps only its range
+        let view_idx = view.meta_index();
+        let mut again = pool.get_mut(view_idx).unwrap();
+        again.as_cpu_slice_mut().unwrap().copy_from_slice(b"WXYZ");
+        assert_eq!(parent.as_cpu_slice().unwrap(), b"0123WXYZ89abcdef");
+        drop(again);
+
+        // Nested view via BufferGuard::slice
+        let inner = view.slice(1, 2).unwrap();
+        assert_eq!(inner.as_cpu_slice().unwrap(), b"XY");
+        assert_eq!(pool.ref_count(view_idx).unwrap(), 2);
+
+        assert!(matches!(
+            pool.create_view(parent_idx, 10, 8, None),
+            Err(Error::OutOfBounds { .. })
+        ));
+        assert!(view.slice(3, 2).is_err());
+    }
+
+    #[test]
+    fn test_view_releases_parent() {
+        let name = unique_name();
+        let pool = BufferPool::create_with_capacity(&name, 3).unwrap();
+
+        let parent = pool.acquire_cpu(64).unwrap();
+        let parent_idx = parent.meta_index();
+        let view = parent.slice(0, 32).unwrap();
+        assert_eq!(view.mode(), AccessMode::ReadWrite);
+
+        // Parent stays allocated while the view is alive
+        drop(parent);
+        assert_eq!(pool.ref_count(parent_idx).unwrap(), 1);
+        assert_eq!(view.as_cpu_slice().unwrap().len(), 32);
+
+        // Freeing the view recycles both slots
+        drop(view);
+        assert_eq!(pool.ref_count(parent_idx).unwrap(), 0);
+        let a = pool.acquire_cpu(8).unwrap();
+        let b = pool.acquire_cpu(8).unwrap();
+        let c = pool.acquire_cpu(8).unwrap();
+        assert_eq!(
+            [a.meta_index(), b.meta_index(), c.meta_index()],
+            [parent_idx, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_view_of_released_parent() {
+        let name = unique_name();
+        let pool = BufferPool::create_with_capacity(&name, 2).unwrap();
+
+        let parent = pool.acquire_cpu(64).unwrap();
+        let parent_idx = parent.meta_index();
+        drop(parent);
+        assert!(matches!(
+            pool.create_view(parent_idx, 0, 8, None),
+            Err(Error::BufferNotFound(_))
+        ));
+
+        // A freed buffer is never revived, and the failed view leaks no slot
+        let meta = pool.meta_region().get(parent_idx).unwrap();
+        assert!(!add_ref_if_live(meta));
+        assert_eq!(pool.ref_count(parent_idx).unwrap(), 0);
+        let _a = pool.acquire_cpu(8).unwrap();
+        let _b = pool.acquire_cpu(8).unwrap();
+    }
+
+    #[test]
+    fn test_try_clone() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let mut buf = pool.acquire_cpu(8).unwrap();
+        let idx = buf.meta_index();
+        buf.as_cpu_slice_mut().unwrap().fill(3);
+        buf.forget();
+
+        // The clone shares the original's write lock
+        let writer = pool.get_mut(idx).unwrap();
+        let clone = writer.try_clone().unwrap();
+        assert_eq!(pool.ref_count(idx).unwrap(), 3);
+        assert_eq!(clone.mode(), AccessMode::ReadWrite);
+
+        // The lock outlives the original while the clone can still write
+        drop(writer);
+        assert_eq!(clone.as_cpu_slice().unwrap(), &[3; 8]);
+        assert!(matches!(pool.try_get_mut(idx), Err(Error::Locked(_))));
+        assert!(matches!(pool.try_get(idx), Err(Error::Locked(_))));
+
+        drop(clone);
+        assert_eq!(pool.ref_count(idx).unwrap(), 1);
+        assert!(pool.try_get_mut(idx).is_ok());
+    }
+
+    #[test]
+    fn test_view_locks_root() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let buf = pool.acquire_cpu(64).unwrap();
+        let idx = buf.meta_index();
+        let view = pool.create_view(idx, 16, 16, None).unwrap();
+        let nested = pool.create_view(view.meta_index(), 0, 8, None).unwrap();
+        let (view_idx, nested_idx) = (view.meta_index(), nested.meta_index());
+        // Keep all three alive without holding guards
+        buf.forget();
+        view.forget();
+        nested.forget();
+
+        // Writers of a view and of its parent exclude each other
+        let writer = pool.try_get_mut(nested_idx).unwrap();
+        assert!(matches!(pool.try_get_mut(idx), Err(Error::Locked(_))));
+        assert!(matches!(pool.try_get(view_idx), Err(Error::Locked(_))));
+        drop(writer);
+
+        let parent = pool.try_get_mut(idx).unwrap();
+        assert!(matches!(pool.try_get_mut(view_idx), Err(Error::Locked(_))));
+
+        // A slice shares the parent's lock
+        let slice = parent.slice(0, 8).unwrap();
+        drop(parent);
+        assert!(matches!(pool.try_get_mut(idx), Err(Error::Locked(_))));
+        drop(slice);
+
+        let reader = pool.try_get(view_idx).unwrap();
+        assert!(pool.try_get(idx).is_ok());
+        assert!(matches!(pool.try_get_mut(idx), Err(Error::Locked(_))));
+        drop(reader);
+    }
+
+    #[test]
+    fn test_publish_receive() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        assert!(pool.try_receive().unwrap().is_none());
+
+        let notifier = pool.notifier().unwrap();
+        let first = pool.acquire_cpu(4).unwrap();
+        let mut second = pool.acquire_cpu(4).unwrap();
+        let (first_idx, second_idx) = (first.meta_index(), second.meta_index());
+        second.as_cpu_slice_mut().unwrap().copy_from_slice(b"two!");
+
+        // Published out of index order
+        pool.publish(second).unwrap();
+        pool.publish(first).unwrap();
+        assert!(notifier.drain());
+
+        // FIFO order, each buffer received once, reference transferred
+        let rx = pool.try_receive().unwrap().unwrap();
+        assert_eq!(rx.meta_index(), second_idx);
+        assert_eq!(rx.as_cpu_slice().unwrap(), b"two!");
+        assert!(rx.meta().unwrap().timestamp.load(Ordering::SeqCst) > 0);
+        let rx2 = pool.receive_timeout(Duration::from_millis(10)).unwrap();
+        assert_eq!(rx2.meta_index(), first_idx);
+        assert_eq!(pool.ref_count(first_idx).unwrap(), 1);
+        assert!(matches!(
+            pool.receive_timeout(Duration::from_millis(10)),
+            Err(Error::Timeout)
+        ));
+
+        // Freeing wakes subscribers as well
+        drop(rx2);
+        assert!(notifier.drain());
+        assert_eq!(pool.ref_count(first_idx).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_receive_wakes_on_publish() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        let buf = pool.acquire_cpu(8).unwrap();
+
+        let publisher = pool.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            publisher.publish(buf).unwrap();
+        });
+        let rx = pool.receive_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(rx.as_cpu_slice().unwrap().len(), 8);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_receive_wakes_across_handles() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        // A separately opened handle shares no in-process wakers, like another process
+        let other = BufferPool::open(&name).unwrap();
+        let buf = pool.acquire_cpu(8).unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let start = Instant::now();
+            let rx = other.receive_timeout(Duration::from_secs(10)).unwrap();
+            (rx.meta_index(), start.elapsed())
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        let idx = buf.meta_index();
+        pool.publish(buf).unwrap();
+
+        let (rx_idx, elapsed) = handle.join().unwrap();
+        assert_eq!(rx_idx, idx);
+        assert!(elapsed < Duration::from_secs(5));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_notifier_wakes_across_handles() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        let other = BufferPool::open(&name).unwrap();
+        let notifier = other.notifier().unwrap();
+        assert!(!notifier.drain());
+
+        // Events through a handle sharing no wakers still reach the notifier
+        let buf = pool.acquire_cpu(8).unwrap();
+        pool.publish(buf).unwrap();
+        assert!(notifier.wait(Duration::from_secs(5)));
+        drop(pool.try_receive().unwrap().unwrap());
+        assert!(notifier.wait(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_get_locking() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let buf = pool.acquire_cpu(64).unwrap();
+        let idx = buf.meta_index();
+
+        // Readers share, writers exclude
+        let r1 = pool.get(idx).unwrap();
+        let r2 = pool.try_get(idx).unwrap();
+        assert!(matches!(pool.try_get_mut(idx), Err(Error::Locked(_))));
+        drop(r1);
+        drop(r2);
+
+        let w = pool.get_mut(idx).unwrap();
+        assert!(matches!(pool.try_get(idx), Err(Error::Locked(_))));
+        assert!(matches!(
+            pool.get_timeout(idx, Duration::from_millis(10)),
+            Err(Error::Timeout)
+        ));
+        assert_eq!(pool.ref_count(idx).unwrap(), 2);
+
+        // forget() transfers the reference but still drops the lock
+        w.forget();
+        assert!(pool.try_get_mut(idx).is_ok());
+        assert_eq!(pool.ref_count(idx).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_get_released() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        // Released between the slot lookup and the reference: not revived
+        let buf = pool.acquire_cpu(64).unwrap();
+        let idx = buf.meta_index();
+        buf.forget();
+        pool.set_ref_count(idx, 0).unwrap();
+        assert!(matches!(pool.get(idx), Err(Error::BufferNotFound(i)) if i == idx));
+        assert!(matches!(pool.try_get_mut(idx), Err(Error::BufferNotFound(i)) if i == idx));
+        assert_eq!(pool.ref_count(idx).unwrap(), 0);
+
+        // Neither attempt left its lock behind
+        pool.set_ref_count(idx, 1).unwrap();
+        assert!(pool.try_get_mut(idx).is_ok());
+        assert!(pool.try_get(idx).is_ok());
+    }
+
     #[test]
     fn test_acquire_blocking_timeout() {
         let name = unique_name();
@@ -544,6 +2079,467 @@ mod tests {
         let result = pool2.acquire_cpu_blocking(1024, Duration::from_millis(100));
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_backing_segment() {
+        use std::io::{Read, Write};
+
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        let mut buf = pool.acquire_cpu(4).unwrap();
+        buf.as_cpu_slice_mut().unwrap().copy_from_slice(b"data");
+        assert_eq!(
+            buf.backing_name().unwrap(),
+            format!("{}_buf_{}", name, buf.meta_index())
+        );
+
+        let mut file = std::fs::File::from(buf.backing_fd().unwrap());
+        let mut contents = [0u8; 4];
+        file.read_exact(&mut contents).unwrap();
+        assert_eq!(&contents, b"data");
+
+        // Read-only guards get a read-only descriptor
+        let reader = pool.get(buf.meta_index()).unwrap();
+        let mut file = std::fs::File::from(reader.backing_fd().unwrap());
+        assert!(file.write_all(b"x").is_err());
+
+        // Views report the parent's segment
+        let view = pool.create_view(buf.meta_index(), 1, 2, None).unwrap();
+        assert_eq!(view.backing_name().unwrap(), buf.backing_name().unwrap());
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<BufferPool>();
+
+        let pool = BufferPool::create_with_capacity(&unique_name(), 4).unwrap();
+        std::thread::scope(|s| {
+            for i in 0..4u8 {
+                let pool = &pool;
+                s.spawn(move || {
+                    for _ in 0..50 {
+                        let mut buf = pool
+                            .acquire_cpu_blocking(8, Duration::from_secs(1))
+                            .unwrap();
+                        buf.as_cpu_slice_mut().unwrap().fill(i);
+                        assert!(buf.as_cpu_slice().unwrap().iter().all(|&b| b == i));
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_in_memory_pool() {
+        // Same name, independent pools, no /dev/shm segments
+        let name = unique_name();
+        let pool = BufferPool::in_memory_with_capacity(&name, 2).unwrap();
+        let other = BufferPool::in_memory_with_capacity(&name, 2).unwrap();
+        assert!(BufferPool::open(&name).is_err());
+
+        let mut buf = pool.acquire_cpu(8).unwrap();
+        buf.as_cpu_slice_mut().unwrap().copy_from_slice(b"in-place");
+        let idx = buf.meta_index();
+        assert_eq!(
+            buf.meta().unwrap().storage_type.load(Ordering::SeqCst),
+            StorageType::Heap as u8
+        );
+        assert!(buf.backing_name().is_err());
+
+        // Other handles see the same data, views included
+        buf.forget();
+        let reader = pool.get(idx).unwrap();
+        assert_eq!(reader.as_cpu_slice().unwrap(), b"in-place");
+        assert_eq!(reader.slice(3, 5).unwrap().as_cpu_slice().unwrap(), b"place");
+        assert!(pool.try_get_mut(idx).is_err());
+        drop(reader);
+
+        let mut empty = other.acquire_cpu(8).unwrap();
+        assert_eq!(empty.as_cpu_slice_mut().unwrap(), &[0; 8]);
+
+        // Publish / receive and backpressure work as with shared memory
+        let mut frame = pool.acquire_cpu_array(&[2, 2], DType::UInt16).unwrap();
+        frame.as_cpu_slice_mut().unwrap().fill(7);
+        pool.publish(frame).unwrap();
+        assert!(pool.try_acquire_cpu(1).unwrap().is_none());
+        let frame = pool.try_receive().unwrap().unwrap();
+        assert_eq!(frame.meta().unwrap().shape(), vec![2, 2]);
+        assert!(frame.as_cpu_slice().unwrap().iter().all(|&b| b == 7));
+
+        // Freed slots are reused with fresh memory
+        assert_eq!(pool.release(idx).unwrap(), 0);
+        pool.release_buffer(idx).unwrap();
+        let mut reused = pool.acquire_cpu(8).unwrap();
+        assert_eq!(reused.meta_index(), idx);
+        assert_eq!(reused.as_cpu_slice_mut().unwrap(), &[0; 8]);
+    }
+
+    fn temp_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(unique_name().trim_start_matches('/'));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_pool_reopen() {
+        let dir = temp_dir();
+        let pool = BufferPool::create_file_with_capacity(&dir, "rec", 8).unwrap();
+        assert!(BufferPool::create_file(&dir, "rec").is_err());
+
+        for i in 0..3u8 {
+            let mut frame = pool.acquire_cpu_array(&[4], DType::UInt8).unwrap();
+            frame.as_cpu_slice_mut().unwrap().fill(i);
+            frame.sync().unwrap();
+            pool.publish(frame).unwrap();
+        }
+        let mut kept = pool.acquire_cpu(4).unwrap();
+        kept.as_cpu_slice_mut().unwrap().copy_from_slice(b"kept");
+        let kept_idx = kept.meta_index();
+        kept.forget();
+        pool.sync().unwrap();
+        drop(pool);
+
+        // Files survive the pool, the recorder reattaches to its frames
+        assert!(dir.join("rec_meta").exists());
+        let pool = BufferPool::open_file(&dir, "rec").unwrap();
+        assert!(pool.fsck().unwrap().is_clean());
+        for i in 0..3u8 {
+            let frame = pool.try_receive().unwrap().unwrap();
+            assert_eq!(frame.meta().unwrap().shape(), vec![4]);
+            assert!(frame.as_cpu_slice().unwrap().iter().all(|&b| b == i));
+        }
+        assert!(pool.try_receive().unwrap().is_none());
+        assert_eq!(pool.get(kept_idx).unwrap().as_cpu_slice().unwrap(), b"kept");
+
+        // Freed buffers remove their files
+        assert_eq!(pool.release(kept_idx).unwrap(), 0);
+        pool.release_buffer(kept_idx).unwrap();
+        assert!(!dir.join(format!("rec_buf_{}", kept_idx)).exists());
+
+        // Attaching while another handle is live skips the repair
+        let other = BufferPool::open_file(&dir, "rec").unwrap();
+        assert_eq!(other.capacity(), 8);
+
+        assert!(BufferPool::open_file(&dir, "missing").is_err());
+        std::fs::write(dir.join("bad_meta"), [0u8; 64]).unwrap();
+        assert!(BufferPool::open_file(&dir, "bad").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_pool_fsck() {
+        let dir = temp_dir();
+        let pool = BufferPool::create_file_with_capacity(&dir, "rec", 8).unwrap();
+        let indices = pool.preallocate_cpu(16, 4).unwrap();
+        let view = pool.create_view(indices[1], 0, 8, None).unwrap();
+        let view_idx = view.meta_index();
+        view.forget();
+        let seq = pool.get_mut(indices[3]).unwrap();
+        seq.enable_seqlock().unwrap();
+        drop(seq);
+
+        // Crash damage: lost data file, leaked slot, dead lock holder, torn write
+        std::fs::remove_file(dir.join(format!("rec_buf_{}", indices[1]))).unwrap();
+        pool.set_ref_count(indices[2], 0).unwrap();
+        let meta = pool.meta_region().get(indices[0]).unwrap();
+        meta.lock_state.store(0x7FFF_FFF0, Ordering::SeqCst);
+        let meta = pool.meta_region().get(indices[3]).unwrap();
+        meta.seq.store(5, Ordering::SeqCst);
+
+        let report = pool.fsck().unwrap();
+        assert_eq!(report.reclaimed, vec![indices[1], view_idx]);
+        assert_eq!(report.locks_recovered, vec![indices[0]]);
+        assert_eq!(report.torn_writes, vec![indices[3]]);
+        assert!(report.free_list_rebuilt);
+        assert!(pool.fsck().unwrap().is_clean());
+
+        assert!(pool.try_get_mut(indices[0]).is_ok());
+        assert!(pool.get(indices[3]).is_ok());
+        assert!(pool.get(view_idx).is_err());
+
+        // All three freed slots are handed out again before new ones
+        let mut reused: Vec<u32> = (0..3)
+            .map(|_| {
+                let buf = pool.acquire_cpu(4).unwrap();
+                let idx = buf.meta_index();
+                buf.forget();
+                idx
+            })
+            .collect();
+        reused.sort();
+        let mut expected = vec![indices[1], indices[2], view_idx];
+        expected.sort();
+        assert_eq!(reused, expected);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pool_config() {
+        let name = unique_name();
+        let dir = temp_dir();
+        let config = PoolConfig::new().capacity(4).namespace("ns_").backing_dir(&dir);
+        let pool = BufferPool::create_with_config(&name, &config).unwrap();
+        let full = format!("/ns_{}", name.trim_start_matches('/'));
+        assert_eq!(pool.name(), full);
+        assert_eq!(pool.backing_dir(), Some(dir.as_path()));
+        assert_eq!(pool.capacity(), 4);
+
+        // Segments are files in the backing directory, not shm names
+        let mut buf = pool.acquire_cpu(4).unwrap();
+        buf.as_cpu_slice_mut().unwrap().copy_from_slice(b"file");
+        let idx = buf.meta_index();
+        buf.forget();
+        let base = full.trim_start_matches('/');
+        assert!(dir.join(format!("{}_meta", base)).exists());
+        assert!(dir.join(format!("{}_buf_{}", base, idx)).exists());
+        assert!(BufferPool::open(&full).is_err());
+
+        let reader = BufferPool::open_with_config(&name, &config).unwrap();
+        assert_eq!(reader.capacity(), 4);
+        assert_eq!(reader.get(idx).unwrap().as_cpu_slice().unwrap(), b"file");
+        drop(reader);
+
+        // Like shared memory, the creator removes everything
+        drop(pool);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Namespaced shm pools open by full name
+        let pool = BufferPool::create_with_config(&name, &PoolConfig::new().namespace("ns_")).unwrap();
+        assert!(BufferPool::open(pool.name()).is_ok());
+        assert!(BufferPool::open(&name).is_err());
+    }
+
+    #[test]
+    fn test_acquire_out_of_space() {
+        let name = unique_name();
+        let dir = temp_dir();
+        let shm_pool = BufferPool::create_with_capacity(&name, 2).unwrap();
+        let config = PoolConfig::new().capacity(2).backing_dir(&dir);
+        let file_pool = BufferPool::create_with_config(&name, &config).unwrap();
+
+        // POSIX shared memory only has a filesystem to check on Linux
+        let pools = match cfg!(target_os = "linux") {
+            true => vec![&shm_pool, &file_pool],
+            false => vec![&file_pool],
+        };
+        crate::shm::MOCK_AVAILABLE.with(|m| m.set(Some(1 << 20)));
+        for pool in pools {
+            let err = pool.acquire_cpu(2 << 20).err().unwrap();
+            assert!(
+                matches!(err, Error::OutOfShmSpace { requested, available }
+                    if requested == 2 << 20 && available == 1 << 20),
+                "{}",
+                err
+            );
+            // The slot went back to the pool
+            let a = pool.acquire_cpu(16).unwrap();
+            let b = pool.acquire_cpu(16).unwrap();
+            assert_ne!(a.meta_index(), b.meta_index());
+        }
+        crate::shm::MOCK_AVAILABLE.with(|m| m.set(None));
+
+        // No half-created segment files are left behind
+        let files = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(files, 1);
+        drop(file_pool);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_huge_pages_and_prefault() {
+        let name = unique_name();
+        let config = PoolConfig::new()
+            .capacity(4)
+            .huge_pages(HugePages::HugeTlb)
+            .prefault(true);
+        let pool = BufferPool::create_with_config(&name, &config).unwrap();
+        // /dev/shm is never hugetlbfs: falls back to THP where the kernel allows it
+        assert_ne!(pool.huge_pages(), HugePages::HugeTlb);
+        assert_eq!(
+            pool.huge_pages(),
+            memory::resolve(HugePages::Transparent, None)
+        );
+
+        let indices = pool.preallocate_cpu(3 << 20, 2).unwrap();
+        let mut buf = pool.get_mut(indices[0]).unwrap();
+        assert_eq!(buf.size().unwrap(), 3 << 20);
+        assert!(buf.as_cpu_slice().unwrap().iter().all(|&b| b == 0));
+        buf.as_cpu_slice_mut().unwrap()[..4].copy_from_slice(b"huge");
+        drop(buf);
+        assert_eq!(&pool.get(indices[0]).unwrap().as_cpu_slice().unwrap()[..4], b"huge");
+
+        let in_memory = BufferPool::in_memory(&name).unwrap();
+        assert_eq!(in_memory.huge_pages(), HugePages::Off);
+    }
+
+    #[test]
+    fn test_mlock_and_numa_node() {
+        let name = unique_name();
+        let config = PoolConfig::new().capacity(4).mlock(true).numa_node(0);
+        let pool = BufferPool::create_with_config(&name, &config).unwrap();
+        assert!(pool.is_mlocked());
+        assert_eq!(pool.numa_node(), memory::resolve_node(Some(0)));
+
+        let buf = pool.acquire_cpu(8192).unwrap();
+        assert_eq!(buf.meta().unwrap().numa_node(), pool.numa_node());
+        let view = pool.create_view(buf.meta_index(), 0, 4096, None).unwrap();
+        assert_eq!(view.meta().unwrap().numa_node(), pool.numa_node());
+
+        // Readers lock their own mapping
+        let reader = BufferPool::open_with_config(&name, &config).unwrap();
+        assert!(reader.get(buf.meta_index()).is_ok());
+
+        // Unbound pools record no node
+        let plain = BufferPool::create_with_capacity(&unique_name(), 2).unwrap();
+        let buf = plain.acquire_cpu(64).unwrap();
+        assert_eq!(plain.numa_node(), None);
+        assert_eq!(buf.meta().unwrap().numa_node(), None);
+    }
+
+    #[test]
+    fn test_invalid_pool_name() {
+        for bad in ["", "/", "/a/b"] {
+            assert!(matches!(BufferPool::create(bad), Err(Error::InvalidName { .. })));
+            assert!(matches!(BufferPool::open(bad), Err(Error::InvalidName { .. })));
+        }
+        let config = PoolConfig::new().namespace("a/");
+        assert!(matches!(
+            BufferPool::create_with_config("/pool", &config),
+            Err(Error::InvalidName { .. })
+        ));
+    }
+
+    /// In-process test double: data lives on the heap, located through the storage handle
+    struct HandleBackend {
+        storage_type: u8,
+        blocks: std::sync::Mutex<std::collections::HashMap<u64, Arc<Block>>>,
+    }
+
+    struct Block(Box<[std::cell::UnsafeCell<u8>]>);
+
+    // Safety: test data is only accessed through guards, like shared memory
+    unsafe impl Send for Block {}
+    unsafe impl Sync for Block {}
+
+    struct BlockSegment {
+        key: u64,
+        block: Arc<Block>,
+    }
+
+    impl StorageSegment for BlockSegment {
+        fn size(&self) -> usize {
+            self.block.0.len()
+        }
+
+        fn cpu_ptr(&self) -> Option<*mut u8> {
+            Some(self.block.0.as_ptr() as *mut u8)
+        }
+
+        fn export_handle(&self, meta: &crate::BufferMeta) -> Result<()> {
+            let mut handle = [0u8; CUDA_IPC_HANDLE_SIZE];
+            handle[..8].copy_from_slice(&self.key.to_le_bytes());
+            meta.set_storage_handle(&handle);
+            Ok(())
+        }
+    }
+
+    fn handle_key(meta: &crate::BufferMeta) -> u64 {
+        u64::from_le_bytes(meta.storage_handle()[..8].try_into().unwrap())
+    }
+
+    impl StorageBackend for HandleBackend {
+        fn storage_type(&self) -> u8 {
+            self.storage_type
+        }
+
+        fn name(&self) -> &str {
+            "Test"
+        }
+
+        fn allocate(
+            &self,
+            id: SegmentId<'_>,
+            size: usize,
+            _device_id: u8,
+        ) -> Result<Box<dyn StorageSegment>> {
+            let key = 0x1000 + id.index as u64;
+            let block = Arc::new(Block((0..size).map(|_| Default::default()).collect()));
+            self.blocks.lock().unwrap().insert(key, Arc::clone(&block));
+            Ok(Box::new(BlockSegment { key, block }))
+        }
+
+        fn import(
+            &self,
+            _id: SegmentId<'_>,
+            meta: &crate::BufferMeta,
+        ) -> Result<Box<dyn StorageSegment>> {
+            let key = handle_key(meta);
+            let block = self.blocks.lock().unwrap().get(&key).cloned();
+            let block = block.ok_or_else(|| Error::SharedMemory("no such block".to_string()))?;
+            Ok(Box::new(BlockSegment { key, block }))
+        }
+
+        fn free(&self, _id: SegmentId<'_>, meta: &crate::BufferMeta) -> Result<()> {
+            self.blocks.lock().unwrap().remove(&handle_key(meta));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_custom_backend() {
+        use crate::storage::{register_backend, FIRST_CUSTOM_STORAGE_TYPE};
+
+        let backend = Arc::new(HandleBackend {
+            storage_type: FIRST_CUSTOM_STORAGE_TYPE + 1,
+            blocks: Default::default(),
+        });
+        register_backend(backend.clone()).unwrap();
+        assert!(matches!(
+            register_backend(backend.clone()),
+            Err(Error::StorageTypeTaken(_))
+        ));
+        let reserved = Arc::new(HandleBackend {
+            storage_type: StorageType::View as u8,
+            blocks: Default::default(),
+        });
+        assert!(matches!(
+            register_backend(reserved),
+            Err(Error::StorageTypeTaken(2))
+        ));
+
+        let pool = BufferPool::create(&unique_name()).unwrap();
+        assert!(matches!(
+            pool.acquire_storage(FIRST_CUSTOM_STORAGE_TYPE + 2, 8),
+            Err(Error::UnknownStorage(_))
+        ));
+
+        let mut buf = pool.acquire_storage(FIRST_CUSTOM_STORAGE_TYPE + 1, 8).unwrap();
+        buf.as_cpu_slice_mut().unwrap().copy_from_slice(b"heapdata");
+        let idx = buf.meta_index();
+        assert_eq!(
+            buf.meta().unwrap().storage_type.load(Ordering::SeqCst),
+            FIRST_CUSTOM_STORAGE_TYPE + 1
+        );
+        assert!(matches!(
+            buf.backing_name(),
+            Err(Error::TypeMismatch { actual, .. }) if actual == "Test"
+        ));
+
+        // Opened again through the handle exported into the metadata
+        let reader = pool.get(idx).unwrap();
+        assert_eq!(reader.as_cpu_slice().unwrap(), b"heapdata");
+        let view = reader.slice(4, 4).unwrap();
+        assert_eq!(view.as_cpu_slice().unwrap(), b"data");
+        drop(reader);
+
+        // Freed once the buffer and its view are gone
+        drop(buf);
+        assert_eq!(backend.blocks.lock().unwrap().len(), 1);
+        drop(view);
+        assert!(backend.blocks.lock().unwrap().is_empty());
+    }
 }
 
 #[cfg(all(test, feature = "cuda"))]
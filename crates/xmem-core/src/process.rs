@@ -0,0 +1,318 @@
+//! 进程登记表
+//!
+//! 跨进程读写锁（[`crate::lock`]）和缓存段占有（[`crate::cache`]）在持有者崩溃后需要回收，
+//! 因此要能判断持有者是否存活。pid 不适合作为身份：共享同一 tmpfs 卷的容器位于不同的
+//! PID namespace，另一个容器中存活的进程在本进程看来并不存在（`kill` 返回 `ESRCH`），
+//! 其锁会被误回收；pid 也可能被无关进程复用。
+//!
+//! 元数据区头部有一张 [`MAX_PROCESSES`] 项的登记表。进程第一次加锁或占有缓存段时
+//! 登记一个槽位，锁和占有记录中保存槽位号而不是 pid：
+//!
+//! - Linux：进程在元数据文件上对槽位号对应的字节持有 OFD 记录锁（`F_OFD_SETLK`），
+//!   进程退出时由内核释放。存活检查使用 `F_OFD_GETLK`，与 PID namespace 无关，
+//!   只要各进程打开的是同一个文件（同一个 `/dev/shm` 或同一个 tmpfs 卷）。
+//!   回收死亡槽位的记录前先锁住该槽位，回收期间槽位不会被新进程登记
+//! - 其他平台：登记表记录 pid，存活检查使用 `kill(pid, 0)`，
+//!   跨 PID namespace 共享池时不能可靠地回收
+//!
+//! 登记表中的 pid 在 Linux 上仅用于诊断。同一进程打开同一个池的多个句柄共用一个槽位；
+//! fork 出的子进程在第一次使用时重新登记。登记新槽位时会先清除该槽位上一个
+//! （已死亡的）持有者留下的锁和占有记录。所有槽位都被存活进程占用时返回
+//! [`Error::TooManyProcesses`]。
+
+use crate::lock::LOCK_READER_WORDS;
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Number of process slots in the metadata region header
+pub const MAX_PROCESSES: usize = LOCK_READER_WORDS * 64;
+
+/// Process slot table in the metadata region header
+pub(crate) type ProcessTable = [AtomicU32; MAX_PROCESSES];
+
+pub(crate) fn current_pid() -> u32 {
+    std::process::id()
+}
+
+/// Check whether a process is still alive (same PID namespace only)
+#[cfg(not(target_os = "linux"))]
+fn process_alive(pid: u32) -> bool {
+    let ret = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    ret == 0 || std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+/// 本进程在一个池的登记表中占用的槽位
+pub(crate) struct Registration {
+    slot: u32,
+    pid: u32,
+    /// Metadata file holding the slot's record lock, `None` for in-memory pools
+    fd: Option<OwnedFd>,
+    /// Identity of the metadata file, for sharing between handles
+    file_id: Option<(u64, u64)>,
+    /// Read locks held by this process, by metadata index, see [`crate::lock`]
+    pub(crate) readers: Mutex<HashMap<u32, u32>>,
+}
+
+/// Registrations of this process, shared by handles of the same pool
+static REGISTRATIONS: Mutex<Vec<Weak<Registration>>> = Mutex::new(Vec::new());
+
+impl Registration {
+    /// Slot number recorded in locks and claims
+    pub(crate) fn slot(&self) -> u32 {
+        self.slot
+    }
+
+    /// Whether this registration belongs to the calling process (not a fork parent's)
+    pub(crate) fn is_current(&self) -> bool {
+        self.pid == current_pid()
+    }
+}
+
+/// 登记本进程，`fd` 为新打开的元数据文件描述符（进程内池为 `None`）
+///
+/// 本进程已登记过同一文件时复用原槽位；否则占用一个空闲或已死亡的槽位，
+/// 并在返回前以 `sweep(slot)` 清除上一个持有者的记录。
+pub(crate) fn register(
+    table: &ProcessTable,
+    fd: Option<OwnedFd>,
+    sweep: impl Fn(u32),
+) -> Result<Arc<Registration>> {
+    let file_id = fd.as_ref().map(file_id).transpose()?;
+    let mut registrations = REGISTRATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    registrations.retain(|reg| reg.strong_count() > 0);
+    if file_id.is_some() {
+        let existing = registrations
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|reg| reg.file_id == file_id && reg.is_current());
+        if let Some(reg) = existing {
+            return Ok(reg);
+        }
+    }
+    let reg = Arc::new(register_new(table, fd, file_id, sweep)?);
+    registrations.push(Arc::downgrade(&reg));
+    Ok(reg)
+}
+
+/// Take a slot for a new registration without looking for an existing one
+fn register_new(
+    table: &ProcessTable,
+    fd: Option<OwnedFd>,
+    file_id: Option<(u64, u64)>,
+    sweep: impl Fn(u32),
+) -> Result<Registration> {
+    let pid = current_pid();
+    for (slot, entry) in (0u32..).zip(table) {
+        let taken = match &fd {
+            #[cfg(target_os = "linux")]
+            Some(fd) => record_lock(fd, slot, libc::F_WRLCK)?,
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                let old = entry.load(Ordering::SeqCst);
+                (old == 0 || !process_alive(old))
+                    && entry.compare_exchange(old, pid, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+            }
+            // In-memory pools are never seen by other processes
+            None => entry.compare_exchange(0, pid, Ordering::SeqCst, Ordering::SeqCst).is_ok(),
+        };
+        if taken {
+            entry.store(pid, Ordering::SeqCst);
+            sweep(slot);
+            return Ok(Registration {
+                slot,
+                pid,
+                fd,
+                file_id,
+                readers: Mutex::new(HashMap::new()),
+            });
+        }
+    }
+    Err(Error::TooManyProcesses(MAX_PROCESSES))
+}
+
+fn file_id(fd: &OwnedFd) -> Result<(u64, u64)> {
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(fd.as_raw_fd(), &mut stat) } != 0 {
+        return Err(Error::SharedMemory(
+            std::io::Error::last_os_error().to_string(),
+        ));
+    }
+    Ok((stat.st_dev as u64, stat.st_ino as u64))
+}
+
+/// Set (`F_WRLCK`) or clear (`F_UNLCK`) the record lock of `slot`, `false` if held elsewhere
+#[cfg(target_os = "linux")]
+fn record_lock(fd: &OwnedFd, slot: u32, l_type: libc::c_int) -> Result<bool> {
+    let mut lock = slot_lock(slot, l_type);
+    if unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_OFD_SETLK, &mut lock) } == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EAGAIN) | Some(libc::EACCES) => Ok(false),
+        _ => Err(Error::SharedMemory(err.to_string())),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn slot_lock(slot: u32, l_type: libc::c_int) -> libc::flock {
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = l_type as libc::c_short;
+    lock.l_whence = libc::SEEK_SET as libc::c_short;
+    lock.l_start = slot as libc::off_t;
+    lock.l_len = 1;
+    lock
+}
+
+/// 本进程的登记及其所在池的登记表，用于检查其他槽位的持有者
+pub(crate) struct Holder<'a> {
+    reg: Arc<Registration>,
+    /// Pids of registered processes, consulted off Linux
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    table: &'a ProcessTable,
+}
+
+impl<'a> Holder<'a> {
+    pub(crate) fn new(reg: Arc<Registration>, table: &'a ProcessTable) -> Self {
+        Self { reg, table }
+    }
+
+    pub(crate) fn registration(&self) -> &Arc<Registration> {
+        &self.reg
+    }
+
+    pub(crate) fn slot(&self) -> u32 {
+        self.reg.slot
+    }
+
+    /// Whether the process registered in `slot` is alive
+    pub(crate) fn alive(&self, slot: u32) -> bool {
+        if slot == self.reg.slot {
+            return true;
+        }
+        if slot as usize >= MAX_PROCESSES {
+            return false;
+        }
+        match &self.reg.fd {
+            #[cfg(target_os = "linux")]
+            Some(fd) => {
+                let mut lock = slot_lock(slot, libc::F_WRLCK);
+                // Unknown errors count as alive: never steal a lock on doubt
+                let rc = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_OFD_GETLK, &mut lock) };
+                rc != 0 || lock.l_type != libc::F_UNLCK as libc::c_short
+            }
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                let pid = self.table[slot as usize].load(Ordering::SeqCst);
+                pid != 0 && process_alive(pid)
+            }
+            // Every holder of an in-memory pool is this process
+            None => true,
+        }
+    }
+
+    /// 若 `slot` 的持有者已死亡，执行 `recover` 清除其记录并返回 true
+    ///
+    /// Linux 上回收期间锁住该槽位，新登记的进程不会与回收交错。
+    pub(crate) fn reclaim(&self, slot: u32, recover: impl FnOnce()) -> bool {
+        if slot as usize >= MAX_PROCESSES {
+            recover();
+            return true;
+        }
+        if slot == self.reg.slot {
+            return false;
+        }
+        match &self.reg.fd {
+            #[cfg(target_os = "linux")]
+            Some(fd) => {
+                if !record_lock(fd, slot, libc::F_WRLCK).unwrap_or(false) {
+                    return false;
+                }
+                recover();
+                let _ = record_lock(fd, slot, libc::F_UNLCK);
+                true
+            }
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                if self.alive(slot) {
+                    return false;
+                }
+                recover();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::meta_region::MetaRegion;
+
+    fn unique_name() -> String {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        format!("/xmem_proc_{}", ts)
+    }
+
+    /// A registration standing in for another process: its own descriptor, its own slot
+    pub(crate) fn other_process(region: &MetaRegion) -> Arc<Registration> {
+        let fd = region.open_fd().unwrap().unwrap();
+        let file_id = Some(file_id(&fd).unwrap());
+        Arc::new(register_new(region.processes(), Some(fd), file_id, |slot| region.sweep(slot)).unwrap())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_registration() {
+        let name = unique_name();
+        let region = MetaRegion::create(&name, 4).unwrap();
+        let holder = region.holder().unwrap();
+
+        // Handles of the same pool in one process share the slot
+        let again = MetaRegion::open(&name).unwrap();
+        assert_eq!(again.holder().unwrap().slot(), holder.slot());
+        assert_eq!(
+            region.processes()[holder.slot() as usize].load(Ordering::SeqCst),
+            current_pid()
+        );
+
+        // Another process takes another slot and is alive while registered
+        let other = other_process(&region);
+        let other_slot = other.slot();
+        assert_ne!(other_slot, holder.slot());
+        assert!(holder.alive(other_slot));
+        assert!(!holder.reclaim(other_slot, || panic!("reclaimed a live slot")));
+
+        // Liveness does not depend on the recorded pid
+        region.processes()[other_slot as usize].store(0x7FFF_FFF0, Ordering::SeqCst);
+        assert!(holder.alive(other_slot));
+
+        // Once it exits the slot is dead, reclaimable, and taken by the next process
+        drop(other);
+        assert!(!holder.alive(other_slot));
+        let mut recovered = false;
+        assert!(holder.reclaim(other_slot, || recovered = true));
+        assert!(recovered);
+        assert_eq!(other_process(&region).slot(), other_slot);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_too_many_processes() {
+        let name = unique_name();
+        let region = MetaRegion::create(&name, 4).unwrap();
+        let others: Vec<_> = (0..MAX_PROCESSES).map(|_| other_process(&region)).collect();
+
+        assert!(matches!(
+            region.holder(),
+            Err(Error::TooManyProcesses(MAX_PROCESSES))
+        ));
+        drop(others);
+        assert!(region.holder().is_ok());
+    }
+}
@@ -0,0 +1,309 @@
+//! Seqlock 原地覆写模式
+//!
+//! 适用于小而高频更新的 buffer（传感器状态、位姿向量等）：
+//! 写者原地覆写同一块 buffer，读者无锁读取最新的一致副本。
+//!
+//! 启用后 [`BufferMeta::seq`] 作为序列计数器：写入期间为奇数，写完后为偶数。
+//! 读者将数据以 volatile 方式拷贝到自己的缓冲区，前后两次读到相同的偶数序列号时
+//! 才接受该副本，因此不会看到撕裂数据，也不会阻塞写者。seqlock 模式下 [`BufferPool::get`](crate::BufferPool::get)
+//! 不再获取读锁。
+//!
+//! # 示例
+//!
+//! ```
+//! use xmem_core::BufferPool;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = BufferPool::create("/my_pool_seqlock_doc")?;
+//! let mut writer = pool.acquire_cpu(8)?;
+//! writer.enable_seqlock()?;
+//!
+//! let mut w = writer.begin_write()?;
+//! w.as_mut_slice().copy_from_slice(&42u64.to_le_bytes());
+//! w.end_write();
+//!
+//! let reader = pool.get(writer.meta_index())?;
+//! let mut value = [0u8; 8];
+//! let seq = reader.read_consistent(&mut value)?;
+//! assert_eq!((u64::from_le_bytes(value), seq), (42, 2));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::guard::BufferGuard;
+use crate::meta::{BufferMeta, FLAG_SEQLOCK};
+use crate::storage::AccessMode;
+use crate::{Error, Result};
+use std::ptr;
+use std::sync::atomic::{fence, Ordering};
+use std::time::{Duration, Instant};
+
+/// Seqlock 写入守卫
+///
+/// 创建时将序列号置为奇数，Drop 或 [`end_write()`](Self::end_write) 时恢复为偶数。
+pub struct SeqWriteGuard<'a> {
+    guard: &'a mut BufferGuard,
+    seq: u64,
+}
+
+impl SeqWriteGuard<'_> {
+    /// Get the buffer contents for writing
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // Mode and storage type were checked in begin_write
+        self.guard.as_cpu_slice_mut().unwrap_or_default()
+    }
+
+    /// Sequence number that readers will observe once this write completes
+    pub fn seq(&self) -> u64 {
+        self.seq + 2
+    }
+
+    /// Publish the write (same as dropping the guard)
+    pub fn end_write(self) {}
+}
+
+impl Drop for SeqWriteGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(meta) = self.guard.meta() {
+            meta.seq.store(self.seq + 2, Ordering::Release);
+        }
+    }
+}
+
+impl BufferGuard {
+    /// 启用 seqlock 模式（需要 ReadWrite 模式）
+    ///
+    /// 将序列号重置为 0。应在发布 buffer 索引前调用。
+    pub fn enable_seqlock(&self) -> Result<()> {
+        if self.mode() == AccessMode::ReadOnly {
+            return Err(Error::ReadOnly);
+        }
+        self.as_cpu_slice()?;
+        let meta = self.meta()?;
+        meta.seq.store(0, Ordering::SeqCst);
+        meta.flags.fetch_or(FLAG_SEQLOCK, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 检查 buffer 是否处于 seqlock 模式
+    pub fn is_seqlock(&self) -> bool {
+        self.meta()
+            .map(|m| m.flags.load(Ordering::SeqCst) & FLAG_SEQLOCK != 0)
+            .unwrap_or(false)
+    }
+
+    /// 开始一次 seqlock 写入
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::ReadOnly`]: guard 为只读
+    /// - [`Error::SeqlockDisabled`]: 未启用 seqlock 模式
+    /// - [`Error::Locked`]: 另一个 seqlock 写入尚未结束
+    pub fn begin_write(&mut self) -> Result<SeqWriteGuard<'_>> {
+        self.as_cpu_slice_mut()?;
+        let meta = self.seqlock_meta()?;
+
+        let seq = meta.seq.load(Ordering::Acquire);
+        if seq & 1 == 1
+            || meta
+                .seq
+                .compare_exchange(seq, seq + 1, Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+        {
+            return Err(Error::Locked(self.meta_index()));
+        }
+        // Data writes must not become visible before the odd sequence
+        fence(Ordering::Release);
+
+        Ok(SeqWriteGuard { guard: self, seq })
+    }
+
+    /// 将 buffer 开头 `out.len()` 字节的一致副本读入 `out`，返回该副本的序列号
+    ///
+    /// 反复拷贝直到拷贝前后序列号相同且为偶数。拷贝使用 volatile 读取，
+    /// 不会产生指向写者正在修改的内存的引用；被丢弃的拷贝只会留在 `out` 中被覆盖。
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::OutOfBounds`]: `out` 比 buffer 长
+    /// - [`Error::SeqlockDisabled`]: 未启用 seqlock 模式
+    pub fn read_consistent(&self, out: &mut [u8]) -> Result<u64> {
+        self.read_consistent_inner(None, out)
+    }
+
+    /// 同 [`read_consistent`](Self::read_consistent)，超时返回 [`Error::Timeout`]
+    ///
+    /// 可用于防止写者在写入过程中崩溃导致读者无限重试。
+    pub fn read_consistent_timeout(&self, timeout: Duration, out: &mut [u8]) -> Result<u64> {
+        self.read_consistent_inner(Some(Instant::now() + timeout), out)
+    }
+
+    fn read_consistent_inner(&self, deadline: Option<Instant>, out: &mut [u8]) -> Result<u64> {
+        let (data, size) = self.cpu_ptr()?;
+        if out.len() > size {
+            return Err(Error::OutOfBounds {
+                offset: 0,
+                len: out.len(),
+                size,
+            });
+        }
+        let meta = self.seqlock_meta()?;
+
+        let mut attempts = 0u32;
+        loop {
+            let before = meta.seq.load(Ordering::Acquire);
+            if before & 1 == 0 {
+                // Safety: `out.len()` bytes of the mapping are readable
+                unsafe { copy_volatile(data, out) };
+                // Data reads must complete before re-checking the sequence
+                fence(Ordering::Acquire);
+                if meta.seq.load(Ordering::Relaxed) == before {
+                    return Ok(before);
+                }
+            }
+
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Err(Error::Timeout);
+            }
+            attempts = attempts.saturating_add(1);
+            if attempts < 64 {
+                std::hint::spin_loop();
+            } else {
+                std::thread::yield_now();
+            }
+        }
+    }
+
+    fn seqlock_meta(&self) -> Result<&BufferMeta> {
+        let meta = self.meta()?;
+        if meta.flags.load(Ordering::SeqCst) & FLAG_SEQLOCK == 0 {
+            return Err(Error::SeqlockDisabled(self.meta_index()));
+        }
+        Ok(meta)
+    }
+}
+
+/// 逐字拷贝可能被并发修改的内存，对齐部分按机器字读取
+///
+/// # Safety
+///
+/// `src` 起始的 `dst.len()` 字节必须可读。
+unsafe fn copy_volatile(src: *const u8, dst: &mut [u8]) {
+    const WORD: usize = std::mem::size_of::<usize>();
+    let len = dst.len();
+    let mut i = 0;
+    while i < len && !(src as usize + i).is_multiple_of(WORD) {
+        dst[i] = ptr::read_volatile(src.add(i));
+        i += 1;
+    }
+    while i + WORD <= len {
+        let word = ptr::read_volatile(src.add(i) as *const usize);
+        dst[i..i + WORD].copy_from_slice(&word.to_ne_bytes());
+        i += WORD;
+    }
+    while i < len {
+        dst[i] = ptr::read_volatile(src.add(i));
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BufferPool, Error};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn unique_name() -> String {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        format!("/xmem_seqlock_test_{}", ts)
+    }
+
+    #[test]
+    fn test_seqlock_write_read() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let mut buf = pool.acquire_cpu(16).unwrap();
+        assert!(matches!(buf.begin_write(), Err(Error::SeqlockDisabled(_))));
+        buf.enable_seqlock().unwrap();
+
+        let mut w = buf.begin_write().unwrap();
+        assert_eq!(w.seq(), 2);
+        w.as_mut_slice().fill(7);
+        w.end_write();
+
+        // Readers do not take the RW lock, so a writer can still get_mut
+        let reader = pool.get(buf.meta_index()).unwrap();
+        assert!(reader.is_seqlock());
+        let _writer = pool.try_get_mut(buf.meta_index()).unwrap();
+
+        let mut copy = [0u8; 16];
+        assert_eq!(reader.read_consistent(&mut copy).unwrap(), 2);
+        assert_eq!(copy, [7; 16]);
+
+        // Prefixes of any alignment, but not past the end
+        let mut prefix = [0u8; 11];
+        reader.read_consistent(&mut prefix).unwrap();
+        assert_eq!(prefix, [7; 11]);
+        assert!(matches!(
+            reader.read_consistent(&mut [0u8; 17]),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_seqlock_write_in_progress() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let mut buf = pool.acquire_cpu(8).unwrap();
+        buf.enable_seqlock().unwrap();
+        let reader = pool.get(buf.meta_index()).unwrap();
+
+        let w = buf.begin_write().unwrap();
+        let mut copy = [0u8; 8];
+        assert!(matches!(
+            reader.read_consistent_timeout(Duration::from_millis(10), &mut copy),
+            Err(Error::Timeout)
+        ));
+        drop(w);
+        assert!(reader.read_consistent(&mut copy).is_ok());
+    }
+
+    #[test]
+    fn test_seqlock_no_torn_reads() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+
+        let mut buf = pool.acquire_cpu(256).unwrap();
+        buf.enable_seqlock().unwrap();
+        let idx = buf.meta_index();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_writer = Arc::clone(&stop);
+        let writer = std::thread::spawn(move || {
+            let mut i = 0u8;
+            while !stop_writer.load(Ordering::Relaxed) {
+                let mut w = buf.begin_write().unwrap();
+                w.as_mut_slice().fill(i);
+                drop(w);
+                i = i.wrapping_add(1);
+            }
+        });
+
+        let reader = pool.get(idx).unwrap();
+        let mut copy = [0u8; 256];
+        for _ in 0..10_000 {
+            reader.read_consistent(&mut copy).unwrap();
+            assert!(copy.iter().all(|&x| x == copy[0]));
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+    }
+}
@@ -19,8 +19,18 @@
 //! # }
 //! ```
 
+use crate::meta::BufferMeta;
+use crate::storage::{SegmentId, StorageBackend, StorageSegment, StorageType};
 use crate::{Error, Result};
 use shared_memory::{Shmem, ShmemConf};
+#[cfg(target_os = "linux")]
+use std::os::fd::{AsFd, AsRawFd};
+use std::os::fd::{BorrowedFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Filesystem holding POSIX shared memory on Linux
+pub(crate) const SHM_DIR: &str = "/dev/shm";
 
 /// POSIX 共享内存区域封装
 ///
@@ -50,6 +60,9 @@ pub struct SharedMemory {
     owner: bool,
 }
 
+// Safety: the mapping is valid process-wide and only unmapped on drop
+unsafe impl Send for SharedMemory {}
+
 impl SharedMemory {
     /// 创建新的共享内存区域
     ///
@@ -68,6 +81,11 @@ impl SharedMemory {
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// # 错误
+    ///
+    /// - [`Error::OutOfShmSpace`]: `/dev/shm` 空间不足，见 [`reserve_space`]（仅 Linux，
+    ///   其他平台的 POSIX 共享内存没有可检查的文件系统）
     pub fn create(name: &str, size: usize) -> Result<Self> {
         let shmem = ShmemConf::new()
             .size(size)
@@ -75,12 +93,16 @@ impl SharedMemory {
             .create()
             .map_err(|e| Error::SharedMemory(e.to_string()))?;
 
-        Ok(Self {
+        // Unlinked again on drop if the reservation fails
+        let shm = Self {
             inner: shmem,
             name: name.to_string(),
             size,
             owner: true,
-        })
+        };
+        #[cfg(target_os = "linux")]
+        reserve_space(Self::open_fd(name, true)?.as_fd(), size, Path::new(SHM_DIR))?;
+        Ok(shm)
     }
 
     /// Open an existing shared memory region
@@ -117,7 +139,7 @@ impl SharedMemory {
 
     /// Get a mutable raw pointer to the shared memory
     pub fn as_mut_ptr(&mut self) -> *mut u8 {
-        self.inner.as_ptr() as *mut u8
+        self.inner.as_ptr()
     }
 
     /// Get a slice view of the shared memory
@@ -134,6 +156,169 @@ impl SharedMemory {
     pub fn is_owner(&self) -> bool {
         self.owner
     }
+
+    /// 设置是否为 owner
+    ///
+    /// owner 在 Drop 时会 unlink 共享内存名称；设为 `false` 后
+    /// 名称的生命周期由调用方通过 [`unlink()`](Self::unlink) 管理。
+    pub fn set_owner(&mut self, owner: bool) {
+        self.inner.set_owner(owner);
+        self.owner = owner;
+    }
+
+    /// Remove a shared memory name (existing mappings stay valid)
+    pub fn unlink(name: &str) -> Result<()> {
+        let c_name = std::ffi::CString::new(name)
+            .map_err(|e| Error::SharedMemory(e.to_string()))?;
+        if unsafe { libc::shm_unlink(c_name.as_ptr()) } != 0 {
+            return Err(Error::SharedMemory(
+                std::io::Error::last_os_error().to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 打开共享内存名称，返回新的文件描述符（不映射）
+    ///
+    /// `writable` 为 false 时以只读方式打开。
+    pub fn open_fd(name: &str, writable: bool) -> Result<OwnedFd> {
+        let c_name = std::ffi::CString::new(name)
+            .map_err(|e| Error::SharedMemory(e.to_string()))?;
+        let flags = if writable { libc::O_RDWR } else { libc::O_RDONLY };
+        let fd = unsafe { libc::shm_open(c_name.as_ptr(), flags | libc::O_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(Error::SharedMemory(
+                std::io::Error::last_os_error().to_string(),
+            ));
+        }
+        Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+    }
+}
+
+/// 为新段预留全部物理页
+///
+/// tmpfs 上的 ftruncate 只产生稀疏文件，空间不足时进程会在首次写入时被 SIGBUS 杀死。
+/// 这里先按 `statvfs` 检查 `fs` 所在文件系统的可用空间，再用 `posix_fallocate`
+/// 实际分配；文件系统不支持预分配时只做空间检查。`posix_fallocate` 仅在 Linux 上使用，
+/// 其他平台（如 macOS 没有该调用）只做空间检查，文件大小已由调用方 `ftruncate` 设定。
+///
+/// # 错误
+///
+/// - [`Error::OutOfShmSpace`]: 可用空间不足 `size` 字节
+pub(crate) fn reserve_space(fd: BorrowedFd<'_>, size: usize, fs: &Path) -> Result<()> {
+    if size == 0 {
+        return Ok(());
+    }
+    let out_of_space = |available: Option<u64>| Error::OutOfShmSpace {
+        requested: size as u64,
+        available: available.unwrap_or(0),
+    };
+    let available = available_space(fs)?;
+    if available.is_some_and(|available| available < size as u64) {
+        return Err(out_of_space(available));
+    }
+
+    allocate(fd, size).or_else(|err| match err {
+        libc::EOPNOTSUPP | libc::EINVAL => Ok(()),
+        libc::ENOSPC => Err(out_of_space(available_space(fs)?)),
+        err => Err(Error::SharedMemory(
+            std::io::Error::from_raw_os_error(err).to_string(),
+        )),
+    })
+}
+
+/// Allocate the blocks of `fd` up to `size`, returning the errno on failure
+#[cfg(target_os = "linux")]
+fn allocate(fd: BorrowedFd<'_>, size: usize) -> std::result::Result<(), i32> {
+    match unsafe { libc::posix_fallocate(fd.as_raw_fd(), 0, size as libc::off_t) } {
+        0 => Ok(()),
+        err => Err(err),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn allocate(_fd: BorrowedFd<'_>, _size: usize) -> std::result::Result<(), i32> {
+    Ok(())
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Stand-in for `statvfs` results in tests
+    pub(crate) static MOCK_AVAILABLE: std::cell::Cell<Option<u64>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Bytes available to unprivileged users on the filesystem of `path`, `None` if unlimited
+fn available_space(path: &Path) -> Result<Option<u64>> {
+    #[cfg(test)]
+    if let Some(available) = MOCK_AVAILABLE.with(|m| m.get()) {
+        return Ok(Some(available));
+    }
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| Error::SharedMemory(e.to_string()))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(Error::SharedMemory(
+            std::io::Error::last_os_error().to_string(),
+        ));
+    }
+    // tmpfs mounted without a size limit reports no blocks at all
+    if stat.f_blocks == 0 {
+        return Ok(None);
+    }
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+impl StorageSegment for SharedMemory {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn cpu_ptr(&self) -> Option<*mut u8> {
+        Some(self.inner.as_ptr())
+    }
+
+    fn shm(&self) -> Option<&SharedMemory> {
+        Some(self)
+    }
+}
+
+/// 内置 CPU 存储后端：每个 buffer 一个 POSIX 共享内存段，名称为 [`SegmentId::shm_name`]
+///
+/// 段不归任何一个映射所有：名称在 [`free`](StorageBackend::free)（引用计数归 0
+/// 或创建方销毁池）时才 unlink，创建方的 guard 先于其他进程 drop 不影响读取。
+pub struct ShmBackend;
+
+impl StorageBackend for ShmBackend {
+    fn storage_type(&self) -> u8 {
+        StorageType::Cpu as u8
+    }
+
+    fn name(&self) -> &str {
+        "Cpu"
+    }
+
+    fn allocate(
+        &self,
+        id: SegmentId<'_>,
+        size: usize,
+        _device_id: u8,
+    ) -> Result<Box<dyn StorageSegment>> {
+        let mut shm = SharedMemory::create(&id.shm_name(), size)?;
+        // The segment lives until the ref count drops to 0, not until this mapping drops
+        shm.set_owner(false);
+        Ok(Box::new(shm))
+    }
+
+    fn import(&self, id: SegmentId<'_>, _meta: &BufferMeta) -> Result<Box<dyn StorageSegment>> {
+        Ok(Box::new(SharedMemory::open(&id.shm_name())?))
+    }
+
+    fn free(&self, id: SegmentId<'_>, _meta: &BufferMeta) -> Result<()> {
+        // Processes still mapping the segment keep their mapping
+        SharedMemory::unlink(&id.shm_name())
+    }
 }
 
 #[cfg(test)]
@@ -179,6 +364,37 @@ mod tests {
         assert_eq!(&shm2.as_slice()[..data.len()], data);
     }
 
+    #[test]
+    fn test_unlink_keeps_mapping() {
+        let name = unique_name();
+        let mut shm = SharedMemory::create(&name, 64).unwrap();
+        shm.set_owner(false);
+        shm.as_mut_slice()[..2].copy_from_slice(b"ok");
+
+        SharedMemory::unlink(&name).unwrap();
+        assert!(SharedMemory::open(&name).is_err());
+        assert_eq!(&shm.as_slice()[..2], b"ok");
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_out_of_space() {
+        let name = unique_name();
+        MOCK_AVAILABLE.with(|m| m.set(Some(4096)));
+        let result = SharedMemory::create(&name, 8192);
+        MOCK_AVAILABLE.with(|m| m.set(None));
+
+        assert!(matches!(
+            result,
+            Err(Error::OutOfShmSpace {
+                requested: 8192,
+                available: 4096
+            })
+        ));
+        // The failed segment is not left behind
+        assert!(SharedMemory::open(&name).is_err());
+    }
+
     #[test]
     fn test_open_nonexistent() {
         let result = SharedMemory::open("/xmem_nonexistent_12345");
@@ -1,4 +1,22 @@
-//! Storage type definitions
+//! Storage type definitions and backend registry
+//!
+//! buffer 数据由 [`StorageBackend`] 分配和打开，按 [`BufferMeta::storage_type`] 中的
+//! 存储类型 id 注册到进程内的注册表。内置 CPU 共享内存（[`ShmBackend`]）和
+//! CUDA 显存（`CudaBackend`，需启用 `cuda` feature）两种实现；自定义后端使用
+//! [`FIRST_CUSTOM_STORAGE_TYPE`] 及以上的 id，通过 [`register_backend`] 注册，
+//! 再以 [`BufferPool::acquire_storage`] 分配。
+//!
+//! 跨进程共享时，每个进程都需要注册同一 id 的后端才能打开对应 buffer。
+//!
+//! [`BufferMeta::storage_type`]: crate::BufferMeta::storage_type
+//! [`ShmBackend`]: crate::shm::ShmBackend
+//! [`BufferPool::acquire_storage`]: crate::BufferPool::acquire_storage
+
+use crate::meta::BufferMeta;
+use crate::shm::{ShmBackend, SharedMemory};
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
 
 /// Storage location type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -7,6 +25,12 @@ pub enum StorageType {
     Cpu = 0,
     #[cfg(feature = "cuda")]
     Cuda = 1,
+    /// Sub-range of another buffer
+    View = 2,
+    /// Process-local heap memory, see [`BufferPool::in_memory`](crate::BufferPool::in_memory)
+    Heap = 3,
+    /// Memory-mapped file, see [`BufferPool::create_file`](crate::BufferPool::create_file)
+    File = 4,
 }
 
 impl StorageType {
@@ -16,6 +40,9 @@ impl StorageType {
             0 => Some(StorageType::Cpu),
             #[cfg(feature = "cuda")]
             1 => Some(StorageType::Cuda),
+            2 => Some(StorageType::View),
+            3 => Some(StorageType::Heap),
+            4 => Some(StorageType::File),
             _ => None,
         }
     }
@@ -27,3 +54,134 @@ pub enum AccessMode {
     ReadOnly,
     ReadWrite,
 }
+
+/// First storage type id available to [`register_backend`]; lower ids are reserved for xmem
+pub const FIRST_CUSTOM_STORAGE_TYPE: u8 = 128;
+
+/// Identifies the data segment of one buffer slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentId<'a> {
+    /// Pool name
+    pub pool: &'a str,
+    /// Metadata index of the buffer
+    pub index: u32,
+}
+
+impl SegmentId<'_> {
+    /// Conventional segment name, `{pool}_buf_{index}`
+    pub fn shm_name(&self) -> String {
+        format!("{}_buf_{}", self.pool, self.index)
+    }
+}
+
+/// 一个 buffer 的数据段，由 [`StorageBackend`] 创建
+///
+/// 段在 drop 时只释放本进程的映射或句柄；底层存储由 [`StorageBackend::free`] 回收。
+pub trait StorageSegment: Send {
+    /// Size in bytes
+    fn size(&self) -> usize;
+
+    /// Host-accessible data pointer, `None` for device memory
+    fn cpu_ptr(&self) -> Option<*mut u8> {
+        None
+    }
+
+    /// Device pointer, `None` for host memory
+    fn device_ptr(&self) -> Option<u64> {
+        None
+    }
+
+    /// Device ID of device memory
+    fn device_id(&self) -> Option<i32> {
+        None
+    }
+
+    /// Backing POSIX shared memory segment, if any
+    fn shm(&self) -> Option<&SharedMemory> {
+        None
+    }
+
+    /// 将跨进程打开所需的句柄写入元数据（[`BufferMeta::set_storage_handle`]）
+    ///
+    /// 默认不写入，适用于可由 [`SegmentId`] 推导位置的存储。
+    fn export_handle(&self, meta: &BufferMeta) -> Result<()> {
+        let _ = meta;
+        Ok(())
+    }
+
+    /// 将数据刷写到持久存储，默认无操作（非持久化存储）
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// 存储后端：分配、打开和回收某一存储类型的 buffer 数据
+///
+/// 实现需要是无状态的或内部同步的，同一后端会被多个线程和池同时使用。
+pub trait StorageBackend: Send + Sync {
+    /// Storage type id recorded in [`BufferMeta::storage_type`]
+    fn storage_type(&self) -> u8;
+
+    /// Human-readable name used in error messages (e.g. `"Cpu"`)
+    fn name(&self) -> &str;
+
+    /// 为新 buffer 分配 `size` 字节存储
+    ///
+    /// `device_id` 仅对设备内存有意义。返回的段随后通过
+    /// [`StorageSegment::export_handle`] 写入元数据。
+    fn allocate(&self, id: SegmentId<'_>, size: usize, device_id: u8)
+        -> Result<Box<dyn StorageSegment>>;
+
+    /// 根据元数据（size、device_id、storage handle 等）打开已分配的存储
+    fn import(&self, id: SegmentId<'_>, meta: &BufferMeta) -> Result<Box<dyn StorageSegment>>;
+
+    /// 引用计数归零时回收存储，已打开的段应保持可用直到 drop
+    ///
+    /// 创建池的进程在池销毁时会对所有用过的槽再次调用，因此需容忍已回收的存储。
+    fn free(&self, id: SegmentId<'_>, meta: &BufferMeta) -> Result<()>;
+}
+
+type Registry = RwLock<HashMap<u8, Arc<dyn StorageBackend>>>;
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut backends: HashMap<u8, Arc<dyn StorageBackend>> = HashMap::new();
+        backends.insert(StorageType::Cpu as u8, Arc::new(ShmBackend));
+        #[cfg(feature = "cuda")]
+        backends.insert(StorageType::Cuda as u8, Arc::new(crate::cuda::CudaBackend));
+        RwLock::new(backends)
+    })
+}
+
+/// 注册自定义存储后端
+///
+/// # 错误
+///
+/// - [`Error::StorageTypeTaken`]: id 小于 [`FIRST_CUSTOM_STORAGE_TYPE`] 或已被注册
+pub fn register_backend(backend: Arc<dyn StorageBackend>) -> Result<()> {
+    let storage_type = backend.storage_type();
+    if storage_type < FIRST_CUSTOM_STORAGE_TYPE {
+        return Err(Error::StorageTypeTaken(storage_type));
+    }
+    let mut backends = registry().write().unwrap_or_else(|e| e.into_inner());
+    if backends.contains_key(&storage_type) {
+        return Err(Error::StorageTypeTaken(storage_type));
+    }
+    backends.insert(storage_type, backend);
+    Ok(())
+}
+
+/// Look up the backend registered for a storage type id
+///
+/// # 错误
+///
+/// - [`Error::UnknownStorage`]: 该 id 没有注册后端（视图也没有后端）
+pub fn backend(storage_type: u8) -> Result<Arc<dyn StorageBackend>> {
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&storage_type)
+        .cloned()
+        .ok_or(Error::UnknownStorage(storage_type))
+}
@@ -143,6 +143,37 @@ mod integration {
         }
     }
 
+    /// 测试持锁进程崩溃后锁可被回收
+    #[test]
+    fn test_lock_recovered_after_process_death() {
+        let name = unique_name();
+        let pool = BufferPool::create(&name).unwrap();
+        let buf = pool.acquire_cpu(1024).unwrap();
+        let idx = buf.meta_index();
+
+        match unsafe { fork() }.unwrap() {
+            ForkResult::Child => {
+                let pool = BufferPool::open(&name).unwrap();
+                let _w = pool.get_mut(idx).unwrap();
+                // exit() 不运行析构函数，写锁保持未释放
+                std::process::exit(0);
+            }
+            ForkResult::Parent { child } => {
+                let status = waitpid(child, None).unwrap();
+                assert!(is_exit_success(status));
+
+                // 子进程已退出，写锁应被回收
+                let r = pool.try_get(idx).unwrap();
+                drop(r);
+                assert!(pool.try_get_mut(idx).is_ok());
+
+                drop(buf);
+                drop(pool);
+                clean_shared_memory(&name);
+            }
+        }
+    }
+
     /// 清理共享内存辅助函数
     fn clean_shared_memory(pool_name: &str) {
         // 清理 meta
@@ -0,0 +1,281 @@
+/* xmem C API. Generated by cbindgen from crates/xmem-ffi, do not edit. */
+
+#ifndef XMEM_H
+#define XMEM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Maximum number of tensor dimensions
+#define XMEM_MAX_NDIM 8
+
+// Size of `XmemMeta` label fields, including the terminating NUL
+#define XMEM_LABEL_CAPACITY 33
+
+// Result of an API call
+typedef enum XmemStatus {
+  XMEM_STATUS_OK = 0,
+  // Null pointer, invalid string or out-of-range argument
+  XMEM_STATUS_INVALID_ARGUMENT = 1,
+  // Shared memory could not be created, opened or mapped, or the pool or its process table is full
+  XMEM_STATUS_SHARED_MEMORY = 2,
+  // No buffer at the index
+  XMEM_STATUS_NOT_FOUND = 3,
+  // Operation not supported by the buffer's storage type, or no backend for it
+  XMEM_STATUS_TYPE_MISMATCH = 4,
+  // Write through a read-only buffer
+  XMEM_STATUS_READ_ONLY = 5,
+  // Buffer was already released or forgotten
+  XMEM_STATUS_ALREADY_FORGOTTEN = 6,
+  // Access outside the buffer
+  XMEM_STATUS_OUT_OF_BOUNDS = 7,
+  // Invalid tensor shape or dtype
+  XMEM_STATUS_INVALID_SHAPE = 8,
+  // Buffer is locked by another holder
+  XMEM_STATUS_LOCKED = 9,
+  // Timed out waiting for a buffer or lock
+  XMEM_STATUS_TIMEOUT = 10,
+  // Handle refers to a recycled buffer
+  XMEM_STATUS_STALE_HANDLE = 11,
+  // Other error
+  XMEM_STATUS_OTHER = 12,
+  // Internal panic (a bug in xmem)
+  XMEM_STATUS_PANIC = 13,
+  // Not enough space left in /dev/shm (or the backing directory) for the buffer
+  XMEM_STATUS_OUT_OF_SPACE = 14,
+} XmemStatus;
+
+// Element data types, same values as the shared-memory `dtype` field
+typedef enum XmemDtype {
+  XMEM_DTYPE_UINT8 = 0,
+  XMEM_DTYPE_INT8 = 1,
+  XMEM_DTYPE_UINT16 = 2,
+  XMEM_DTYPE_INT16 = 3,
+  XMEM_DTYPE_UINT32 = 4,
+  XMEM_DTYPE_INT32 = 5,
+  XMEM_DTYPE_UINT64 = 6,
+  XMEM_DTYPE_INT64 = 7,
+  XMEM_DTYPE_FLOAT16 = 8,
+  XMEM_DTYPE_FLOAT32 = 9,
+  XMEM_DTYPE_FLOAT64 = 10,
+  XMEM_DTYPE_BFLOAT16 = 11,
+  XMEM_DTYPE_BOOL = 12,
+  XMEM_DTYPE_COMPLEX64 = 13,
+  XMEM_DTYPE_COMPLEX128 = 14,
+  XMEM_DTYPE_FLOAT8_E4M3 = 15,
+  XMEM_DTYPE_FLOAT8_E5M2 = 16,
+} XmemDtype;
+
+// Opaque buffer handle, holding one reference (and a lock for `get` / `get_mut`)
+typedef struct XmemBuffer XmemBuffer;
+
+// Opaque pool handle
+typedef struct XmemPool XmemPool;
+
+// Snapshot of a buffer's metadata
+typedef struct XmemMeta {
+  // Number of valid `shape` / `strides` entries
+  uint32_t ndim;
+  // `XmemDtype` value, -1 if unknown
+  int32_t dtype;
+  uint64_t shape[XMEM_MAX_NDIM];
+  // Strides in bytes
+  uint64_t strides[XMEM_MAX_NDIM];
+  // Buffer size in bytes
+  uint64_t size;
+  // Publish time in milliseconds since the Unix epoch
+  uint64_t timestamp;
+  uint64_t seq;
+  // NUL-terminated content type label
+  char content_type[XMEM_LABEL_CAPACITY];
+  // NUL-terminated producer label
+  char producer[XMEM_LABEL_CAPACITY];
+} XmemMeta;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Release a buffer handle and its reference (NULL is ignored)
+//
+// # Safety
+//
+// `buf` must come from this API and not be used afterwards.
+void xmem_buffer_release(struct XmemBuffer *buf);
+
+// Free a buffer handle but keep its reference, e.g. to hand the buffer to another process
+//
+// The reference is later given back with `xmem_pool_release`. NULL is ignored.
+//
+// # Safety
+//
+// `buf` must come from this API and not be used afterwards.
+void xmem_buffer_forget(struct XmemBuffer *buf);
+
+// Get the metadata index of a buffer (`UINT32_MAX` for NULL)
+//
+// # Safety
+//
+// `buf` must be a valid pointer or NULL.
+uint32_t xmem_buffer_index(const struct XmemBuffer *buf);
+
+// Get read-only access to a CPU buffer's data
+//
+// # Safety
+//
+// All pointers must be valid. The data stays valid until the buffer is released.
+enum XmemStatus xmem_buffer_data(const struct XmemBuffer *buf, const void **data, size_t *size);
+
+// Get writable access to a CPU buffer's data (fails with `XMEM_STATUS_READ_ONLY` for `get`)
+//
+// # Safety
+//
+// All pointers must be valid. The data stays valid until the buffer is released.
+enum XmemStatus xmem_buffer_data_mut(struct XmemBuffer *buf, void **data, size_t *size);
+
+// Copy the buffer's metadata into `out`
+//
+// # Safety
+//
+// `buf` and `out` must be valid pointers.
+enum XmemStatus xmem_buffer_get_meta(const struct XmemBuffer *buf, struct XmemMeta *out);
+
+// Record a C-contiguous tensor layout (writable buffers only)
+//
+// The tensor must fit in the buffer.
+//
+// # Safety
+//
+// `buf` must be a valid pointer and `shape` point to `ndim` values.
+enum XmemStatus xmem_buffer_set_tensor(struct XmemBuffer *buf,
+                                       const size_t *shape,
+                                       uint32_t ndim,
+                                       int32_t dtype);
+
+// Set the content type label, at most 32 bytes (writable buffers only)
+//
+// # Safety
+//
+// `buf` must be a valid pointer and `value` a NUL-terminated string.
+enum XmemStatus xmem_buffer_set_content_type(struct XmemBuffer *buf, const char *value);
+
+// Set the producer label, at most 32 bytes (writable buffers only)
+//
+// # Safety
+//
+// `buf` must be a valid pointer and `value` a NUL-terminated string.
+enum XmemStatus xmem_buffer_set_producer(struct XmemBuffer *buf, const char *value);
+
+// Set the sequence number (writable buffers only, not in seqlock mode)
+//
+// # Safety
+//
+// `buf` must be a valid pointer.
+enum XmemStatus xmem_buffer_set_seq(struct XmemBuffer *buf, uint64_t seq);
+
+// Message of the last failed call on this thread, or NULL if none failed yet
+//
+// The string stays valid until the next failing call on the same thread.
+const char *xmem_last_error(void);
+
+// Create a pool; `capacity` 0 uses the default
+//
+// # Safety
+//
+// `name` must be a NUL-terminated string and `out` a valid pointer.
+enum XmemStatus xmem_pool_create(const char *name, size_t capacity, struct XmemPool **out);
+
+// Open an existing pool
+//
+// # Safety
+//
+// `name` must be a NUL-terminated string and `out` a valid pointer.
+enum XmemStatus xmem_pool_open(const char *name, struct XmemPool **out);
+
+// Close a pool handle (NULL is ignored)
+//
+// Buffers acquired from the pool stay valid until released.
+//
+// # Safety
+//
+// `pool` must come from `xmem_pool_create` / `xmem_pool_open` and not be used afterwards.
+void xmem_pool_close(struct XmemPool *pool);
+
+// Allocate a CPU buffer of `size` bytes
+//
+// # Safety
+//
+// `pool` and `out` must be valid pointers.
+enum XmemStatus xmem_pool_acquire_cpu(const struct XmemPool *pool,
+                                      size_t size,
+                                      struct XmemBuffer **out);
+
+// Get an existing buffer with a shared read lock
+//
+// `timeout_ms` < 0 waits forever, 0 fails with `XMEM_STATUS_LOCKED` if write-locked,
+// otherwise fails with `XMEM_STATUS_TIMEOUT` after that many milliseconds.
+//
+// # Safety
+//
+// `pool` and `out` must be valid pointers.
+enum XmemStatus xmem_pool_get(const struct XmemPool *pool,
+                              uint32_t meta_index,
+                              int64_t timeout_ms,
+                              struct XmemBuffer **out);
+
+// Get an existing buffer with an exclusive write lock
+//
+// `timeout_ms` as for `xmem_pool_get`.
+//
+// # Safety
+//
+// `pool` and `out` must be valid pointers.
+enum XmemStatus xmem_pool_get_mut(const struct XmemPool *pool,
+                                  uint32_t meta_index,
+                                  int64_t timeout_ms,
+                                  struct XmemBuffer **out);
+
+// Set the reference count of a buffer
+//
+// # Safety
+//
+// `pool` must be a valid pointer.
+enum XmemStatus xmem_pool_set_ref_count(const struct XmemPool *pool,
+                                        uint32_t meta_index,
+                                        int32_t count);
+
+// Add a reference, storing the new count in `out_count` (may be NULL)
+//
+// # Safety
+//
+// `pool` must be a valid pointer, `out_count` valid or NULL.
+enum XmemStatus xmem_pool_add_ref(const struct XmemPool *pool,
+                                  uint32_t meta_index,
+                                  int32_t *out_count);
+
+// Drop a reference, freeing the buffer when none are left
+//
+// Gives back a reference taken with `xmem_pool_add_ref` or left by
+// `xmem_buffer_forget`. The new count is stored in `out_count` (may be NULL).
+//
+// # Safety
+//
+// `pool` must be a valid pointer, `out_count` valid or NULL.
+enum XmemStatus xmem_pool_release(const struct XmemPool *pool,
+                                  uint32_t meta_index,
+                                  int32_t *out_count);
+
+// Get the reference count of a buffer
+//
+// # Safety
+//
+// `pool` and `out_count` must be valid pointers.
+enum XmemStatus xmem_pool_ref_count(const struct XmemPool *pool,
+                                    uint32_t meta_index,
+                                    int32_t *out_count);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* XMEM_H */
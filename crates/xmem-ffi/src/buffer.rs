@@ -0,0 +1,241 @@
+//! Buffer functions
+
+use crate::{
+    c_str, deref, deref_mut, ffi_call, FfiError, XmemStatus, XMEM_LABEL_CAPACITY, XMEM_MAX_NDIM,
+};
+use std::ffi::{c_char, c_void};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::Ordering;
+use xmem_core::{AccessMode, BufferGuard, BufferMeta, DType};
+
+/// Opaque buffer handle, holding one reference (and a lock for `get` / `get_mut`)
+pub struct XmemBuffer {
+    guard: BufferGuard,
+}
+
+impl XmemBuffer {
+    pub(crate) fn new(guard: BufferGuard) -> Self {
+        Self { guard }
+    }
+
+    fn writable_meta(&self) -> Result<&BufferMeta, FfiError> {
+        if self.guard.mode() == AccessMode::ReadOnly {
+            return Err(xmem_core::Error::ReadOnly.into());
+        }
+        Ok(self.guard.meta()?)
+    }
+}
+
+/// Snapshot of a buffer's metadata
+#[repr(C)]
+pub struct XmemMeta {
+    /// Number of valid `shape` / `strides` entries
+    pub ndim: u32,
+    /// `XmemDtype` value, -1 if unknown
+    pub dtype: i32,
+    pub shape: [u64; XMEM_MAX_NDIM],
+    /// Strides in bytes
+    pub strides: [u64; XMEM_MAX_NDIM],
+    /// Buffer size in bytes
+    pub size: u64,
+    /// Publish time in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub seq: u64,
+    /// NUL-terminated content type label
+    pub content_type: [c_char; XMEM_LABEL_CAPACITY],
+    /// NUL-terminated producer label
+    pub producer: [c_char; XMEM_LABEL_CAPACITY],
+}
+
+fn copy_label(dst: &mut [c_char; XMEM_LABEL_CAPACITY], value: &str) {
+    dst.fill(0);
+    for (d, &b) in dst.iter_mut().zip(value.as_bytes()) {
+        *d = b as c_char;
+    }
+}
+
+/// Release a buffer handle and its reference (NULL is ignored)
+///
+/// # Safety
+///
+/// `buf` must come from this API and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_release(buf: *mut XmemBuffer) {
+    if !buf.is_null() {
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(buf))));
+    }
+}
+
+/// Free a buffer handle but keep its reference, e.g. to hand the buffer to another process
+///
+/// The reference is later given back with `xmem_pool_release`. NULL is ignored.
+///
+/// # Safety
+///
+/// `buf` must come from this API and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_forget(buf: *mut XmemBuffer) {
+    if !buf.is_null() {
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| Box::from_raw(buf).guard.forget()));
+    }
+}
+
+/// Get the metadata index of a buffer (`UINT32_MAX` for NULL)
+///
+/// # Safety
+///
+/// `buf` must be a valid pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_index(buf: *const XmemBuffer) -> u32 {
+    buf.as_ref().map_or(u32::MAX, |b| b.guard.meta_index())
+}
+
+/// Get read-only access to a CPU buffer's data
+///
+/// # Safety
+///
+/// All pointers must be valid. The data stays valid until the buffer is released.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_data(
+    buf: *const XmemBuffer,
+    data: *mut *const c_void,
+    size: *mut usize,
+) -> XmemStatus {
+    ffi_call(|| {
+        let slice = deref(buf, "buf")?.guard.as_cpu_slice()?;
+        *deref_mut(data, "data")? = slice.as_ptr() as *const c_void;
+        *deref_mut(size, "size")? = slice.len();
+        Ok(())
+    })
+}
+
+/// Get writable access to a CPU buffer's data (fails with `XMEM_STATUS_READ_ONLY` for `get`)
+///
+/// # Safety
+///
+/// All pointers must be valid. The data stays valid until the buffer is released.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_data_mut(
+    buf: *mut XmemBuffer,
+    data: *mut *mut c_void,
+    size: *mut usize,
+) -> XmemStatus {
+    ffi_call(|| {
+        let slice = deref_mut(buf, "buf")?.guard.as_cpu_slice_mut()?;
+        *deref_mut(data, "data")? = slice.as_mut_ptr() as *mut c_void;
+        *deref_mut(size, "size")? = slice.len();
+        Ok(())
+    })
+}
+
+/// Copy the buffer's metadata into `out`
+///
+/// # Safety
+///
+/// `buf` and `out` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_get_meta(
+    buf: *const XmemBuffer,
+    out: *mut XmemMeta,
+) -> XmemStatus {
+    ffi_call(|| {
+        let meta = deref(buf, "buf")?.guard.meta()?;
+        let out = deref_mut(out, "out")?;
+        let shape = meta.shape();
+        let strides = meta.strides();
+        out.ndim = shape.len() as u32;
+        out.dtype = meta.dtype().map_or(-1, |d| d as i32);
+        out.shape = [0; XMEM_MAX_NDIM];
+        out.strides = [0; XMEM_MAX_NDIM];
+        for (i, (&dim, &stride)) in shape.iter().zip(&strides).enumerate() {
+            out.shape[i] = dim as u64;
+            out.strides[i] = stride as u64;
+        }
+        out.size = meta.size.load(Ordering::SeqCst);
+        out.timestamp = meta.timestamp.load(Ordering::SeqCst);
+        out.seq = meta.seq.load(Ordering::SeqCst);
+        copy_label(&mut out.content_type, &meta.content_type());
+        copy_label(&mut out.producer, &meta.producer());
+        Ok(())
+    })
+}
+
+/// Record a C-contiguous tensor layout (writable buffers only)
+///
+/// The tensor must fit in the buffer.
+///
+/// # Safety
+///
+/// `buf` must be a valid pointer and `shape` point to `ndim` values.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_set_tensor(
+    buf: *mut XmemBuffer,
+    shape: *const usize,
+    ndim: u32,
+    dtype: i32,
+) -> XmemStatus {
+    ffi_call(|| {
+        let buf = deref(buf, "buf")?;
+        // Report a read-only buffer before any argument error
+        buf.writable_meta()?;
+        let shape = match ndim {
+            0 => &[][..],
+            n => std::slice::from_raw_parts(deref(shape, "shape")?, n as usize),
+        };
+        let dtype = u8::try_from(dtype)
+            .ok()
+            .and_then(DType::from_u8)
+            .ok_or_else(|| FfiError::invalid(format!("unknown dtype {}", dtype)))?;
+        Ok(buf.guard.set_tensor(shape, dtype)?)
+    })
+}
+
+/// Set the content type label, at most 32 bytes (writable buffers only)
+///
+/// # Safety
+///
+/// `buf` must be a valid pointer and `value` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_set_content_type(
+    buf: *mut XmemBuffer,
+    value: *const c_char,
+) -> XmemStatus {
+    ffi_call(|| {
+        let meta = deref(buf, "buf")?.writable_meta()?;
+        Ok(meta.set_content_type(c_str(value, "value")?)?)
+    })
+}
+
+/// Set the producer label, at most 32 bytes (writable buffers only)
+///
+/// # Safety
+///
+/// `buf` must be a valid pointer and `value` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_set_producer(
+    buf: *mut XmemBuffer,
+    value: *const c_char,
+) -> XmemStatus {
+    ffi_call(|| {
+        let meta = deref(buf, "buf")?.writable_meta()?;
+        Ok(meta.set_producer(c_str(value, "value")?)?)
+    })
+}
+
+/// Set the sequence number (writable buffers only, not in seqlock mode)
+///
+/// # Safety
+///
+/// `buf` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_buffer_set_seq(buf: *mut XmemBuffer, seq: u64) -> XmemStatus {
+    ffi_call(|| {
+        let buf = deref(buf, "buf")?;
+        let meta = buf.writable_meta()?;
+        if buf.guard.is_seqlock() {
+            return Err(FfiError::invalid("seq is managed by the seqlock"));
+        }
+        meta.seq.store(seq, Ordering::SeqCst);
+        Ok(())
+    })
+}
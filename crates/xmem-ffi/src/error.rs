@@ -0,0 +1,147 @@
+//! Status codes and the thread-local error message
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{self, AssertUnwindSafe};
+use xmem_core::Error;
+
+/// Result of an API call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmemStatus {
+    Ok = 0,
+    /// Null pointer, invalid string or out-of-range argument
+    InvalidArgument = 1,
+    /// Shared memory could not be created, opened or mapped, or the pool or its process table is full
+    SharedMemory = 2,
+    /// No buffer at the index
+    NotFound = 3,
+    /// Operation not supported by the buffer's storage type, or no backend for it
+    TypeMismatch = 4,
+    /// Write through a read-only buffer
+    ReadOnly = 5,
+    /// Buffer was already released or forgotten
+    AlreadyForgotten = 6,
+    /// Access outside the buffer
+    OutOfBounds = 7,
+    /// Invalid tensor shape or dtype
+    InvalidShape = 8,
+    /// Buffer is locked by another holder
+    Locked = 9,
+    /// Timed out waiting for a buffer or lock
+    Timeout = 10,
+    /// Handle refers to a recycled buffer
+    StaleHandle = 11,
+    /// Other error
+    Other = 12,
+    /// Internal panic (a bug in xmem)
+    Panic = 13,
+    /// Not enough space left in /dev/shm (or the backing directory) for the buffer
+    OutOfSpace = 14,
+}
+
+/// Error carried back to the C caller
+#[derive(Debug)]
+pub(crate) struct FfiError {
+    status: XmemStatus,
+    message: String,
+}
+
+impl FfiError {
+    pub(crate) fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: XmemStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for FfiError {
+    fn from(e: Error) -> Self {
+        let status = match &e {
+            Error::SharedMemory(_) | Error::TooManyProcesses(_) => XmemStatus::SharedMemory,
+            Error::BufferNotFound(_) => XmemStatus::NotFound,
+            Error::TypeMismatch { .. } => XmemStatus::TypeMismatch,
+            Error::ReadOnly => XmemStatus::ReadOnly,
+            Error::AlreadyForgotten => XmemStatus::AlreadyForgotten,
+            Error::OutOfBounds { .. } => XmemStatus::OutOfBounds,
+            Error::InvalidShape(_) | Error::UnknownDType(_) => XmemStatus::InvalidShape,
+            Error::LabelTooLong { .. } | Error::SeqlockDisabled(_) => XmemStatus::InvalidArgument,
+            Error::UnknownStorage(_) => XmemStatus::TypeMismatch,
+            Error::StorageTypeTaken(_) | Error::InvalidName { .. } => XmemStatus::InvalidArgument,
+            Error::Locked(_) => XmemStatus::Locked,
+            Error::Timeout => XmemStatus::Timeout,
+            Error::OutOfShmSpace { .. } => XmemStatus::OutOfSpace,
+            Error::StaleHandle { .. } => XmemStatus::StaleHandle,
+            #[cfg(feature = "cuda")]
+            Error::Cuda(_) => XmemStatus::Other,
+        };
+        Self {
+            status,
+            message: e.to_string(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NULs would truncate the message, so replace them
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Run an API call body, converting errors and panics to a status
+pub(crate) fn ffi_call(f: impl FnOnce() -> Result<(), FfiError>) -> XmemStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => XmemStatus::Ok,
+        Ok(Err(e)) => {
+            set_last_error(e.message);
+            e.status
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(format!("panic: {}", message));
+            XmemStatus::Panic
+        }
+    }
+}
+
+/// Message of the last failed call on this thread, or NULL if none failed yet
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn xmem_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| {
+        e.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(ffi_call(|| Ok(())), XmemStatus::Ok);
+
+        let status = ffi_call(|| Err(Error::Locked(3).into()));
+        assert_eq!(status, XmemStatus::Locked);
+        let message = unsafe { CStr::from_ptr(xmem_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "buffer 3 is locked by another holder");
+
+        let status = ffi_call(|| panic!("boom"));
+        assert_eq!(status, XmemStatus::Panic);
+        let message = unsafe { CStr::from_ptr(xmem_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "panic: boom");
+    }
+}
@@ -0,0 +1,102 @@
+//! C API for xmem
+//!
+//! 以不透明指针（[`XmemPool`]、[`XmemBuffer`]）封装 [`BufferPool`] 和
+//! [`BufferGuard`]，供 C/C++ 进程与 Rust、Python 进程共享同一个池。
+//! 构建时由 cbindgen 生成 `include/xmem.h`。
+//!
+//! # 约定
+//!
+//! - 可能失败的函数返回 [`XmemStatus`]，成功为 `XMEM_STATUS_OK`；
+//!   失败时可通过 [`xmem_last_error`] 获取当前线程最近一次错误的描述。
+//! - 结果通过 `out` 指针返回，仅在成功时写入。
+//! - `xmem_*_create` / `open` / `acquire` / `get` 返回的对象分别由
+//!   [`xmem_pool_close`] / [`xmem_buffer_release`] 释放。buffer 内部持有池的映射，
+//!   可以在关闭池之后再释放。
+//! - 同一对象可在多个线程间共享，但不能与释放函数并发调用。
+//!
+//! [`BufferPool`]: xmem_core::BufferPool
+//! [`BufferGuard`]: xmem_core::BufferGuard
+
+mod buffer;
+mod error;
+mod pool;
+
+pub use buffer::*;
+pub use error::*;
+pub use pool::*;
+
+use std::ffi::{c_char, CStr};
+
+/// Maximum number of tensor dimensions
+pub const XMEM_MAX_NDIM: usize = 8;
+
+/// Size of `XmemMeta` label fields, including the terminating NUL
+pub const XMEM_LABEL_CAPACITY: usize = 33;
+
+const _: () = assert!(XMEM_MAX_NDIM == xmem_core::MAX_NDIM);
+const _: () = assert!(XMEM_LABEL_CAPACITY == xmem_core::LABEL_SIZE + 1);
+
+/// Element data types, same values as the shared-memory `dtype` field
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmemDtype {
+    Uint8 = 0,
+    Int8 = 1,
+    Uint16 = 2,
+    Int16 = 3,
+    Uint32 = 4,
+    Int32 = 5,
+    Uint64 = 6,
+    Int64 = 7,
+    Float16 = 8,
+    Float32 = 9,
+    Float64 = 10,
+    Bfloat16 = 11,
+    Bool = 12,
+    Complex64 = 13,
+    Complex128 = 14,
+    Float8E4m3 = 15,
+    Float8E5m2 = 16,
+}
+
+/// Borrow a non-null pointer
+unsafe fn deref<'a, T>(ptr: *const T, what: &str) -> Result<&'a T, FfiError> {
+    ptr.as_ref()
+        .ok_or_else(|| FfiError::invalid(format!("{} is null", what)))
+}
+
+/// Borrow a non-null mutable pointer
+unsafe fn deref_mut<'a, T>(ptr: *mut T, what: &str) -> Result<&'a mut T, FfiError> {
+    ptr.as_mut()
+        .ok_or_else(|| FfiError::invalid(format!("{} is null", what)))
+}
+
+/// Borrow a NUL-terminated UTF-8 string
+unsafe fn c_str<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::invalid(format!("{} is null", what)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FfiError::invalid(format!("{} is not valid UTF-8", what)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xmem_core::DType;
+
+    #[test]
+    fn test_dtype_values() {
+        let pairs = [
+            (XmemDtype::Uint8, DType::UInt8),
+            (XmemDtype::Bfloat16, DType::BFloat16),
+            (XmemDtype::Complex128, DType::Complex128),
+            (XmemDtype::Float8E5m2, DType::Float8E5M2),
+        ];
+        for (ffi, core) in pairs {
+            assert_eq!(ffi as u8, core as u8);
+        }
+        assert_eq!(XmemDtype::Float8E5m2 as usize + 1, DType::ALL.len());
+    }
+}
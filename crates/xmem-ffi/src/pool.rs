@@ -0,0 +1,244 @@
+//! Pool functions
+
+use crate::{c_str, deref, deref_mut, ffi_call, FfiError, XmemBuffer, XmemStatus};
+use std::ffi::c_char;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+use xmem_core::BufferPool;
+
+/// Opaque pool handle
+pub struct XmemPool {
+    inner: BufferPool,
+}
+
+/// Store a new buffer in `out`
+unsafe fn put_buffer(
+    out: *mut *mut XmemBuffer,
+    guard: xmem_core::Result<xmem_core::BufferGuard>,
+) -> Result<(), FfiError> {
+    let out = deref_mut(out, "out")?;
+    *out = Box::into_raw(Box::new(XmemBuffer::new(guard?)));
+    Ok(())
+}
+
+/// Store a new pool in `out`
+unsafe fn put_pool(out: *mut *mut XmemPool, pool: xmem_core::Result<BufferPool>) -> Result<(), FfiError> {
+    let out = deref_mut(out, "out")?;
+    *out = Box::into_raw(Box::new(XmemPool { inner: pool? }));
+    Ok(())
+}
+
+/// Create a pool; `capacity` 0 uses the default
+///
+/// # Safety
+///
+/// `name` must be a NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_create(
+    name: *const c_char,
+    capacity: usize,
+    out: *mut *mut XmemPool,
+) -> XmemStatus {
+    ffi_call(|| {
+        let name = c_str(name, "name")?;
+        let pool = match capacity {
+            0 => BufferPool::create(name),
+            n => BufferPool::create_with_capacity(name, n),
+        };
+        put_pool(out, pool)
+    })
+}
+
+/// Open an existing pool
+///
+/// # Safety
+///
+/// `name` must be a NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_open(name: *const c_char, out: *mut *mut XmemPool) -> XmemStatus {
+    ffi_call(|| {
+        let name = c_str(name, "name")?;
+        put_pool(out, BufferPool::open(name))
+    })
+}
+
+/// Close a pool handle (NULL is ignored)
+///
+/// Buffers acquired from the pool stay valid until released.
+///
+/// # Safety
+///
+/// `pool` must come from `xmem_pool_create` / `xmem_pool_open` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_close(pool: *mut XmemPool) {
+    if !pool.is_null() {
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(pool))));
+    }
+}
+
+/// Allocate a CPU buffer of `size` bytes
+///
+/// # Safety
+///
+/// `pool` and `out` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_acquire_cpu(
+    pool: *const XmemPool,
+    size: usize,
+    out: *mut *mut XmemBuffer,
+) -> XmemStatus {
+    ffi_call(|| {
+        let pool = deref(pool, "pool")?;
+        put_buffer(out, pool.inner.acquire_cpu(size))
+    })
+}
+
+/// Lock wait for `timeout_ms`: negative blocks, 0 fails immediately with `XMEM_STATUS_LOCKED`
+enum LockWait {
+    Block,
+    Try,
+    Timeout(Duration),
+}
+
+impl LockWait {
+    fn from_ms(timeout_ms: i64) -> Self {
+        match u64::try_from(timeout_ms) {
+            Err(_) => LockWait::Block,
+            Ok(0) => LockWait::Try,
+            Ok(ms) => LockWait::Timeout(Duration::from_millis(ms)),
+        }
+    }
+}
+
+/// Get an existing buffer with a shared read lock
+///
+/// `timeout_ms` < 0 waits forever, 0 fails with `XMEM_STATUS_LOCKED` if write-locked,
+/// otherwise fails with `XMEM_STATUS_TIMEOUT` after that many milliseconds.
+///
+/// # Safety
+///
+/// `pool` and `out` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_get(
+    pool: *const XmemPool,
+    meta_index: u32,
+    timeout_ms: i64,
+    out: *mut *mut XmemBuffer,
+) -> XmemStatus {
+    ffi_call(|| {
+        let pool = &deref(pool, "pool")?.inner;
+        let guard = match LockWait::from_ms(timeout_ms) {
+            LockWait::Block => pool.get(meta_index),
+            LockWait::Try => pool.try_get(meta_index),
+            LockWait::Timeout(t) => pool.get_timeout(meta_index, t),
+        };
+        put_buffer(out, guard)
+    })
+}
+
+/// Get an existing buffer with an exclusive write lock
+///
+/// `timeout_ms` as for `xmem_pool_get`.
+///
+/// # Safety
+///
+/// `pool` and `out` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_get_mut(
+    pool: *const XmemPool,
+    meta_index: u32,
+    timeout_ms: i64,
+    out: *mut *mut XmemBuffer,
+) -> XmemStatus {
+    ffi_call(|| {
+        let pool = &deref(pool, "pool")?.inner;
+        let guard = match LockWait::from_ms(timeout_ms) {
+            LockWait::Block => pool.get_mut(meta_index),
+            LockWait::Try => pool.try_get_mut(meta_index),
+            LockWait::Timeout(t) => pool.get_mut_timeout(meta_index, t),
+        };
+        put_buffer(out, guard)
+    })
+}
+
+/// Set the reference count of a buffer
+///
+/// # Safety
+///
+/// `pool` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_set_ref_count(
+    pool: *const XmemPool,
+    meta_index: u32,
+    count: i32,
+) -> XmemStatus {
+    ffi_call(|| {
+        let pool = deref(pool, "pool")?;
+        Ok(pool.inner.set_ref_count(meta_index, count)?)
+    })
+}
+
+/// Add a reference, storing the new count in `out_count` (may be NULL)
+///
+/// # Safety
+///
+/// `pool` must be a valid pointer, `out_count` valid or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_add_ref(
+    pool: *const XmemPool,
+    meta_index: u32,
+    out_count: *mut i32,
+) -> XmemStatus {
+    ffi_call(|| {
+        let count = deref(pool, "pool")?.inner.add_ref(meta_index)?;
+        if let Some(out) = out_count.as_mut() {
+            *out = count;
+        }
+        Ok(())
+    })
+}
+
+/// Drop a reference, freeing the buffer when none are left
+///
+/// Gives back a reference taken with `xmem_pool_add_ref` or left by
+/// `xmem_buffer_forget`. The new count is stored in `out_count` (may be NULL).
+///
+/// # Safety
+///
+/// `pool` must be a valid pointer, `out_count` valid or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_release(
+    pool: *const XmemPool,
+    meta_index: u32,
+    out_count: *mut i32,
+) -> XmemStatus {
+    ffi_call(|| {
+        let pool = &deref(pool, "pool")?.inner;
+        let count = pool.release(meta_index)?;
+        if count <= 0 {
+            pool.release_buffer(meta_index)?;
+        }
+        if let Some(out) = out_count.as_mut() {
+            *out = count;
+        }
+        Ok(())
+    })
+}
+
+/// Get the reference count of a buffer
+///
+/// # Safety
+///
+/// `pool` and `out_count` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn xmem_pool_ref_count(
+    pool: *const XmemPool,
+    meta_index: u32,
+    out_count: *mut i32,
+) -> XmemStatus {
+    ffi_call(|| {
+        let count = deref(pool, "pool")?.inner.ref_count(meta_index)?;
+        *deref_mut(out_count, "out_count")? = count;
+        Ok(())
+    })
+}
@@ -0,0 +1,124 @@
+/* C API smoke test, built and run by tests/c_api.rs */
+
+#define _POSIX_C_SOURCE 200809L
+
+#include <assert.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#include "xmem.h"
+#include "xmem_layout.h"
+
+#define CHECK(call)                                                          \
+    do {                                                                     \
+        XmemStatus status_ = (call);                                         \
+        if (status_ != XMEM_STATUS_OK) {                                     \
+            fprintf(stderr, "%s:%d: %s failed (%d): %s\n", __FILE__,         \
+                    __LINE__, #call, (int)status_, xmem_last_error());       \
+            return 1;                                                        \
+        }                                                                    \
+    } while (0)
+
+/* Read a slot straight from the metadata segment, as a non-Rust reader would */
+static void check_raw_meta(const char *pool_name, uint32_t index) {
+    char meta_name[256];
+    snprintf(meta_name, sizeof(meta_name), "%s_meta", pool_name);
+    int fd = shm_open(meta_name, O_RDONLY, 0);
+    assert(fd >= 0);
+    struct stat st;
+    assert(fstat(fd, &st) == 0);
+    const uint8_t *base = mmap(NULL, (size_t)st.st_size, PROT_READ, MAP_SHARED, fd, 0);
+    assert(base != MAP_FAILED);
+    close(fd);
+
+    const xmem_region_header_t *header = (const xmem_region_header_t *)base;
+    assert(header->magic == XMEM_MAGIC);
+    assert(header->version == XMEM_LAYOUT_VERSION);
+    assert(index < header->capacity);
+
+    const xmem_buffer_meta_t *meta = (const xmem_buffer_meta_t *)(base + sizeof(*header)) + index;
+    assert(__atomic_load_n(&meta->size, __ATOMIC_SEQ_CST) == 24);
+    assert(__atomic_load_n(&meta->ref_count, __ATOMIC_SEQ_CST) == 1);
+    assert(meta->storage_type == XMEM_STORAGE_CPU);
+    assert(meta->dtype == XMEM_DTYPE_FLOAT32);
+    assert(meta->ndim == 2 && meta->shape[1] == 3);
+    assert(meta->seq == 42);
+    assert(strncmp(meta->content_type, "tensor", XMEM_LABEL_SIZE) == 0);
+
+    munmap((void *)base, (size_t)st.st_size);
+}
+
+int main(int argc, char **argv) {
+    const char *name = argc > 1 ? argv[1] : "/xmem_c_test";
+    XmemPool *pool = NULL;
+    XmemBuffer *buf = NULL;
+    XmemBuffer *reader = NULL;
+    void *data = NULL;
+    const void *rdata = NULL;
+    size_t size = 0;
+    int32_t count = 0;
+    XmemMeta meta;
+
+    CHECK(xmem_pool_create(name, 16, &pool));
+
+    /* Write a tensor with metadata */
+    CHECK(xmem_pool_acquire_cpu(pool, 24, &buf));
+    CHECK(xmem_buffer_data_mut(buf, &data, &size));
+    assert(size == 24);
+    memcpy(data, "hello from c", 12);
+
+    size_t shape[2] = {2, 3};
+    CHECK(xmem_buffer_set_tensor(buf, shape, 2, XMEM_DTYPE_FLOAT32));
+    CHECK(xmem_buffer_set_content_type(buf, "tensor"));
+    CHECK(xmem_buffer_set_producer(buf, "c-test"));
+    CHECK(xmem_buffer_set_seq(buf, 42));
+
+    uint32_t index = xmem_buffer_index(buf);
+    CHECK(xmem_pool_ref_count(pool, index, &count));
+    assert(count == 1);
+    check_raw_meta(name, index);
+
+    /* Hand the reference over, then read it back like another process would */
+    xmem_buffer_forget(buf);
+    CHECK(xmem_pool_get(pool, index, -1, &reader));
+    CHECK(xmem_buffer_data(reader, &rdata, &size));
+    assert(memcmp(rdata, "hello from c", 12) == 0);
+
+    CHECK(xmem_buffer_get_meta(reader, &meta));
+    assert(meta.ndim == 2);
+    assert(meta.shape[0] == 2 && meta.shape[1] == 3);
+    assert(meta.strides[0] == 12 && meta.strides[1] == 4);
+    assert(meta.dtype == XMEM_DTYPE_FLOAT32);
+    assert(meta.size == 24);
+    assert(meta.seq == 42);
+    assert(strcmp(meta.content_type, "tensor") == 0);
+    assert(strcmp(meta.producer, "c-test") == 0);
+
+    /* Errors come back as status codes with a message */
+    assert(xmem_buffer_data_mut(reader, &data, &size) == XMEM_STATUS_READ_ONLY);
+    assert(strstr(xmem_last_error(), "read-only") != NULL);
+    assert(xmem_pool_get_mut(pool, index, 0, &buf) == XMEM_STATUS_LOCKED);
+    assert(xmem_buffer_set_tensor(reader, shape, 2, 99) == XMEM_STATUS_READ_ONLY);
+    assert(xmem_pool_open(NULL, &pool) == XMEM_STATUS_INVALID_ARGUMENT);
+
+    /* The reader took its own reference; the forgotten one is given back explicitly */
+    CHECK(xmem_pool_ref_count(pool, index, &count));
+    assert(count == 2);
+    xmem_buffer_release(reader);
+    CHECK(xmem_pool_add_ref(pool, index, &count));
+    assert(count == 2);
+    CHECK(xmem_pool_release(pool, index, &count));
+    assert(count == 1);
+    CHECK(xmem_pool_release(pool, index, &count));
+    assert(count == 0);
+    /* The data segment is gone once the last reference is dropped */
+    assert(xmem_pool_get(pool, index, 0, &reader) != XMEM_STATUS_OK);
+
+    xmem_pool_close(pool);
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,56 @@
+//! Build and run the C test program against the shared library
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn unique_name() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("/xmem_ffi_test_{}", ts)
+}
+
+#[test]
+fn test_c_program() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // target/<profile>/deps/c_api-* -> target/<profile>, where the cdylib is built
+    let lib_dir = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(|p| p.parent())
+        .unwrap()
+        .to_path_buf();
+    let exe = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("test_xmem_c");
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .arg("-std=c11")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg("-I")
+        .arg(crate_dir.join("../xmem-core/include"))
+        .arg(crate_dir.join("tests/c/test_xmem.c"))
+        .arg("-o")
+        .arg(&exe)
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-lxmem_ffi")
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling the C test failed");
+
+    let output = Command::new(&exe).arg(unique_name()).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C test failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
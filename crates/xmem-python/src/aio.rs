@@ -0,0 +1,195 @@
+//! asyncio 支持
+//!
+//! 将 core [`Notifier`] 的 fd 通过 `loop.add_reader` 注册到正在运行的事件循环，
+//! 等待期间不占用事件循环线程。Linux 上任何进程的释放/发布都会立即唤醒等待方
+//! （core 的后台线程在共享 futex 上等待并转发）；其他平台该 fd 只被本进程触发，
+//! 因此同时每隔 [`POLL_INTERVAL`] 重试一次，其他进程的事件最多延迟一个间隔。
+
+use crate::{to_py_err, AcquireTimeoutError, BufferGuard};
+use pyo3::prelude::*;
+use std::os::fd::AsRawFd;
+use std::time::Duration;
+use xmem_core::notify::POLL_INTERVAL;
+use xmem_core::{BufferGuard as CoreGuard, BufferPool as CorePool, Notifier};
+
+/// Operation retried until it yields a buffer
+pub enum AsyncOp {
+    /// Acquire a CPU buffer of the given size
+    Acquire(usize),
+    /// Receive a published buffer
+    Receive,
+}
+
+impl AsyncOp {
+    fn attempt(&self, pool: &CorePool) -> xmem_core::Result<Option<CoreGuard>> {
+        match self {
+            AsyncOp::Acquire(size) => pool.try_acquire_cpu(*size),
+            AsyncOp::Receive => pool.try_receive(),
+        }
+    }
+}
+
+/// Start an async wait, returning an awaitable future
+pub fn start(
+    py: Python<'_>,
+    pool: &CorePool,
+    op: AsyncOp,
+    timeout: Option<Duration>,
+) -> PyResult<PyObject> {
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let future: PyObject = event_loop.call_method0("create_future")?.into();
+
+    // Subscribe before the first attempt so no wake-up is missed
+    let notifier = pool.notifier().map_err(to_py_err)?;
+    let wait = Py::new(
+        py,
+        AsyncWait {
+            pool: pool.clone(),
+            op,
+            notifier: None,
+            event_loop: event_loop.into(),
+            future: future.clone_ref(py),
+            wake: None,
+            poll_handle: None,
+            deadline_handle: None,
+        },
+    )?;
+    if wait.borrow_mut(py).attempt(py)? {
+        return Ok(future);
+    }
+
+    let wake = wait.getattr(py, "_wake")?;
+    event_loop.call_method1("add_reader", (notifier.as_raw_fd(), wake.clone_ref(py)))?;
+    future.call_method1(py, "add_done_callback", (wait.getattr(py, "_finish")?,))?;
+    let deadline_handle = match timeout {
+        Some(t) => Some(event_loop.call_method1(
+            "call_later",
+            (t.as_secs_f64(), wait.getattr(py, "_expire")?),
+        )?),
+        None => None,
+    };
+
+    let mut this = wait.borrow_mut(py);
+    this.notifier = Some(notifier);
+    this.wake = Some(wake);
+    this.deadline_handle = deadline_handle.map(Into::into);
+    this.schedule_poll(py)?;
+    Ok(future)
+}
+
+/// Pending wait driven by the event loop
+///
+/// 注册的回调持有自身引用，完成、超时或 future 被取消时统一在
+/// `_finish` 中注销，打破引用环。回调均在事件循环所在线程中执行。
+#[pyclass(module = "xmem")]
+pub struct AsyncWait {
+    pool: CorePool,
+    op: AsyncOp,
+    /// Registered with the loop while waiting
+    notifier: Option<Notifier>,
+    event_loop: PyObject,
+    future: PyObject,
+    /// Bound `_wake`, used for the reader and the poll timer
+    wake: Option<PyObject>,
+    poll_handle: Option<PyObject>,
+    deadline_handle: Option<PyObject>,
+}
+
+impl AsyncWait {
+    /// Try the operation once, completing the future on success or error
+    fn attempt(&mut self, py: Python<'_>) -> PyResult<bool> {
+        let future = self.future.as_ref(py);
+        if future.call_method0("done")?.is_true()? {
+            return Ok(true);
+        }
+        if let Some(notifier) = &self.notifier {
+            notifier.drain();
+        }
+        match self.op.attempt(&self.pool) {
+            Ok(None) => return Ok(false),
+            Ok(Some(guard)) => {
+                future.call_method1("set_result", (BufferGuard::new(guard),))?;
+            }
+            Err(e) => {
+                future.call_method1("set_exception", (to_py_err(e).value(py),))?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Re-arm the fallback timer for events from other processes (not needed on Linux)
+    fn schedule_poll(&mut self, py: Python<'_>) -> PyResult<()> {
+        if cfg!(target_os = "linux") {
+            return Ok(());
+        }
+        if let Some(handle) = self.poll_handle.take() {
+            handle.call_method0(py, "cancel")?;
+        }
+        if let Some(wake) = &self.wake {
+            let handle = self.event_loop.call_method1(
+                py,
+                "call_later",
+                (POLL_INTERVAL.as_secs_f64(), wake.clone_ref(py)),
+            )?;
+            self.poll_handle = Some(handle);
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl AsyncWait {
+    /// Reader / timer callback: retry the operation
+    fn _wake(&mut self, py: Python<'_>) -> PyResult<()> {
+        if self.attempt(py)? {
+            self._finish(py, None)
+        } else {
+            self.schedule_poll(py)
+        }
+    }
+
+    /// Deadline callback
+    fn _expire(&mut self, py: Python<'_>) -> PyResult<()> {
+        let future = self.future.as_ref(py);
+        if !future.call_method0("done")?.is_true()? {
+            let err = AcquireTimeoutError::new_err("operation timed out");
+            future.call_method1("set_exception", (err.value(py),))?;
+        }
+        self._finish(py, None)
+    }
+
+    /// Unregister all callbacks (also the future's done callback)
+    #[pyo3(signature = (_future=None))]
+    fn _finish(&mut self, py: Python<'_>, _future: Option<&PyAny>) -> PyResult<()> {
+        if let Some(notifier) = self.notifier.take() {
+            self.event_loop
+                .call_method1(py, "remove_reader", (notifier.as_raw_fd(),))?;
+        }
+        for handle in [self.poll_handle.take(), self.deadline_handle.take()]
+            .into_iter()
+            .flatten()
+        {
+            handle.call_method0(py, "cancel")?;
+        }
+        self.wake = None;
+        Ok(())
+    }
+}
+
+/// Async iterator over published buffers, see `BufferPool.incoming()`
+#[pyclass(module = "xmem")]
+pub struct Incoming {
+    pub pool: CorePool,
+}
+
+#[pymethods]
+impl Incoming {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Never exhausted: each step awaits the next published buffer
+    fn __anext__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        start(py, &self.pool, AsyncOp::Receive, None).map(Some)
+    }
+}
@@ -0,0 +1,435 @@
+//! DLPack 导出/导入
+//!
+//! 实现 DLPack 1.0 的版本化 capsule（`"dltensor_versioned"`）和 0.8 的
+//! 非版本化 capsule（`"dltensor"`），供 `torch.from_dlpack`、`jax.dlpack`、
+//! `cupy.from_dlpack` 零拷贝使用。导出的张量持有 buffer 的一个独立引用，
+//! 由消费方调用 deleter 释放。
+//!
+//! 只读 guard 只能以版本化 capsule 导出并带上 `DLPACK_FLAG_BITMASK_READ_ONLY`：
+//! 非版本化格式无法表达只读，消费方会把数据当作可写。
+
+use pyo3::exceptions::{PyBufferError, PyTypeError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+use xmem_core::{AccessMode, BufferGuard as CoreGuard, DType, TensorLayout};
+
+const CAPSULE_NAME: &CStr = c"dltensor";
+const USED_CAPSULE_NAME: &CStr = c"used_dltensor";
+const VERSIONED_CAPSULE_NAME: &CStr = c"dltensor_versioned";
+const USED_VERSIONED_CAPSULE_NAME: &CStr = c"used_dltensor_versioned";
+
+/// DLPack version produced by versioned exports
+const DLPACK_VERSION: DLPackVersion = DLPackVersion { major: 1, minor: 0 };
+/// `DLPACK_FLAG_BITMASK_READ_ONLY`
+const FLAG_READ_ONLY: u64 = 1 << 0;
+
+/// `DLDeviceType::kDLCPU`
+pub const DEVICE_CPU: i32 = 1;
+/// `DLDeviceType::kDLCUDA`
+#[cfg(feature = "cuda")]
+pub const DEVICE_CUDA: i32 = 2;
+
+// DLDataTypeCode
+const CODE_INT: u8 = 0;
+const CODE_UINT: u8 = 1;
+const CODE_FLOAT: u8 = 2;
+const CODE_BFLOAT: u8 = 4;
+const CODE_COMPLEX: u8 = 5;
+const CODE_BOOL: u8 = 6;
+const CODE_FLOAT8_E4M3FN: u8 = 10;
+const CODE_FLOAT8_E5M2: u8 = 12;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DLDevice {
+    device_type: i32,
+    device_id: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DLDataType {
+    code: u8,
+    bits: u8,
+    lanes: u16,
+}
+
+#[repr(C)]
+struct DLTensor {
+    data: *mut c_void,
+    device: DLDevice,
+    ndim: i32,
+    dtype: DLDataType,
+    shape: *mut i64,
+    /// Strides in elements, null means C-contiguous
+    strides: *mut i64,
+    byte_offset: u64,
+}
+
+#[repr(C)]
+struct DLManagedTensor {
+    dl_tensor: DLTensor,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DLPackVersion {
+    major: u32,
+    minor: u32,
+}
+
+#[repr(C)]
+struct DLManagedTensorVersioned {
+    version: DLPackVersion,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensorVersioned)>,
+    flags: u64,
+    dl_tensor: DLTensor,
+}
+
+/// Owner of an exported tensor: its own buffer reference plus shape storage
+struct ExportCtx {
+    _guard: CoreGuard,
+    shape: Vec<i64>,
+    strides: Vec<i64>,
+}
+
+fn to_dl_dtype(dtype: DType) -> DLDataType {
+    let code = match dtype {
+        DType::UInt8 | DType::UInt16 | DType::UInt32 | DType::UInt64 => CODE_UINT,
+        DType::Int8 | DType::Int16 | DType::Int32 | DType::Int64 => CODE_INT,
+        DType::Float16 | DType::Float32 | DType::Float64 => CODE_FLOAT,
+        DType::BFloat16 => CODE_BFLOAT,
+        DType::Complex64 | DType::Complex128 => CODE_COMPLEX,
+        DType::Bool => CODE_BOOL,
+        DType::Float8E4M3 => CODE_FLOAT8_E4M3FN,
+        DType::Float8E5M2 => CODE_FLOAT8_E5M2,
+    };
+    DLDataType {
+        code,
+        bits: (dtype.size() * 8) as u8,
+        lanes: 1,
+    }
+}
+
+fn from_dl_dtype(dtype: DLDataType) -> Option<DType> {
+    if dtype.lanes != 1 {
+        return None;
+    }
+    DType::ALL
+        .into_iter()
+        .find(|d| to_dl_dtype(*d).code == dtype.code && d.size() * 8 == dtype.bits as usize)
+}
+
+unsafe extern "C" fn managed_deleter(managed: *mut DLManagedTensor) {
+    if managed.is_null() {
+        return;
+    }
+    let managed = Box::from_raw(managed);
+    // Dropping the guard releases the exported buffer reference (no GIL needed)
+    drop(Box::from_raw(managed.manager_ctx as *mut ExportCtx));
+}
+
+unsafe extern "C" fn versioned_deleter(managed: *mut DLManagedTensorVersioned) {
+    if managed.is_null() {
+        return;
+    }
+    let managed = Box::from_raw(managed);
+    drop(Box::from_raw(managed.manager_ctx as *mut ExportCtx));
+}
+
+/// Capsule destructor: only frees tensors that were never consumed
+unsafe extern "C" fn capsule_destructor(capsule: *mut ffi::PyObject) {
+    if ffi::PyCapsule_IsValid(capsule, CAPSULE_NAME.as_ptr()) == 1 {
+        let managed = ffi::PyCapsule_GetPointer(capsule, CAPSULE_NAME.as_ptr());
+        managed_deleter(managed as *mut DLManagedTensor);
+    }
+}
+
+unsafe extern "C" fn versioned_capsule_destructor(capsule: *mut ffi::PyObject) {
+    if ffi::PyCapsule_IsValid(capsule, VERSIONED_CAPSULE_NAME.as_ptr()) == 1 {
+        let managed = ffi::PyCapsule_GetPointer(capsule, VERSIONED_CAPSULE_NAME.as_ptr());
+        versioned_deleter(managed as *mut DLManagedTensorVersioned);
+    }
+}
+
+/// Export a guard as a DLPack capsule
+///
+/// `guard` 为导出专用的引用（见 [`CoreGuard::try_clone`]），随张量一起释放。
+/// `max_version` 为消费方支持的最高版本：不低于 1.0 时导出版本化 capsule，
+/// 否则导出非版本化 capsule，此时拒绝导出只读 guard。
+pub fn export(
+    py: Python<'_>,
+    guard: CoreGuard,
+    device: (i32, i32),
+    max_version: Option<(u32, u32)>,
+) -> PyResult<PyObject> {
+    let to_err = |e: xmem_core::Error| PyBufferError::new_err(e.to_string());
+    let versioned = max_version.is_some_and(|(major, _)| major >= DLPACK_VERSION.major);
+    let read_only = guard.mode() == AccessMode::ReadOnly;
+    if read_only && !versioned {
+        return Err(PyBufferError::new_err(
+            "read-only buffers can only be exported to DLPack 1.0 consumers (max_version >= (1, 0))",
+        ));
+    }
+    let meta = guard.meta().map_err(to_err)?;
+    let size = guard.size().map_err(to_err)?;
+
+    // Shape and strides come from shared memory: validate them against the buffer
+    let (dtype, shape, strides) = match meta.dtype() {
+        Some(_) if !meta.shape().is_empty() => {
+            let layout = TensorLayout::from_meta(meta, size, false).map_err(to_err)?;
+            // Both fit in i64: checked against isize::MAX
+            let to_i64 = |v: Vec<usize>| v.into_iter().map(|x| x as i64).collect::<Vec<_>>();
+            (layout.dtype, to_i64(layout.shape), to_i64(layout.strides))
+        }
+        _ => (DType::UInt8, vec![size as i64], vec![1]),
+    };
+
+    let data = match device.0 {
+        DEVICE_CPU => guard.as_cpu_slice().map_err(to_err)?.as_ptr() as *mut c_void,
+        #[cfg(feature = "cuda")]
+        DEVICE_CUDA => guard.as_cuda_ptr().map_err(to_err)? as *mut c_void,
+        _ => return Err(PyBufferError::new_err("unsupported device")),
+    };
+
+    let mut ctx = Box::new(ExportCtx {
+        _guard: guard,
+        shape,
+        strides,
+    });
+    let dl_tensor = DLTensor {
+        data,
+        device: DLDevice {
+            device_type: device.0,
+            device_id: device.1,
+        },
+        ndim: ctx.shape.len() as i32,
+        dtype: to_dl_dtype(dtype),
+        shape: ctx.shape.as_mut_ptr(),
+        strides: ctx.strides.as_mut_ptr(),
+        byte_offset: 0,
+    };
+    let manager_ctx = Box::into_raw(ctx) as *mut c_void;
+
+    unsafe {
+        let capsule = if versioned {
+            let managed = Box::into_raw(Box::new(DLManagedTensorVersioned {
+                version: DLPACK_VERSION,
+                manager_ctx,
+                deleter: Some(versioned_deleter),
+                flags: if read_only { FLAG_READ_ONLY } else { 0 },
+                dl_tensor,
+            }));
+            let capsule = ffi::PyCapsule_New(
+                managed as *mut c_void,
+                VERSIONED_CAPSULE_NAME.as_ptr(),
+                Some(versioned_capsule_destructor),
+            );
+            if capsule.is_null() {
+                versioned_deleter(managed);
+            }
+            capsule
+        } else {
+            let managed = Box::into_raw(Box::new(DLManagedTensor {
+                dl_tensor,
+                manager_ctx,
+                deleter: Some(managed_deleter),
+            }));
+            let capsule = ffi::PyCapsule_New(
+                managed as *mut c_void,
+                CAPSULE_NAME.as_ptr(),
+                Some(capsule_destructor),
+            );
+            if capsule.is_null() {
+                managed_deleter(managed);
+            }
+            capsule
+        };
+        if capsule.is_null() {
+            return Err(PyErr::fetch(py));
+        }
+        Ok(PyObject::from_owned_ptr(py, capsule))
+    }
+}
+
+/// Consumed managed tensor of either capsule kind
+#[derive(Clone, Copy)]
+enum Managed {
+    Unversioned(*mut DLManagedTensor),
+    Versioned(*mut DLManagedTensorVersioned),
+}
+
+/// Imported CPU tensor, consumed on drop
+pub struct Imported {
+    managed: Managed,
+    pub dtype: DType,
+    pub shape: Vec<usize>,
+}
+
+impl Imported {
+    /// Consume a capsule from `obj.__dlpack__()`
+    ///
+    /// 仅支持 CPU 张量。优先请求版本化 capsule，生产者不支持 `max_version`
+    /// 时退回非版本化 capsule。
+    pub fn from_object(obj: &PyAny) -> PyResult<Self> {
+        let device: (i32, i32) = obj.call_method0("__dlpack_device__")?.extract()?;
+        if device.0 != DEVICE_CPU {
+            return Err(PyValueError::new_err(format!(
+                "from_dlpack only supports CPU tensors, got device type {}",
+                device.0
+            )));
+        }
+
+        let py = obj.py();
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("max_version", (DLPACK_VERSION.major, DLPACK_VERSION.minor))?;
+        let capsule = match obj.call_method("__dlpack__", (), Some(kwargs)) {
+            Err(e) if e.is_instance_of::<PyTypeError>(py) => obj.call_method0("__dlpack__")?,
+            result => result?,
+        };
+        let managed = unsafe {
+            if ffi::PyCapsule_IsValid(capsule.as_ptr(), VERSIONED_CAPSULE_NAME.as_ptr()) == 1 {
+                Managed::Versioned(take_capsule(
+                    capsule,
+                    VERSIONED_CAPSULE_NAME,
+                    USED_VERSIONED_CAPSULE_NAME,
+                )? as *mut DLManagedTensorVersioned)
+            } else {
+                Managed::Unversioned(
+                    take_capsule(capsule, CAPSULE_NAME, USED_CAPSULE_NAME)? as *mut DLManagedTensor,
+                )
+            }
+        };
+
+        // From here on, drop calls the producer's deleter
+        let mut imported = Self {
+            managed,
+            dtype: DType::UInt8,
+            shape: Vec::new(),
+        };
+        if let Managed::Versioned(managed) = imported.managed {
+            // Only the header layout is stable across major versions
+            let version = unsafe { (*managed).version };
+            if version.major > DLPACK_VERSION.major {
+                return Err(PyValueError::new_err(format!(
+                    "unsupported DLPack version {}.{}",
+                    version.major, version.minor
+                )));
+            }
+        }
+        let tensor = imported.tensor();
+        imported.dtype = from_dl_dtype(tensor.dtype).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "unsupported DLPack dtype (code {}, bits {}, lanes {})",
+                tensor.dtype.code, tensor.dtype.bits, tensor.dtype.lanes
+            ))
+        })?;
+        imported.shape = imported
+            .dims()
+            .iter()
+            .map(|&d| usize::try_from(d).map_err(|_| PyValueError::new_err("negative dimension")))
+            .collect::<PyResult<_>>()?;
+        Ok(imported)
+    }
+
+    fn tensor(&self) -> &DLTensor {
+        match self.managed {
+            Managed::Unversioned(m) => unsafe { &(*m).dl_tensor },
+            Managed::Versioned(m) => unsafe { &(*m).dl_tensor },
+        }
+    }
+
+    fn dims(&self) -> &[i64] {
+        let tensor = self.tensor();
+        match tensor.ndim {
+            0 => &[],
+            n => unsafe { std::slice::from_raw_parts(tensor.shape, n as usize) },
+        }
+    }
+
+    /// Element strides, computing C-contiguous strides when absent
+    fn strides(&self) -> Vec<i64> {
+        let tensor = self.tensor();
+        if !tensor.strides.is_null() && tensor.ndim > 0 {
+            return unsafe { std::slice::from_raw_parts(tensor.strides, tensor.ndim as usize) }
+                .to_vec();
+        }
+        let mut strides = vec![1i64; self.shape.len()];
+        for i in (0..self.shape.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * self.shape[i + 1] as i64;
+        }
+        strides
+    }
+
+    /// Copy the tensor into `dst` in C-contiguous order
+    pub fn copy_to(&self, dst: &mut [u8]) {
+        let tensor = self.tensor();
+        let item = self.dtype.size();
+        let count: usize = self.shape.iter().product();
+        debug_assert_eq!(dst.len(), count * item);
+        if count == 0 {
+            return;
+        }
+
+        let base = unsafe { (tensor.data as *const u8).add(tensor.byte_offset as usize) };
+        let strides = self.strides();
+        let mut index = vec![0usize; self.shape.len()];
+        for chunk in dst.chunks_exact_mut(item) {
+            let offset: i64 = index
+                .iter()
+                .zip(&strides)
+                .map(|(&i, &s)| i as i64 * s)
+                .sum();
+            let src = unsafe { base.offset(offset as isize * item as isize) };
+            unsafe { std::ptr::copy_nonoverlapping(src, chunk.as_mut_ptr(), item) };
+
+            // Advance the multi-dimensional index in C order
+            for axis in (0..index.len()).rev() {
+                index[axis] += 1;
+                if index[axis] < self.shape[axis] {
+                    break;
+                }
+                index[axis] = 0;
+            }
+        }
+    }
+}
+
+/// Take ownership of a capsule's tensor by renaming it to `used_name`
+///
+/// 改名后 capsule 的析构函数不再释放张量。
+unsafe fn take_capsule(capsule: &PyAny, name: &CStr, used_name: &CStr) -> PyResult<*mut c_void> {
+    let managed = ffi::PyCapsule_GetPointer(capsule.as_ptr(), name.as_ptr());
+    if managed.is_null() {
+        return Err(PyErr::fetch(capsule.py()));
+    }
+    if ffi::PyCapsule_SetName(capsule.as_ptr(), used_name.as_ptr() as *const c_char) != 0 {
+        return Err(PyErr::fetch(capsule.py()));
+    }
+    Ok(managed)
+}
+
+impl Drop for Imported {
+    fn drop(&mut self) {
+        unsafe {
+            match self.managed {
+                Managed::Unversioned(m) => {
+                    if let Some(deleter) = (*m).deleter {
+                        deleter(m);
+                    }
+                }
+                Managed::Versioned(m) => {
+                    if let Some(deleter) = (*m).deleter {
+                        deleter(m);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,57 +1,443 @@
 //! Python bindings for xmem
 
+// pyo3 0.20 的 #[pymethods] 宏展开会触发该 lint
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
-use pyo3::exceptions::PyRuntimeError;
-use std::sync::Arc;
-use xmem_core::{BufferPool as CorePool, AccessMode};
+use pyo3::create_exception;
+use pyo3::exceptions::{
+    PyBufferError, PyMemoryError, PyRuntimeError, PyTimeoutError, PyValueError,
+};
+use pyo3::ffi;
+use pyo3::types::{PyBytes, PyDict, PyTuple, PyType};
+use std::os::raw::c_int;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use xmem_core::{
+    BufferPool as CorePool, BufferGuard as CoreGuard, AccessMode, BufferHandle, DType, HugePages,
+    PoolConfig, StorageType, LABEL_SIZE,
+};
+
+mod aio;
+mod dlpack;
+mod meta;
+
+create_exception!(
+    xmem,
+    AcquireTimeoutError,
+    PyTimeoutError,
+    "Timed out waiting for a buffer or buffer lock."
+);
+
+create_exception!(
+    xmem,
+    ReadOnlyBufferError,
+    PyRuntimeError,
+    "Write attempted through a read-only buffer guard."
+);
+
+/// Copies at least this large release the GIL
+const GIL_RELEASE_THRESHOLD: usize = 64 * 1024;
+
+/// Longest stretch spent without the GIL before checking for signals
+const WAIT_SLICE: Duration = Duration::from_millis(100);
 
 /// Convert xmem error to Python exception
 fn to_py_err(e: xmem_core::Error) -> PyErr {
-    PyRuntimeError::new_err(e.to_string())
+    match e {
+        xmem_core::Error::Timeout => AcquireTimeoutError::new_err(e.to_string()),
+        xmem_core::Error::ReadOnly => ReadOnlyBufferError::new_err(e.to_string()),
+        xmem_core::Error::InvalidName { .. } => PyValueError::new_err(e.to_string()),
+        xmem_core::Error::OutOfShmSpace { .. } => PyMemoryError::new_err(e.to_string()),
+        _ => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+/// Huge page mode names used by the Python API
+const HUGE_PAGE_MODES: [(&str, HugePages); 3] = [
+    ("off", HugePages::Off),
+    ("transparent", HugePages::Transparent),
+    ("hugetlb", HugePages::HugeTlb),
+];
+
+fn to_huge_pages(name: &str) -> PyResult<HugePages> {
+    HUGE_PAGE_MODES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, mode)| *mode)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown huge page mode: {:?}", name)))
+}
+
+/// Build a pool configuration from constructor keywords
+fn pool_config(namespace: Option<&str>, backing_dir: Option<std::path::PathBuf>) -> PoolConfig {
+    let mut config = PoolConfig::new();
+    if let Some(namespace) = namespace {
+        config = config.namespace(namespace);
+    }
+    if let Some(dir) = backing_dir {
+        config = config.backing_dir(dir);
+    }
+    config
+}
+
+/// Apply the segment cache settings shared by `BufferPool()` and `BufferPool.open()`
+fn cache_config(
+    config: PoolConfig,
+    cache_segments: bool,
+    trim_idle_after: Option<f64>,
+    max_cached_bytes: Option<u64>,
+) -> PyResult<PoolConfig> {
+    let mut config = config.cache_segments(cache_segments);
+    if let Some(seconds) = trim_idle_after {
+        let idle = Duration::try_from_secs_f64(seconds).map_err(|_| {
+            PyValueError::new_err("trim_idle_after must be a non-negative number")
+        })?;
+        config = config.trim_idle_after(idle);
+    }
+    if let Some(bytes) = max_cached_bytes {
+        config = config.max_cached_bytes(bytes);
+    }
+    Ok(config)
+}
+
+/// Convert a Python timeout in seconds
+fn to_duration(seconds: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| PyValueError::new_err("timeout must be a non-negative number"))
+}
+
+/// Run a blocking core wait with the GIL released
+///
+/// `wait(slice)` 最多等待 `slice`，超时返回 [`xmem_core::Error::Timeout`]。
+/// 每段等待之间重新获取 GIL 检查信号，使 Ctrl-C 能中断无限等待。
+fn wait_without_gil<T: Send>(
+    py: Python<'_>,
+    timeout: Option<Duration>,
+    wait: impl Fn(Duration) -> xmem_core::Result<T> + Sync,
+) -> PyResult<T> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    loop {
+        let slice = match deadline {
+            Some(d) => d.saturating_duration_since(Instant::now()).min(WAIT_SLICE),
+            None => WAIT_SLICE,
+        };
+        match py.allow_threads(|| wait(slice)) {
+            Err(xmem_core::Error::Timeout) if deadline.is_none_or(|d| Instant::now() < d) => {
+                py.check_signals()?;
+            }
+            res => return res.map_err(to_py_err),
+        }
+    }
+}
+
+/// Convert a dtype-like object (str, numpy type or `numpy.dtype`) to [`DType`]
+fn extract_dtype(obj: &PyAny) -> PyResult<DType> {
+    let name = if let Ok(s) = obj.extract::<&str>() {
+        s.to_string()
+    } else if let Ok(ty) = obj.downcast::<PyType>() {
+        ty.name()?.to_string()
+    } else {
+        obj.getattr("name")?.extract::<String>()?
+    };
+    name.parse().map_err(|e: xmem_core::Error| PyValueError::new_err(e.to_string()))
+}
+
+/// Get the numpy dtype object for a [`DType`]
+///
+/// bfloat16 / float8 需要 `ml_dtypes` 提供的扩展类型。
+fn numpy_dtype(py: Python<'_>, dtype: DType) -> PyResult<&PyAny> {
+    match dtype {
+        DType::BFloat16 | DType::Float8E4M3 | DType::Float8E5M2 => {
+            py.import("ml_dtypes")?.getattr(dtype.name())
+        }
+        _ => py.import("numpy")?.getattr("dtype")?.call1((dtype.name(),)),
+    }
+}
+
+/// Check that `offset..offset + len` lies within a buffer of `size` bytes
+fn check_range(offset: usize, len: usize, size: usize) -> PyResult<()> {
+    match offset.checked_add(len) {
+        Some(end) if end <= size => Ok(()),
+        _ => Err(PyValueError::new_err(
+            xmem_core::Error::OutOfBounds { offset, len, size }.to_string(),
+        )),
+    }
+}
+
+/// Copy `len` bytes (ranges may overlap), releasing the GIL for large copies
+fn copy_bytes(py: Python<'_>, src: *const u8, dst: *mut u8, len: usize) {
+    let (src, dst) = (src as usize, dst as usize);
+    let copy = move || unsafe { std::ptr::copy(src as *const u8, dst as *mut u8, len) };
+    if len >= GIL_RELEASE_THRESHOLD {
+        py.allow_threads(copy);
+    } else {
+        copy();
+    }
+}
+
+/// C-contiguous byte view of a bytes-like object, released on drop
+struct ByteView(ffi::Py_buffer);
+
+impl ByteView {
+    fn get(obj: &PyAny) -> PyResult<Self> {
+        let mut view = std::mem::MaybeUninit::<ffi::Py_buffer>::uninit();
+        unsafe {
+            if ffi::PyObject_GetBuffer(obj.as_ptr(), view.as_mut_ptr(), ffi::PyBUF_C_CONTIGUOUS) != 0 {
+                return Err(PyErr::fetch(obj.py()));
+            }
+            Ok(Self(view.assume_init()))
+        }
+    }
+
+    fn as_ptr(&self) -> *const u8 {
+        self.0.buf as *const u8
+    }
+
+    fn len(&self) -> usize {
+        self.0.len as usize
+    }
+}
+
+impl Drop for ByteView {
+    fn drop(&mut self) {
+        // Only dropped with the GIL held
+        unsafe { ffi::PyBuffer_Release(&mut self.0) };
+    }
 }
 
 /// Python wrapper for BufferPool
-#[pyclass(unsendable)]
+///
+/// core pool 是 `Send + Sync` 的，可在任意 Python 线程中使用。
+#[pyclass(module = "xmem")]
 struct BufferPool {
-    inner: Arc<CorePool>,
+    inner: CorePool,
 }
 
 /// Python wrapper for BufferGuard
 ///
-/// 持有 pool 的 Arc 引用，确保 pool 在 guard 存活期间不会被释放
-#[pyclass(unsendable)]
+/// 持有 core guard（其内部保持 pool 映射），实现 Python buffer 协议。
+/// 导出的 memoryview 存活期间 buffer 不会被释放。
+/// 可变状态由互斥锁保护，可在多个 Python 线程间共享；持锁期间不调用 Python 代码。
+#[pyclass(module = "xmem")]
 struct BufferGuard {
-    pool: Arc<CorePool>,
     meta_index: u32,
     mode: AccessMode,
-    /// 是否已调用 forget()
-    forgotten: bool,
+    state: Mutex<GuardState>,
+}
+
+/// Mutable state of a Python `BufferGuard`
+struct GuardState {
+    /// Core guard, `None` after forget() / release
+    inner: Option<CoreGuard>,
+    /// Number of live buffer-protocol exports
+    exports: usize,
+    /// `__exit__` was called while views were exported
+    release_pending: bool,
+}
+
+impl BufferGuard {
+    fn new(inner: CoreGuard) -> Self {
+        Self {
+            meta_index: inner.meta_index(),
+            mode: inner.mode(),
+            state: Mutex::new(GuardState {
+                inner: Some(inner),
+                exports: 0,
+                release_pending: false,
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, GuardState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Pin the CPU buffer, returning its address and length
+    ///
+    /// 与导出的视图一样计入 `exports`：在 [`Self::unpin`] 之前 guard 不会被释放，
+    /// 因此可以在释放 GIL（和状态锁）后访问数据。
+    fn pin(&self, writable: bool) -> PyResult<(*mut u8, usize)> {
+        let mut state = self.state();
+        let guard = state.guard_mut()?;
+        let slice = if writable {
+            guard.as_cpu_slice_mut().map(|s| s as *mut [u8])
+        } else {
+            guard.as_cpu_slice().map(|s| s as *const [u8] as *mut [u8])
+        }
+        .map_err(to_py_err)?;
+        state.exports += 1;
+        Ok((slice as *mut u8, slice.len()))
+    }
+
+    /// Undo [`Self::pin`], finishing a release deferred by `__exit__`
+    fn unpin(&self) {
+        let mut state = self.state();
+        state.exports = state.exports.saturating_sub(1);
+        if state.exports == 0 && state.release_pending {
+            state.inner = None;
+        }
+    }
+
+    /// [`GuardState::take_inner`] under the lock
+    fn take_inner(&self) -> PyResult<CoreGuard> {
+        self.state().take_inner()
+    }
+}
+
+impl GuardState {
+    fn guard(&self) -> PyResult<&CoreGuard> {
+        match &self.inner {
+            Some(guard) if !self.release_pending => Ok(guard),
+            _ => Err(PyRuntimeError::new_err("buffer already forgotten")),
+        }
+    }
+
+    /// Take the core guard out (forget / publish), refused while views are exported
+    fn take_inner(&mut self) -> PyResult<CoreGuard> {
+        if self.exports > 0 {
+            return Err(PyBufferError::new_err(
+                "cannot hand off buffer while views are exported",
+            ));
+        }
+        self.guard()?;
+        Ok(self.inner.take().expect("checked by guard()"))
+    }
+
+    fn guard_mut(&mut self) -> PyResult<&mut CoreGuard> {
+        match &mut self.inner {
+            Some(guard) if !self.release_pending => Ok(guard),
+            _ => Err(PyRuntimeError::new_err("buffer already forgotten")),
+        }
+    }
 }
 
 #[pymethods]
 impl BufferPool {
     /// Create a new buffer pool
+    ///
+    /// `namespace` is prepended to the name; with `backing_dir` the segments
+    /// are files in that directory instead of POSIX shared memory.
+    /// `huge_pages` is "off", "transparent" or "hugetlb"; `prefault` populates
+    /// pages at allocation. `mlock` locks buffer mappings in memory and
+    /// `numa_node` binds new buffers to a NUMA node (a no-op on single-node machines).
+    /// `cache_segments` keeps released CPU segments for reuse, bounded by
+    /// `trim_idle_after` (seconds, see `trim()`) and `max_cached_bytes`.
     #[new]
-    #[pyo3(signature = (name, capacity=1024))]
-    fn new(name: &str, capacity: usize) -> PyResult<Self> {
-        let inner = CorePool::create_with_capacity(name, capacity)
+    #[pyo3(signature = (
+        name,
+        capacity=1024,
+        *,
+        namespace=None,
+        backing_dir=None,
+        huge_pages="off",
+        prefault=false,
+        mlock=false,
+        numa_node=None,
+        cache_segments=false,
+        trim_idle_after=None,
+        max_cached_bytes=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        name: &str,
+        capacity: usize,
+        namespace: Option<&str>,
+        backing_dir: Option<std::path::PathBuf>,
+        huge_pages: &str,
+        prefault: bool,
+        mlock: bool,
+        numa_node: Option<u32>,
+        cache_segments: bool,
+        trim_idle_after: Option<f64>,
+        max_cached_bytes: Option<u64>,
+    ) -> PyResult<Self> {
+        let config = pool_config(namespace, backing_dir);
+        let mut config = cache_config(config, cache_segments, trim_idle_after, max_cached_bytes)?
+            .capacity(capacity)
+            .huge_pages(to_huge_pages(huge_pages)?)
+            .prefault(prefault)
+            .mlock(mlock);
+        if let Some(node) = numa_node {
+            config = config.numa_node(node);
+        }
+        let inner = CorePool::create_with_config(name, &config)
             .map_err(to_py_err)?;
-        Ok(Self { inner: Arc::new(inner) })
+        Ok(Self { inner })
     }
 
-    /// Open an existing buffer pool
+    /// Open an existing buffer pool (same `namespace` / `backing_dir` as the creator)
+    ///
+    /// The cache settings apply to buffers this process releases and should
+    /// match the other processes sharing the pool.
     #[staticmethod]
-    fn open(name: &str) -> PyResult<Self> {
-        let inner = CorePool::open(name).map_err(to_py_err)?;
-        Ok(Self { inner: Arc::new(inner) })
+    #[pyo3(signature = (
+        name,
+        namespace=None,
+        backing_dir=None,
+        *,
+        mlock=false,
+        cache_segments=false,
+        trim_idle_after=None,
+        max_cached_bytes=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn open(
+        name: &str,
+        namespace: Option<&str>,
+        backing_dir: Option<std::path::PathBuf>,
+        mlock: bool,
+        cache_segments: bool,
+        trim_idle_after: Option<f64>,
+        max_cached_bytes: Option<u64>,
+    ) -> PyResult<Self> {
+        let config = pool_config(namespace, backing_dir).mlock(mlock);
+        let config = cache_config(config, cache_segments, trim_idle_after, max_cached_bytes)?;
+        let inner = CorePool::open_with_config(name, &config).map_err(to_py_err)?;
+        Ok(Self { inner })
     }
 
-    /// Get pool name
+    /// Get pool name (including the namespace)
     #[getter]
     fn name(&self) -> &str {
         self.inner.name()
     }
 
+    /// Huge page mode in effect ("off", "transparent" or "hugetlb")
+    #[getter]
+    fn huge_pages(&self) -> &'static str {
+        let mode = self.inner.huge_pages();
+        HUGE_PAGE_MODES
+            .iter()
+            .find(|(_, m)| *m == mode)
+            .map_or("off", |(name, _)| name)
+    }
+
+    /// NUMA node new buffers are bound to, None if unbound or single-node
+    #[getter]
+    fn numa_node(&self) -> Option<u32> {
+        self.inner.numa_node()
+    }
+
+    /// Whether buffer mappings are locked in memory
+    #[getter]
+    fn mlock(&self) -> bool {
+        self.inner.is_mlocked()
+    }
+
+    /// Directory holding the segment files, or None for shared memory
+    #[getter]
+    fn backing_dir(&self) -> Option<String> {
+        self.inner.backing_dir().map(|dir| dir.display().to_string())
+    }
+
+    /// Pickle support: unpickling reopens the pool by name
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, PyObject)> {
+        // The name already carries the namespace
+        let open = py.get_type::<Self>().getattr("open")?;
+        let args = (self.inner.name(), None::<&str>, self.backing_dir());
+        Ok((open.into(), args.into_py(py)))
+    }
+
     /// Get capacity
     #[getter]
     fn capacity(&self) -> usize {
@@ -59,69 +445,151 @@ impl BufferPool {
     }
 
     /// Acquire a CPU buffer
-    fn acquire_cpu(&self, size: usize) -> PyResult<BufferGuard> {
-        // 先获取 guard 以分配 buffer，然后立即 forget 以转移所有权
-        let guard = self.inner.acquire_cpu(size).map_err(to_py_err)?;
-        let meta_index = guard.meta_index();
-        guard.forget(); // 不减少引用计数，由 PyBufferGuard 管理
+    ///
+    /// 默认池满时立即失败；`block=True` 时等待空闲槽位，
+    /// 指定 `timeout`（秒）时最多等待该时长，超时抛出 `AcquireTimeoutError`。
+    /// 等待期间释放 GIL。
+    #[pyo3(signature = (size, timeout=None, block=false))]
+    fn acquire_cpu(
+        &self,
+        py: Python<'_>,
+        size: usize,
+        timeout: Option<f64>,
+        block: bool,
+    ) -> PyResult<BufferGuard> {
+        let pool = &self.inner;
+        let guard = match timeout.map(to_duration).transpose()? {
+            None if !block => py.allow_threads(|| pool.acquire_cpu(size)).map_err(to_py_err)?,
+            timeout => wait_without_gil(py, timeout, |slice| {
+                pool.acquire_cpu_blocking(size, slice)
+            })?,
+        };
+        Ok(BufferGuard::new(guard))
+    }
 
-        Ok(BufferGuard {
-            pool: Arc::clone(&self.inner),
-            meta_index,
-            mode: AccessMode::ReadWrite,
-            forgotten: false,
-        })
+    /// Acquire a CPU buffer without blocking the event loop
+    ///
+    /// 返回 awaitable；池满时在事件循环中等待空闲槽位（不占用线程），
+    /// 超时抛出 `AcquireTimeoutError`。
+    #[pyo3(signature = (size, timeout=None))]
+    fn acquire_cpu_async(
+        &self,
+        py: Python<'_>,
+        size: usize,
+        timeout: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let timeout = timeout.map(to_duration).transpose()?;
+        aio::start(py, &self.inner, aio::AsyncOp::Acquire(size), timeout)
     }
 
     /// Acquire a CUDA buffer
     #[cfg(feature = "cuda")]
     fn acquire_cuda(&self, size: usize, device_id: i32) -> PyResult<BufferGuard> {
         let guard = self.inner.acquire_cuda(size, device_id).map_err(to_py_err)?;
-        let meta_index = guard.meta_index();
-        guard.forget();
+        Ok(BufferGuard::new(guard))
+    }
+
+    /// Acquire a CPU buffer sized for a tensor and record its metadata
+    ///
+    /// `dtype` 可为类型名字符串（如 `"float32"`）、numpy 类型或 `numpy.dtype`。
+    fn acquire_cpu_array(&self, shape: Vec<usize>, dtype: &PyAny) -> PyResult<BufferGuard> {
+        let dtype = extract_dtype(dtype)?;
+        let guard = self.inner.acquire_cpu_array(&shape, dtype).map_err(to_py_err)?;
+        Ok(BufferGuard::new(guard))
+    }
 
-        Ok(BufferGuard {
-            pool: Arc::clone(&self.inner),
-            meta_index,
-            mode: AccessMode::ReadWrite,
-            forgotten: false,
-        })
+    /// Copy an external CPU tensor into a new buffer via DLPack
+    ///
+    /// `obj` 需实现 `__dlpack__` / `__dlpack_device__`（torch、numpy、jax 等）。
+    /// 新 buffer 记录张量的 shape/dtype，数据按 C 连续布局拷贝。
+    #[allow(clippy::wrong_self_convention)] // 与 torch.from_dlpack 等命名保持一致
+    fn from_dlpack(&self, obj: &PyAny) -> PyResult<BufferGuard> {
+        let tensor = dlpack::Imported::from_object(obj)?;
+        let mut guard = self
+            .inner
+            .acquire_cpu_array(&tensor.shape, tensor.dtype)
+            .map_err(to_py_err)?;
+        tensor.copy_to(guard.as_cpu_slice_mut().map_err(to_py_err)?);
+        Ok(BufferGuard::new(guard))
     }
 
     /// Preallocate CPU buffers
-    fn preallocate_cpu(&self, size: usize, count: usize) -> PyResult<Vec<u32>> {
-        self.inner.preallocate_cpu(size, count).map_err(to_py_err)
+    fn preallocate_cpu(&self, py: Python<'_>, size: usize, count: usize) -> PyResult<Vec<u32>> {
+        let pool = &self.inner;
+        py.allow_threads(|| pool.preallocate_cpu(size, count)).map_err(to_py_err)
     }
 
     /// Preallocate CUDA buffers
     #[cfg(feature = "cuda")]
-    fn preallocate_cuda(&self, size: usize, count: usize, device_id: i32) -> PyResult<Vec<u32>> {
-        self.inner.preallocate_cuda(size, count, device_id).map_err(to_py_err)
+    fn preallocate_cuda(
+        &self,
+        py: Python<'_>,
+        size: usize,
+        count: usize,
+        device_id: i32,
+    ) -> PyResult<Vec<u32>> {
+        let pool = &self.inner;
+        py.allow_threads(|| pool.preallocate_cuda(size, count, device_id))
+            .map_err(to_py_err)
     }
 
     /// Get a buffer (read-only)
-    fn get(&self, meta_index: u32) -> PyResult<BufferGuard> {
-        // 验证 buffer 存在并增加引用计数
-        self.inner.add_ref(meta_index).map_err(to_py_err)?;
-
-        Ok(BufferGuard {
-            pool: Arc::clone(&self.inner),
-            meta_index,
-            mode: AccessMode::ReadOnly,
-            forgotten: false,
-        })
+    ///
+    /// 等待其他进程的写锁时释放 GIL。
+    fn get(&self, py: Python<'_>, meta_index: u32) -> PyResult<BufferGuard> {
+        let pool = &self.inner;
+        let guard = wait_without_gil(py, None, |slice| pool.get_timeout(meta_index, slice))?;
+        Ok(BufferGuard::new(guard))
     }
 
     /// Get a buffer (read-write)
-    fn get_mut(&self, meta_index: u32) -> PyResult<BufferGuard> {
-        self.inner.add_ref(meta_index).map_err(to_py_err)?;
+    ///
+    /// 等待其他读者/写者释放锁时释放 GIL。
+    fn get_mut(&self, py: Python<'_>, meta_index: u32) -> PyResult<BufferGuard> {
+        let pool = &self.inner;
+        let guard = wait_without_gil(py, None, |slice| pool.get_mut_timeout(meta_index, slice))?;
+        Ok(BufferGuard::new(guard))
+    }
+
+    /// Publish a buffer to one receiver, transferring the guard's reference
+    ///
+    /// 发布后 guard 失效；存在导出的 memoryview 时抛出 BufferError。
+    fn publish(&self, buf: &BufferGuard) -> PyResult<()> {
+        let guard = buf.take_inner()?;
+        self.inner.publish(guard).map_err(to_py_err)
+    }
 
-        Ok(BufferGuard {
-            pool: Arc::clone(&self.inner),
-            meta_index,
-            mode: AccessMode::ReadWrite,
-            forgotten: false,
-        })
+    /// Receive the oldest published buffer, or `None` if there is none
+    fn try_receive(&self) -> PyResult<Option<BufferGuard>> {
+        let guard = self.inner.try_receive().map_err(to_py_err)?;
+        Ok(guard.map(BufferGuard::new))
+    }
+
+    /// Receive a published buffer, waiting up to `timeout` seconds (forever if `None`)
+    ///
+    /// 等待期间释放 GIL。
+    #[pyo3(signature = (timeout=None))]
+    fn receive(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<BufferGuard> {
+        let timeout = timeout.map(to_duration).transpose()?;
+        let pool = &self.inner;
+        let guard = wait_without_gil(py, timeout, |slice| pool.receive_timeout(slice))?;
+        Ok(BufferGuard::new(guard))
+    }
+
+    /// Receive a published buffer without blocking the event loop
+    ///
+    /// Linux 上任何进程的发布都立即唤醒；其他平台上其他进程的发布按 10ms 间隔轮询发现。
+    #[pyo3(signature = (timeout=None))]
+    fn receive_async(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<PyObject> {
+        let timeout = timeout.map(to_duration).transpose()?;
+        aio::start(py, &self.inner, aio::AsyncOp::Receive, timeout)
+    }
+
+    /// Async iterator over published buffers: `async for buf in pool.incoming()`
+    fn incoming(&self) -> aio::Incoming {
+        aio::Incoming {
+            pool: self.inner.clone(),
+        }
     }
 
     /// Set reference count
@@ -143,6 +611,17 @@ impl BufferPool {
     fn ref_count(&self, meta_index: u32) -> PyResult<i32> {
         self.inner.ref_count(meta_index).map_err(to_py_err)
     }
+
+    /// Return memory held by cached segments to the system
+    ///
+    /// Returns `(released, unlinked, cached_bytes)`: slots whose idle pages
+    /// were released, slots whose segments were deleted to fit
+    /// `max_cached_bytes`, and the bytes still cached. Without
+    /// `trim_idle_after` the pages of every cached segment are released.
+    fn trim(&self, py: Python<'_>) -> PyResult<(Vec<u32>, Vec<u32>, u64)> {
+        let report = py.allow_threads(|| self.inner.trim()).map_err(to_py_err)?;
+        Ok((report.released, report.unlinked, report.cached_bytes))
+    }
 }
 
 #[pymethods]
@@ -153,86 +632,380 @@ impl BufferGuard {
         self.meta_index
     }
 
-    /// Check if buffer is valid (not forgotten)
+    /// Check if buffer is valid (not forgotten or released)
     #[getter]
     fn is_valid(&self) -> bool {
-        !self.forgotten
+        let state = self.state();
+        state.inner.is_some() && !state.release_pending
     }
 
-    /// Get CPU pointer as integer (按需创建临时 guard)
+    /// Get CPU pointer as integer
     #[getter]
     fn cpu_ptr(&self) -> PyResult<u64> {
-        if self.forgotten {
-            return Err(PyRuntimeError::new_err("buffer already forgotten"));
-        }
-        let guard = self.pool.get(self.meta_index).map_err(to_py_err)?;
-        let slice = guard.as_cpu_slice().map_err(to_py_err)?;
-        let ptr = slice.as_ptr() as u64;
-        guard.forget(); // 不减少引用计数
-        Ok(ptr)
+        let state = self.state();
+        let slice = state.guard()?.as_cpu_slice().map_err(to_py_err)?;
+        Ok(slice.as_ptr() as u64)
     }
 
     /// Get CPU pointer as integer (mutable)
     #[getter]
     fn cpu_ptr_mut(&self) -> PyResult<u64> {
-        if self.forgotten {
-            return Err(PyRuntimeError::new_err("buffer already forgotten"));
-        }
         if self.mode == AccessMode::ReadOnly {
-            return Err(PyRuntimeError::new_err("buffer is read-only"));
+            return Err(to_py_err(xmem_core::Error::ReadOnly));
         }
-        let mut guard = self.pool.get_mut(self.meta_index).map_err(to_py_err)?;
-        let slice = guard.as_cpu_slice_mut().map_err(to_py_err)?;
-        let ptr = slice.as_mut_ptr() as u64;
-        guard.forget();
-        Ok(ptr)
+        let mut state = self.state();
+        let slice = state.guard_mut()?.as_cpu_slice_mut().map_err(to_py_err)?;
+        Ok(slice.as_mut_ptr() as u64)
     }
 
     /// Get CUDA device pointer
     #[cfg(feature = "cuda")]
     #[getter]
     fn cuda_ptr(&self) -> PyResult<u64> {
-        if self.forgotten {
-            return Err(PyRuntimeError::new_err("buffer already forgotten"));
-        }
-        let guard = self.pool.get(self.meta_index).map_err(to_py_err)?;
-        let ptr = guard.as_cuda_ptr().map_err(to_py_err)?;
-        guard.forget();
-        Ok(ptr)
+        self.state().guard()?.as_cuda_ptr().map_err(to_py_err)
     }
 
     /// Get CUDA device pointer (mutable)
     #[cfg(feature = "cuda")]
     #[getter]
     fn cuda_ptr_mut(&self) -> PyResult<u64> {
-        if self.forgotten {
-            return Err(PyRuntimeError::new_err("buffer already forgotten"));
-        }
         if self.mode == AccessMode::ReadOnly {
-            return Err(PyRuntimeError::new_err("buffer is read-only"));
+            return Err(to_py_err(xmem_core::Error::ReadOnly));
         }
-        let mut guard = self.pool.get_mut(self.meta_index).map_err(to_py_err)?;
-        let ptr = guard.as_cuda_ptr_mut().map_err(to_py_err)?;
-        guard.forget();
-        Ok(ptr)
+        self.state().guard_mut()?.as_cuda_ptr_mut().map_err(to_py_err)
     }
 
     /// Get buffer size
     #[getter]
     fn size(&self) -> PyResult<usize> {
-        if self.forgotten {
-            return Err(PyRuntimeError::new_err("buffer already forgotten"));
+        self.state().guard()?.size().map_err(to_py_err)
+    }
+
+    /// Copy a bytes-like object into the buffer at `offset`, returning the byte count
+    ///
+    /// 越界时抛出 ValueError，只读 guard 抛出 `ReadOnlyBufferError`。
+    /// 大块拷贝期间释放 GIL。
+    #[pyo3(signature = (data, offset=0))]
+    fn write(&self, py: Python<'_>, data: &PyAny, offset: usize) -> PyResult<usize> {
+        if self.mode == AccessMode::ReadOnly {
+            return Err(to_py_err(xmem_core::Error::ReadOnly));
         }
-        let guard = self.pool.get(self.meta_index).map_err(to_py_err)?;
-        let slice = guard.as_cpu_slice().map_err(to_py_err)?;
-        let size = slice.len();
-        guard.forget();
-        Ok(size)
+        // Acquire the source first: it may be a view of this very guard
+        let src = ByteView::get(data)?;
+        let (ptr, size) = self.pin(true)?;
+        let len = src.len();
+        let result = check_range(offset, len, size).map(|()| {
+            copy_bytes(py, src.as_ptr(), unsafe { ptr.add(offset) }, len);
+            len
+        });
+        self.unpin();
+        result
+    }
+
+    /// Read `n` bytes from `offset` (`n=-1` reads to the end of the buffer)
+    ///
+    /// 越界时抛出 ValueError。大块拷贝期间释放 GIL。
+    #[pyo3(signature = (n=-1, offset=0))]
+    fn read<'py>(&self, py: Python<'py>, n: isize, offset: usize) -> PyResult<&'py PyBytes> {
+        let (ptr, size) = self.pin(false)?;
+        let len = match usize::try_from(n) {
+            Ok(n) => Ok(n),
+            Err(_) if n == -1 => Ok(size.saturating_sub(offset)),
+            Err(_) => Err(PyValueError::new_err("n must be non-negative or -1")),
+        };
+        let result = len.and_then(|len| {
+            check_range(offset, len, size)?;
+            PyBytes::new_with(py, len, |dst| {
+                copy_bytes(py, unsafe { ptr.add(offset) }, dst.as_mut_ptr(), len);
+                Ok(())
+            })
+        });
+        self.unpin();
+        result
+    }
+
+    /// Name of the shared memory segment holding the data (e.g. `/pool_buf_3`)
+    ///
+    /// 视图返回父 buffer 的段名称。
+    #[getter]
+    fn shm_name(&self) -> PyResult<String> {
+        let state = self.state();
+        Ok(state.guard()?.backing_name().map_err(to_py_err)?.to_string())
+    }
+
+    /// Attach a `multiprocessing.shared_memory.SharedMemory` to the buffer's segment
+    ///
+    /// 返回的对象持有 buffer 的一个独立引用（对象回收时释放），
+    /// 不会 unlink 段名称，也不登记到 resource_tracker；`close()` 只解除其自身的映射。
+    /// stdlib 对象总是可写的，因此只读 guard 抛出 `ReadOnlyBufferError`；视图抛出 ValueError。
+    fn as_shared_memory(&self, py: Python<'_>) -> PyResult<PyObject> {
+        if self.mode == AccessMode::ReadOnly {
+            return Err(to_py_err(xmem_core::Error::ReadOnly));
+        }
+        let (name, guard) = {
+            let state = self.state();
+            let guard = state.guard()?;
+            let meta = guard.meta().map_err(to_py_err)?;
+            if meta.storage_type.load(std::sync::atomic::Ordering::SeqCst)
+                == StorageType::View as u8
+            {
+                return Err(PyValueError::new_err(
+                    "views are not backed by a segment of their own",
+                ));
+            }
+            let name = guard.backing_name().map_err(to_py_err)?.to_string();
+            (name, guard.try_clone().map_err(to_py_err)?)
+        };
+        let guard = Py::new(py, BufferGuard::new(guard))?;
+
+        // The stdlib adds the leading slash itself
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("name", name.trim_start_matches('/'))?;
+        let track = py.version_info() < (3, 13);
+        if !track {
+            kwargs.set_item("track", false)?;
+        }
+        let shm = py
+            .import("multiprocessing.shared_memory")?
+            .getattr("SharedMemory")?
+            .call((), Some(kwargs))?;
+        if track {
+            // Attaching registers the segment for unlinking at exit; undo that
+            py.import("multiprocessing.resource_tracker")?
+                .call_method1("unregister", (shm.getattr("_name")?, "shared_memory"))?;
+        }
+        shm.setattr("_xmem_guard", guard)?;
+        Ok(shm.into())
+    }
+
+    /// Get a read-only snapshot of the buffer metadata
+    #[getter]
+    fn meta(&self) -> PyResult<meta::MetaInfo> {
+        let state = self.state();
+        let meta = state.guard()?.meta().map_err(to_py_err)?;
+        Ok(meta::MetaInfo::snapshot(meta))
+    }
+
+    /// Update buffer metadata (writable guards only)
+    ///
+    /// 只修改传入的字段。`shape`/`dtype` 只传其一时另一个沿用当前值
+    /// （没有 dtype 时为 uint8），strides 按 C 连续布局重新计算，
+    /// 张量大小不能超过 buffer 大小。seqlock 模式下 `seq` 由 seqlock 维护，不能手动设置。
+    #[pyo3(signature = (*, shape=None, dtype=None, content_type=None, producer=None, seq=None))]
+    fn set_meta(
+        &self,
+        shape: Option<Vec<usize>>,
+        dtype: Option<&PyAny>,
+        content_type: Option<&str>,
+        producer: Option<&str>,
+        seq: Option<u64>,
+    ) -> PyResult<()> {
+        if self.mode == AccessMode::ReadOnly {
+            return Err(to_py_err(xmem_core::Error::ReadOnly));
+        }
+        let dtype = dtype.map(extract_dtype).transpose()?;
+        let invalid = |e: xmem_core::Error| PyValueError::new_err(e.to_string());
+
+        let state = self.state();
+        let guard = state.guard()?;
+        let meta = guard.meta().map_err(to_py_err)?;
+        if seq.is_some() && guard.is_seqlock() {
+            return Err(PyValueError::new_err("seq is managed by the seqlock"));
+        }
+        // Validate everything before writing anything: labels here, the tensor
+        // in set_tensor, which runs first
+        for label in [content_type, producer].into_iter().flatten() {
+            if label.len() > LABEL_SIZE {
+                return Err(invalid(xmem_core::Error::LabelTooLong {
+                    len: label.len(),
+                    max: LABEL_SIZE,
+                }));
+            }
+        }
+        if shape.is_some() || dtype.is_some() {
+            let shape = shape.unwrap_or_else(|| meta.shape());
+            let dtype = dtype.or(meta.dtype()).unwrap_or(DType::UInt8);
+            guard.set_tensor(&shape, dtype).map_err(invalid)?;
+        }
+        if let Some(value) = content_type {
+            meta.set_content_type(value).map_err(invalid)?;
+        }
+        if let Some(value) = producer {
+            meta.set_producer(value).map_err(invalid)?;
+        }
+        if let Some(seq) = seq {
+            meta.seq.store(seq, std::sync::atomic::Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Get a zero-copy numpy array over the buffer
+    ///
+    /// 未指定参数时使用元数据中的 shape/strides/dtype；没有元数据时
+    /// 返回一维 uint8 数组。数组的 base 为导出的 memoryview，
+    /// 数组存活期间 guard 不会被释放。只读 guard 返回只读数组。
+    #[pyo3(signature = (shape=None, dtype=None, strides=None))]
+    fn numpy(
+        slf: &PyCell<Self>,
+        shape: Option<Vec<usize>>,
+        dtype: Option<&PyAny>,
+        strides: Option<Vec<isize>>,
+    ) -> PyResult<PyObject> {
+        let py = slf.py();
+        let (size, meta_shape, meta_strides, meta_dtype) = {
+            let this = slf.borrow();
+            let state = this.state();
+            let guard = state.guard()?;
+            let meta = guard.meta().map_err(to_py_err)?;
+            (guard.size().map_err(to_py_err)?, meta.shape(), meta.strides(), meta.dtype())
+        };
+
+        let dtype = match dtype {
+            Some(obj) => extract_dtype(obj)?,
+            None => meta_dtype.unwrap_or(DType::UInt8),
+        };
+        let (shape, strides) = match shape {
+            Some(shape) => (shape, strides),
+            None if strides.is_some() => {
+                return Err(PyValueError::new_err("strides requires an explicit shape"));
+            }
+            None if !meta_shape.is_empty() && meta_dtype == Some(dtype) => {
+                let strides = meta_strides.into_iter().map(|s| s as isize).collect();
+                (meta_shape, Some(strides))
+            }
+            None if size % dtype.size() == 0 => (vec![size / dtype.size()], None),
+            None => {
+                return Err(PyValueError::new_err(format!(
+                    "buffer size {} is not a multiple of {} element size",
+                    size, dtype
+                )));
+            }
+        };
+
+        // numpy validates the layout against the buffer length
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("shape", PyTuple::new(py, shape))?;
+        kwargs.set_item("dtype", numpy_dtype(py, dtype)?)?;
+        let view = py.import("builtins")?.getattr("memoryview")?.call1((slf,))?;
+        kwargs.set_item("buffer", view)?;
+        kwargs.set_item("strides", strides.map(|s| PyTuple::new(py, s)))?;
+        let array = py.import("numpy")?.getattr("ndarray")?.call((), Some(kwargs))?;
+        Ok(array.into())
+    }
+
+    /// DLPack: export the buffer as a `"dltensor"` capsule
+    ///
+    /// 导出的张量持有 buffer 的独立引用，并与 guard 共享读写锁，
+    /// 由消费方释放张量时归还。数据由生产者写完后发布，无需流同步。
+    ///
+    /// `max_version` 不低于 `(1, 0)` 时导出 `"dltensor_versioned"`，只读 guard
+    /// 带只读标志；否则导出 `"dltensor"`，只读 guard 抛出 `BufferError`。
+    #[pyo3(signature = (*, stream=None, max_version=None, dl_device=None, copy=None))]
+    fn __dlpack__(
+        &self,
+        py: Python<'_>,
+        stream: Option<&PyAny>,
+        max_version: Option<(u32, u32)>,
+        dl_device: Option<(i32, i32)>,
+        copy: Option<bool>,
+    ) -> PyResult<PyObject> {
+        // Data is complete once published, so there is no stream to wait on
+        let _ = stream;
+        let device = self.__dlpack_device__()?;
+        if dl_device.is_some_and(|d| d != device) {
+            return Err(PyBufferError::new_err("cannot export to a different device"));
+        }
+        if copy == Some(true) {
+            return Err(PyBufferError::new_err("copying export is not supported"));
+        }
+        let guard = self.state().guard()?.try_clone().map_err(to_py_err)?;
+        dlpack::export(py, guard, device, max_version)
+    }
+
+    /// DLPack: `(device_type, device_id)` of the buffer
+    fn __dlpack_device__(&self) -> PyResult<(i32, i32)> {
+        let state = self.state();
+        let guard = state.guard()?;
+        #[cfg(feature = "cuda")]
+        if guard.as_cuda_ptr().is_ok() {
+            let meta = guard.meta().map_err(to_py_err)?;
+            let device_id = meta.device_id.load(std::sync::atomic::Ordering::SeqCst);
+            return Ok((dlpack::DEVICE_CUDA, device_id as i32));
+        }
+        guard.as_cpu_slice().map_err(to_py_err)?;
+        Ok((dlpack::DEVICE_CPU, 0))
+    }
+
+    /// Pickle support: the guard's reference moves into the pickle
+    ///
+    /// 与 `publish` 一样，pickle 后 guard 失效，读写锁随之释放，
+    /// 反序列化时重新获取；存在导出的 memoryview 时抛出 BufferError。
+    /// 引用由反序列化接管：从未反序列化的 pickle 数据会使 buffer 一直存活，
+    /// 同一份数据反序列化多次会多释放引用。
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, PyObject)> {
+        let adopt = py.import("xmem")?.getattr("_adopt_buffer")?;
+        let guard = self.take_inner()?;
+        let backing_dir = guard.backing_dir().map(|d| d.display().to_string());
+        let handle = guard.into_handle().map_err(to_py_err)?;
+        let args = (
+            handle.pool_name,
+            handle.meta_index,
+            handle.generation,
+            handle.mode == AccessMode::ReadOnly,
+            backing_dir,
+        );
+        Ok((adopt.into(), args.into_py(py)))
     }
 
     /// Forget this guard without releasing
-    fn forget(&mut self) {
-        self.forgotten = true;
+    ///
+    /// 存在导出的 memoryview 时抛出 BufferError。
+    fn forget(&self) -> PyResult<()> {
+        let mut state = self.state();
+        if state.exports > 0 {
+            return Err(PyBufferError::new_err(
+                "cannot hand off buffer while views are exported",
+            ));
+        }
+        if let Some(guard) = state.inner.take() {
+            guard.forget();
+        }
+        Ok(())
+    }
+
+    /// Buffer protocol: export the CPU buffer as 1-D unsigned bytes
+    ///
+    /// 只读 guard 导出只读视图，请求可写视图时抛出 BufferError。
+    unsafe fn __getbuffer__(
+        slf: PyRef<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let readonly = slf.mode == AccessMode::ReadOnly;
+        let (ptr, len) = slf
+            .pin(!readonly)
+            .map_err(|e| PyBufferError::new_err(e.to_string()))?;
+
+        // Fills a contiguous "B" view, increfs obj and rejects writable
+        // requests on read-only buffers
+        let ret = ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            ptr as *mut std::os::raw::c_void,
+            len as ffi::Py_ssize_t,
+            readonly as c_int,
+            flags,
+        );
+        if ret != 0 {
+            slf.unpin();
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+
+    /// Buffer protocol: a view was released
+    unsafe fn __releasebuffer__(slf: PyRef<'_, Self>, _view: *mut ffi::Py_buffer) {
+        slf.unpin();
     }
 
     /// Context manager enter
@@ -241,31 +1014,56 @@ impl BufferGuard {
     }
 
     /// Context manager exit - release reference
+    ///
+    /// 仍有导出的 memoryview 时延迟到最后一个视图释放后再释放。
     fn __exit__(
-        &mut self,
+        &self,
         _exc_type: Option<&PyAny>,
         _exc_val: Option<&PyAny>,
         _exc_tb: Option<&PyAny>,
     ) -> bool {
-        if !self.forgotten {
-            let _ = self.pool.release(self.meta_index);
-            self.forgotten = true;
+        let mut state = self.state();
+        if state.exports > 0 {
+            state.release_pending = true;
+        } else {
+            state.inner = None;
         }
         false
     }
 }
 
-impl Drop for BufferGuard {
-    fn drop(&mut self) {
-        if !self.forgotten {
-            let _ = self.pool.release(self.meta_index);
-        }
-    }
+/// Unpickle a `BufferGuard`: reopen the pool and adopt the transferred reference
+///
+/// 按原访问模式重新获取读写锁，等待期间释放 GIL。
+#[pyfunction]
+#[pyo3(name = "_adopt_buffer", signature = (pool_name, meta_index, generation, readonly, backing_dir=None))]
+fn adopt_buffer(
+    py: Python<'_>,
+    pool_name: String,
+    meta_index: u32,
+    generation: u32,
+    readonly: bool,
+    backing_dir: Option<std::path::PathBuf>,
+) -> PyResult<BufferGuard> {
+    let config = pool_config(None, backing_dir);
+    let pool = CorePool::open_with_config(&pool_name, &config).map_err(to_py_err)?;
+    let handle = BufferHandle {
+        pool_name,
+        meta_index,
+        generation,
+        mode: if readonly { AccessMode::ReadOnly } else { AccessMode::ReadWrite },
+    };
+    let guard = wait_without_gil(py, None, |slice| pool.adopt_timeout(&handle, slice))?;
+    Ok(BufferGuard::new(guard))
 }
 
 #[pymodule]
-fn xmem(_py: Python, m: &PyModule) -> PyResult<()> {
+fn xmem(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<BufferPool>()?;
     m.add_class::<BufferGuard>()?;
+    m.add_class::<meta::MetaInfo>()?;
+    m.add("AcquireTimeoutError", py.get_type::<AcquireTimeoutError>())?;
+    m.add("ReadOnlyBufferError", py.get_type::<ReadOnlyBufferError>())?;
+    m.add_function(wrap_pyfunction!(adopt_buffer, m)?)?;
     Ok(())
 }
@@ -0,0 +1,114 @@
+//! Buffer metadata snapshot
+
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::sync::atomic::Ordering;
+use xmem_core::BufferMeta as CoreMeta;
+
+/// Read-only snapshot of a buffer's metadata, see `BufferGuard.meta`
+///
+/// 读取时拷贝共享内存中的字段，之后的修改不会反映到该对象上。
+#[pyclass(module = "xmem", name = "BufferMeta", frozen)]
+pub struct MetaInfo {
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    dtype: Option<String>,
+    size: u64,
+    timestamp: u64,
+    seq: u64,
+    content_type: String,
+    producer: String,
+    numa_node: Option<u32>,
+}
+
+impl MetaInfo {
+    pub fn snapshot(meta: &CoreMeta) -> Self {
+        Self {
+            shape: meta.shape(),
+            strides: meta.strides(),
+            dtype: meta.dtype().map(|d| d.to_string()),
+            size: meta.size.load(Ordering::SeqCst),
+            timestamp: meta.timestamp.load(Ordering::SeqCst),
+            seq: meta.seq.load(Ordering::SeqCst),
+            content_type: meta.content_type(),
+            producer: meta.producer(),
+            numa_node: meta.numa_node(),
+        }
+    }
+}
+
+#[pymethods]
+impl MetaInfo {
+    /// Tensor shape, empty if none was recorded
+    #[getter]
+    fn shape<'py>(&self, py: Python<'py>) -> &'py PyTuple {
+        PyTuple::new(py, &self.shape)
+    }
+
+    /// Tensor strides in bytes
+    #[getter]
+    fn strides<'py>(&self, py: Python<'py>) -> &'py PyTuple {
+        PyTuple::new(py, &self.strides)
+    }
+
+    /// Element dtype name (e.g. `"float32"`)
+    #[getter]
+    fn dtype(&self) -> Option<&str> {
+        self.dtype.as_deref()
+    }
+
+    /// Buffer size in bytes
+    #[getter]
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Publish time in milliseconds since the Unix epoch, 0 if never published
+    #[getter]
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Producer-defined sequence number (seqlock counter in seqlock mode)
+    #[getter]
+    fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Content type label, empty if unset
+    #[getter]
+    fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Producer label, empty if unset
+    #[getter]
+    fn producer(&self) -> &str {
+        &self.producer
+    }
+
+    /// NUMA node the data is bound to, None if unbound
+    #[getter]
+    fn numa_node(&self) -> Option<u32> {
+        self.numa_node
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let fields: [(&str, PyObject); 9] = [
+            ("shape", self.shape(py).into()),
+            ("dtype", self.dtype.clone().into_py(py)),
+            ("strides", self.strides(py).into()),
+            ("size", self.size.into_py(py)),
+            ("timestamp", self.timestamp.into_py(py)),
+            ("seq", self.seq.into_py(py)),
+            ("content_type", self.content_type.clone().into_py(py)),
+            ("producer", self.producer.clone().into_py(py)),
+            ("numa_node", self.numa_node.into_py(py)),
+        ];
+        let fields = fields
+            .iter()
+            .map(|(name, value)| Ok(format!("{}={}", name, value.as_ref(py).repr()?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(format!("BufferMeta({})", fields.join(", ")))
+    }
+}
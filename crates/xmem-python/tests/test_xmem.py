@@ -1,9 +1,22 @@
 """Tests for xmem Python bindings."""
 
+import asyncio
 import pytest
+import threading
 import time
 
 
+def _read_buffer(buf):
+    """Worker for the process pool test."""
+    return bytes(buf)
+
+
+def _echo_buffer(conn):
+    """Child process: receive a buffer and send back its contents."""
+    buf = conn.recv()
+    conn.send(bytes(buf))
+
+
 def unique_name():
     """Generate unique pool name."""
     return f"/xmem_pytest_{int(time.time() * 1e9)}"
@@ -29,6 +42,66 @@ class TestBufferPool:
         pool = BufferPool(name, capacity=100)
         assert pool.capacity == 100
 
+    def test_namespace_and_backing_dir(self, tmp_path):
+        """Test pools with a name prefix and file-backed segments."""
+        import os
+        import pickle
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, capacity=8, namespace="ns_", backing_dir=str(tmp_path))
+        full = "/ns_" + name[1:]
+        assert pool.name == full
+        assert pool.backing_dir == str(tmp_path)
+        assert os.path.exists(tmp_path / (full[1:] + "_meta"))
+
+        buf = pool.acquire_cpu(4)
+        memoryview(buf)[:] = b"file"
+        reader = BufferPool.open(name, namespace="ns_", backing_dir=str(tmp_path))
+        assert bytes(reader.get(buf.meta_index)) == b"file"
+        copy = pickle.loads(pickle.dumps(reader.get(buf.meta_index)))
+        assert bytes(copy) == b"file"
+        assert pickle.loads(pickle.dumps(pool)).backing_dir == str(tmp_path)
+
+        with pytest.raises(RuntimeError):
+            BufferPool.open(full)
+
+    def test_huge_pages(self):
+        """Test huge page requests fall back and report the mode in effect."""
+        from xmem import BufferPool
+
+        pool = BufferPool(unique_name(), huge_pages="hugetlb", prefault=True)
+        assert pool.huge_pages in ("off", "transparent")
+        buf = pool.acquire_cpu(1 << 20)
+        assert bytes(memoryview(buf)[:4]) == b"\0\0\0\0"
+        assert BufferPool(unique_name()).huge_pages == "off"
+
+        with pytest.raises(ValueError):
+            BufferPool(unique_name(), huge_pages="giant")
+
+    def test_mlock_numa_node(self):
+        """Test memory locking and NUMA placement options."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, mlock=True, numa_node=0)
+        assert pool.mlock
+        buf = pool.acquire_cpu(4096)
+        assert buf.meta.numa_node == pool.numa_node
+        reader = BufferPool.open(name, mlock=True)
+        assert reader.get(buf.meta_index).meta.numa_node == pool.numa_node
+        assert BufferPool(unique_name()).numa_node is None
+
+    def test_invalid_name(self):
+        """Test that bad pool names are rejected up front."""
+        from xmem import BufferPool
+
+        for bad in ["", "/", "/a/b"]:
+            with pytest.raises(ValueError):
+                BufferPool(bad)
+        with pytest.raises(ValueError):
+            BufferPool(unique_name(), namespace="a/b")
+
     def test_acquire_cpu(self):
         """Test acquiring CPU buffer."""
         from xmem import BufferPool
@@ -89,6 +162,68 @@ class TestBufferPool:
         # After context, ref count should be decremented
         assert pool.ref_count(meta_index) == 1
 
+    def test_acquire_timeout(self):
+        """Test that a full pool times out with AcquireTimeoutError."""
+        from xmem import AcquireTimeoutError, BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, capacity=1)
+        buf = pool.acquire_cpu(64)
+
+        with pytest.raises(RuntimeError):
+            pool.acquire_cpu(64)
+
+        start = time.monotonic()
+        with pytest.raises(AcquireTimeoutError):
+            pool.acquire_cpu(64, timeout=0.05)
+        assert time.monotonic() - start >= 0.05
+        assert issubclass(AcquireTimeoutError, TimeoutError)
+
+        with pytest.raises(ValueError):
+            pool.acquire_cpu(64, timeout=-1)
+        buf.forget()
+
+    def test_acquire_releases_gil(self):
+        """Test that other Python threads run while acquire waits."""
+        from xmem import AcquireTimeoutError, BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, capacity=1)
+        buf = pool.acquire_cpu(64)
+
+        ticks = []
+        done = threading.Event()
+
+        def spin():
+            while not done.is_set():
+                ticks.append(1)
+                time.sleep(0.001)
+
+        t = threading.Thread(target=spin)
+        t.start()
+        try:
+            with pytest.raises(AcquireTimeoutError):
+                pool.acquire_cpu(64, timeout=0.2, block=True)
+        finally:
+            done.set()
+            t.join()
+        assert len(ticks) > 10
+
+        buf.forget()
+
+    def test_acquire_block_after_release(self):
+        """Test that a blocking acquire succeeds once a slot is freed."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, capacity=1)
+        buf = pool.acquire_cpu(64)
+        meta_index = buf.meta_index
+        buf.__exit__(None, None, None)
+
+        buf = pool.acquire_cpu(64, block=True)
+        assert buf.meta_index == meta_index
+
     def test_preallocate_cpu(self):
         """Test preallocating CPU buffers."""
         from xmem import BufferPool
@@ -102,6 +237,34 @@ class TestBufferPool:
         for idx in indices:
             assert pool.ref_count(idx) == 1
 
+    def test_cache_segments_and_trim(self):
+        """Test reusing cached segments and trimming them."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, cache_segments=True, trim_idle_after=3600)
+        buf = pool.acquire_cpu(4096)
+        buf.write(b"kept")
+        meta_index = buf.meta_index
+        buf.__exit__(None, None, None)
+
+        buf = pool.acquire_cpu(4096)
+        assert buf.meta_index == meta_index
+        assert buf.read(4) == b"kept"
+        buf.__exit__(None, None, None)
+
+        assert pool.trim() == ([], [], 4096)
+
+        trimmer = BufferPool.open(name, trim_idle_after=0)
+        assert trimmer.trim() == ([meta_index], [], 4096)
+
+        buf = pool.acquire_cpu(4096)
+        assert buf.read(4) == b"\0" * 4
+        buf.__exit__(None, None, None)
+
+        with pytest.raises(ValueError):
+            BufferPool(unique_name(), trim_idle_after=-1)
+
 
 class TestBufferGuard:
     """Tests for BufferGuard."""
@@ -135,3 +298,762 @@ class TestBufferGuard:
 
         with pytest.raises(RuntimeError):
             _ = buf.cpu_ptr_mut  # Should fail
+
+    def test_size(self):
+        """Test buffer size."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(100)
+        assert buf.size == 100
+
+
+class TestMeta:
+    """Tests for metadata access."""
+
+    def test_meta_defaults(self):
+        """Test metadata of a fresh buffer."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu_array([2, 3], "float32")
+
+        meta = buf.meta
+        assert meta.shape == (2, 3)
+        assert meta.strides == (12, 4)
+        assert meta.dtype == "float32"
+        assert meta.size == 24
+        assert meta.timestamp == 0
+        assert meta.seq == 0
+        assert meta.content_type == ""
+        assert meta.producer == ""
+        assert "shape=(2, 3)" in repr(meta)
+        with pytest.raises(AttributeError):
+            meta.seq = 1
+
+    def test_set_meta(self):
+        """Test writing metadata visible to readers."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(64)
+        buf.set_meta(shape=[4, 4], dtype="int32", content_type="tensor",
+                     producer="camera0", seq=7)
+
+        meta = pool.get(buf.meta_index).meta
+        assert meta.shape == (4, 4)
+        assert meta.dtype == "int32"
+        assert meta.content_type == "tensor"
+        assert meta.producer == "camera0"
+        assert meta.seq == 7
+
+        # Only the given fields change
+        buf.set_meta(dtype="uint8")
+        assert buf.meta.shape == (4, 4)
+        assert buf.meta.producer == "camera0"
+
+    def test_set_meta_errors(self):
+        """Test metadata validation."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(16)
+        with pytest.raises(ValueError):
+            buf.set_meta(shape=[5], dtype="float32")
+        with pytest.raises(ValueError):
+            buf.set_meta(producer="p" * 33)
+        assert buf.meta.producer == ""
+
+        reader = pool.get(buf.meta_index)
+        with pytest.raises(RuntimeError, match="read-only"):
+            reader.set_meta(seq=1)
+
+
+class TestBufferProtocol:
+    """Tests for the buffer protocol."""
+
+    def test_memoryview_write_read(self):
+        """Test zero-copy write through memoryview."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(16)
+        mv = memoryview(buf)
+        assert len(mv) == 16
+        assert mv.format == "B"
+        assert not mv.readonly
+
+        mv[:5] = b"hello"
+        assert bytes(buf)[:5] == b"hello"
+        assert bytes(mv[1:3]) == b"el"
+        mv.release()
+
+    def test_shared_between_guards(self):
+        """Test that views of two guards see the same memory."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(8)
+        with memoryview(buf) as mv:
+            mv[:] = b"abcdefgh"
+
+        reader = pool.get(buf.meta_index)
+        assert bytes(reader) == b"abcdefgh"
+
+    def test_read_only_view(self):
+        """Test that read-only guards export read-only views."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(8)
+        meta_index = buf.meta_index
+        buf.forget()
+
+        reader = pool.get(meta_index)
+        mv = memoryview(reader)
+        assert mv.readonly
+        with pytest.raises(TypeError):
+            mv[0] = 1
+        mv.release()
+
+    def test_forget_while_exported(self):
+        """Test that forget() fails while a view is exported."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(8)
+        mv = memoryview(buf)
+        with pytest.raises(BufferError):
+            buf.forget()
+        assert buf.is_valid
+
+        mv.release()
+        buf.forget()
+        assert not buf.is_valid
+
+    def test_exit_deferred_while_exported(self):
+        """Test that __exit__ keeps the buffer alive until views are released."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        with pool.acquire_cpu(8) as buf:
+            meta_index = buf.meta_index
+            mv = memoryview(buf)
+
+        assert not buf.is_valid
+        assert pool.ref_count(meta_index) == 1
+        mv[0] = 42
+        assert mv[0] == 42
+
+        mv.release()
+        assert pool.ref_count(meta_index) == 0
+
+
+class TestReadWrite:
+    """Tests for write()/read() byte copies."""
+
+    def test_write_read(self):
+        """Test copying bytes in and out at an offset."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(8)
+        assert buf.write(b"abcd") == 4
+        assert buf.write(bytearray(b"xy"), offset=6) == 2
+        assert buf.read(4) == b"abcd"
+        assert buf.read(offset=6) == b"xy"
+        assert buf.read(0, offset=8) == b""
+        assert len(buf.read()) == 8
+
+        # Any bytes-like object, including a view of the buffer itself
+        buf.write(memoryview(buf)[:2], offset=2)
+        assert buf.read(4) == b"abab"
+
+    def test_large_copy(self):
+        """Test copies large enough to release the GIL."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        data = bytes(range(256)) * 4096
+        buf = pool.acquire_cpu(len(data))
+        buf.write(data)
+        assert buf.read() == data
+
+    def test_bounds(self):
+        """Test out-of-bounds access."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        with pytest.raises(ValueError, match="out of bounds"):
+            buf.write(b"12345")
+        with pytest.raises(ValueError):
+            buf.write(b"12", offset=3)
+        with pytest.raises(ValueError):
+            buf.read(5)
+        with pytest.raises(ValueError):
+            buf.read(offset=5)
+        with pytest.raises(ValueError):
+            buf.read(-2)
+
+    def test_read_only(self):
+        """Test writes through a read-only guard."""
+        from xmem import BufferPool, ReadOnlyBufferError
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        buf.write(b"data")
+
+        reader = pool.get(buf.meta_index)
+        with pytest.raises(ReadOnlyBufferError):
+            reader.write(b"x")
+        assert reader.read() == b"data"
+
+
+class TestSharedMemoryInterop:
+    """Tests for multiprocessing.shared_memory interop."""
+
+    def test_shm_name(self):
+        """Test the segment name of a buffer."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        assert buf.shm_name == f"{name}_buf_{buf.meta_index}"
+
+    def test_as_shared_memory(self):
+        """Test attaching a stdlib SharedMemory to a buffer."""
+        from multiprocessing import shared_memory
+        from xmem import BufferPool, ReadOnlyBufferError
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        buf.write(b"data")
+        meta_index = buf.meta_index
+
+        shm = buf.as_shared_memory()
+        assert isinstance(shm, shared_memory.SharedMemory)
+        assert bytes(shm.buf[:4]) == b"data"
+        shm.buf[:2] = b"DA"
+        assert buf.read() == b"DAta"
+
+        # The stdlib object keeps its own reference
+        assert pool.ref_count(meta_index) == 2
+        del buf
+        assert bytes(shm.buf[:4]) == b"DAta"
+        shm.close()
+        del shm
+        assert pool.ref_count(meta_index) == 0
+
+        writer = pool.acquire_cpu(4)
+        with pytest.raises(ReadOnlyBufferError):
+            pool.get(writer.meta_index).as_shared_memory()
+
+
+class TestNumpy:
+    """Tests for numpy interop."""
+
+    def test_numpy_from_metadata(self):
+        """Test that numpy() uses shape and dtype from metadata."""
+        np = pytest.importorskip("numpy")
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu_array([2, 3], "float32")
+        assert buf.size == 24
+
+        arr = buf.numpy()
+        assert arr.shape == (2, 3)
+        assert arr.dtype == np.float32
+        arr[:] = np.arange(6, dtype=np.float32).reshape(2, 3)
+
+        reader = pool.get(buf.meta_index)
+        out = reader.numpy()
+        assert not out.flags.writeable
+        assert out[1, 2] == 5.0
+
+    def test_numpy_dtype_objects(self):
+        """Test that numpy dtypes are accepted."""
+        np = pytest.importorskip("numpy")
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu_array([4], np.int16)
+        assert buf.numpy().dtype == np.int16
+        buf = pool.acquire_cpu_array([4], np.dtype("<u4"))
+        assert buf.numpy().dtype == np.uint32
+
+    def test_numpy_explicit_layout(self):
+        """Test numpy() with explicit shape, dtype and strides."""
+        np = pytest.importorskip("numpy")
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(32)
+        raw = buf.numpy()
+        assert raw.shape == (32,)
+        assert raw.dtype == np.uint8
+        raw[:] = np.arange(32, dtype=np.uint8)
+
+        cols = buf.numpy(shape=(2, 4), dtype="uint8", strides=(16, 2))
+        assert cols.tolist() == [[0, 2, 4, 6], [16, 18, 20, 22]]
+
+        with pytest.raises(ValueError):
+            buf.numpy(strides=(1,))
+
+    def test_numpy_keeps_guard_alive(self):
+        """Test that the array keeps the buffer alive."""
+        np = pytest.importorskip("numpy")
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        with pool.acquire_cpu_array([8], "uint8") as buf:
+            meta_index = buf.meta_index
+            arr = buf.numpy()
+            with pytest.raises(BufferError):
+                buf.forget()
+
+        assert pool.ref_count(meta_index) == 1
+        arr[:] = 7
+        del arr
+        assert pool.ref_count(meta_index) == 0
+
+
+class TestDLPack:
+    """Tests for DLPack export and import."""
+
+    def test_dlpack_device(self):
+        """Test DLPack device of a CPU buffer."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(8)
+        assert buf.__dlpack_device__() == (1, 0)
+
+    def test_capsule_holds_reference(self):
+        """Test that an exported capsule owns a buffer reference."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(8)
+        meta_index = buf.meta_index
+        capsule = buf.__dlpack__()
+        assert pool.ref_count(meta_index) == 2
+
+        # Unconsumed capsules release their reference when collected
+        del capsule
+        assert pool.ref_count(meta_index) == 1
+
+    def test_roundtrip_copy(self):
+        """Test importing another buffer through DLPack."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        src = pool.acquire_cpu_array([2, 2], "int16")
+        memoryview(src).cast("h")[:] = memoryview(bytes(range(8))).cast("h")
+
+        copy = pool.from_dlpack(src)
+        assert copy.meta_index != src.meta_index
+        assert copy.size == 8
+        assert bytes(copy) == bytes(range(8))
+        # The temporary export was consumed and released
+        assert pool.ref_count(src.meta_index) == 1
+
+    def test_versioned_export(self):
+        """Test DLPack 1.0 capsules and read-only exports."""
+        import ctypes
+        from xmem import BufferPool
+
+        get_name = ctypes.pythonapi.PyCapsule_GetName
+        get_name.argtypes = [ctypes.py_object]
+        get_name.restype = ctypes.c_char_p
+        get_pointer = ctypes.pythonapi.PyCapsule_GetPointer
+        get_pointer.argtypes = [ctypes.py_object, ctypes.c_char_p]
+        get_pointer.restype = ctypes.c_void_p
+
+        def flags(capsule):
+            # DLManagedTensorVersioned: version, manager_ctx, deleter, flags
+            managed = get_pointer(capsule, b"dltensor_versioned")
+            return ctypes.c_uint64.from_address(managed + 24).value
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(8)
+        meta_index = buf.meta_index
+        assert get_name(buf.__dlpack__()) == b"dltensor"
+        capsule = buf.__dlpack__(max_version=(1, 0))
+        assert get_name(capsule) == b"dltensor_versioned"
+        assert flags(capsule) == 0
+        del capsule
+        memoryview(buf)[:] = bytes(range(8))
+        buf.forget()
+
+        # Read-only data cannot be expressed in an unversioned capsule
+        reader = pool.get(meta_index)
+        with pytest.raises(BufferError, match="read-only"):
+            reader.__dlpack__()
+        capsule = reader.__dlpack__(max_version=(1, 2))
+        assert flags(capsule) & 1
+        del capsule
+
+        # Importing requests a versioned capsule, so read-only sources work
+        copy = pool.from_dlpack(reader)
+        assert bytes(copy) == bytes(range(8))
+        assert pool.ref_count(meta_index) == 2
+
+    def test_numpy_interop(self):
+        """Test zero-copy export to numpy and strided import."""
+        np = pytest.importorskip("numpy")
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu_array([2, 3], "float32")
+        buf.numpy()[:] = np.arange(6, dtype=np.float32).reshape(2, 3)
+
+        arr = np.from_dlpack(buf)
+        assert arr.shape == (2, 3)
+        assert arr[1, 2] == 5.0
+
+        src = np.arange(12, dtype=np.int64).reshape(3, 4)[:, ::2]
+        copy = pool.from_dlpack(src)
+        assert copy.numpy().tolist() == src.tolist()
+
+
+class TestPublish:
+    """Tests for publish/receive."""
+
+    def test_publish_receive(self):
+        """Test handing a buffer to a receiver."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        assert pool.try_receive() is None
+
+        buf = pool.acquire_cpu(4)
+        memoryview(buf)[:] = b"ping"
+        meta_index = buf.meta_index
+        pool.publish(buf)
+        assert not buf.is_valid
+
+        rx = pool.receive(timeout=1.0)
+        assert rx.meta_index == meta_index
+        assert bytes(rx) == b"ping"
+        assert pool.ref_count(meta_index) == 1
+        assert pool.try_receive() is None
+
+    def test_publish_while_exported(self):
+        """Test that publishing a buffer with live views fails."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        buf = pool.acquire_cpu(4)
+        mv = memoryview(buf)
+        with pytest.raises(BufferError):
+            pool.publish(buf)
+        mv.release()
+        pool.publish(buf)
+
+
+class TestPickle:
+    """Tests for passing buffers between processes via pickle."""
+
+    def test_pickle_roundtrip(self):
+        """Test that pickling moves the guard's reference into the pickle."""
+        import pickle
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        assert pickle.loads(pickle.dumps(pool)).name == name
+
+        buf = pool.acquire_cpu(4)
+        memoryview(buf)[:] = b"data"
+        meta_index = buf.meta_index
+        data = pickle.dumps(buf)
+        assert not buf.is_valid
+        assert pool.ref_count(meta_index) == 1
+
+        copy = pickle.loads(data)
+        assert copy.meta_index == meta_index
+        assert bytes(copy) == b"data"
+        assert pool.ref_count(meta_index) == 1
+
+        del copy
+        assert pool.ref_count(meta_index) == 0
+
+    def test_pickle_exported_view(self):
+        """Test that a guard with exported views cannot be pickled."""
+        import pickle
+        from xmem import BufferPool
+
+        pool = BufferPool(unique_name())
+        buf = pool.acquire_cpu(4)
+        view = memoryview(buf)
+        with pytest.raises(BufferError):
+            pickle.dumps(buf)
+        view.release()
+        assert buf.is_valid
+
+    def test_pickle_keeps_write_lock(self):
+        """Test that an unpickled writer still excludes other writers."""
+        import pickle
+        from xmem import BufferPool
+
+        import threading
+
+        pool = BufferPool(unique_name())
+        buf = pool.acquire_cpu(4)
+        writer = pickle.loads(pickle.dumps(pool.get_mut(buf.meta_index)))
+        other = threading.Thread(target=pool.get_mut, args=(buf.meta_index,))
+        other.start()
+        other.join(timeout=0.2)
+        assert other.is_alive()
+        del writer
+        other.join(timeout=10)
+        assert not other.is_alive()
+
+    def test_pickle_stale(self):
+        """Test that unpickling a recycled buffer fails."""
+        import pickle
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        data = pickle.dumps(pool.acquire_cpu(4))
+        pickle.loads(data)  # adopted and released at once
+
+        pool.acquire_cpu(4)
+        with pytest.raises(RuntimeError, match="stale"):
+            pickle.loads(data)
+
+    def test_pipe_to_child(self):
+        """Test sending a buffer to a child process."""
+        import multiprocessing
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        memoryview(buf)[:] = b"sent"
+
+        ctx = multiprocessing.get_context("fork")
+        parent, child = ctx.Pipe()
+        proc = ctx.Process(target=_echo_buffer, args=(child,))
+        proc.start()
+        parent.send(pool.get(buf.meta_index))
+        assert parent.recv() == b"sent"
+        proc.join(timeout=10)
+        assert proc.exitcode == 0
+        assert pool.ref_count(buf.meta_index) == 1
+
+    def test_process_pool(self):
+        """Test sending a buffer to a ProcessPoolExecutor worker."""
+        import multiprocessing
+        from concurrent.futures import ProcessPoolExecutor
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        memoryview(buf)[:] = b"work"
+
+        ctx = multiprocessing.get_context("fork")
+        with ProcessPoolExecutor(max_workers=1, mp_context=ctx) as executor:
+            sent = pool.get(buf.meta_index)
+            assert executor.submit(_read_buffer, sent).result(timeout=10) == b"work"
+        assert pool.ref_count(buf.meta_index) == 1
+
+    def test_queue(self):
+        """Test passing a buffer through multiprocessing.Queue."""
+        import multiprocessing
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        memoryview(buf)[:] = b"sent"
+        meta_index = buf.meta_index
+
+        queue = multiprocessing.get_context("fork").Queue()
+        queue.put(buf)  # pickled by the feeder thread
+        received = queue.get(timeout=10)
+        del buf
+        assert bytes(received) == b"sent"
+        del received
+        assert pool.ref_count(meta_index) == 0
+
+
+class TestThreads:
+    """Tests for sharing objects between Python threads."""
+
+    def test_pool_in_worker_threads(self):
+        """Test using a pool created in the main thread from a thread pool."""
+        from concurrent.futures import ThreadPoolExecutor
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, capacity=4)
+
+        def work(i):
+            with pool.acquire_cpu(8, timeout=5.0) as buf:
+                memoryview(buf)[:] = bytes([i]) * 8
+                return bytes(buf) == bytes([i]) * 8
+
+        with ThreadPoolExecutor(max_workers=4) as executor:
+            assert all(executor.map(work, range(64)))
+
+    def test_guard_across_threads(self):
+        """Test reading and releasing a guard from another thread."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        buf = pool.acquire_cpu(4)
+        memoryview(buf)[:] = b"main"
+        meta_index = buf.meta_index
+        result = []
+
+        def work():
+            result.append(bytes(buf))
+            buf.__exit__(None, None, None)
+
+        thread = threading.Thread(target=work)
+        thread.start()
+        thread.join()
+        assert result == [b"main"]
+        assert not buf.is_valid
+        assert pool.ref_count(meta_index) == 0
+
+
+class TestAsync:
+    """Tests for asyncio support."""
+
+    def test_acquire_async(self):
+        """Test that acquire_cpu_async waits for a freed slot."""
+        from xmem import AcquireTimeoutError, BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, capacity=1)
+
+        async def main():
+            buf = await pool.acquire_cpu_async(64)
+            meta_index = buf.meta_index
+
+            with pytest.raises(AcquireTimeoutError):
+                await pool.acquire_cpu_async(64, timeout=0.02)
+
+            loop = asyncio.get_running_loop()
+            loop.call_later(0.02, buf.__exit__, None, None, None)
+            buf2 = await pool.acquire_cpu_async(64, timeout=5)
+            assert buf2.meta_index == meta_index
+
+        asyncio.run(main())
+
+    def test_cancelled_wait(self):
+        """Test that a cancelled wait does not leak its registration."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name, capacity=1)
+
+        async def main():
+            buf = await pool.acquire_cpu_async(64)
+            with pytest.raises(asyncio.TimeoutError):
+                await asyncio.wait_for(pool.acquire_cpu_async(64), 0.02)
+            await asyncio.sleep(0.05)
+            buf.forget()
+
+        asyncio.run(main())
+
+    def test_incoming(self):
+        """Test async iteration over published buffers."""
+        from xmem import AcquireTimeoutError, BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+
+        async def produce():
+            for i in range(3):
+                await asyncio.sleep(0.01)
+                buf = pool.acquire_cpu(1)
+                memoryview(buf)[0] = i
+                pool.publish(buf)
+
+        async def main():
+            producer = asyncio.ensure_future(produce())
+            received = []
+            async for buf in pool.incoming():
+                received.append(bytes(buf)[0])
+                if len(received) == 3:
+                    break
+            await producer
+            assert received == [0, 1, 2]
+
+            with pytest.raises(AcquireTimeoutError):
+                await pool.receive_async(timeout=0.02)
+
+        asyncio.run(main())
+
+    def test_receive_async_across_handles(self):
+        """Test that a publish through a handle sharing no wakers reaches an async receiver."""
+        from xmem import BufferPool
+
+        name = unique_name()
+        pool = BufferPool(name)
+        other = BufferPool.open(name)
+
+        def publish():
+            time.sleep(0.05)
+            buf = other.acquire_cpu(1)
+            memoryview(buf)[0] = 7
+            other.publish(buf)
+
+        async def main():
+            thread = threading.Thread(target=publish)
+            thread.start()
+            buf = await pool.receive_async(timeout=5)
+            thread.join()
+            return bytes(buf)
+
+        assert asyncio.run(main()) == b"\x07"
@@ -1,22 +1,98 @@
 """Type stubs for xmem Python bindings."""
 
-from typing import List
+import multiprocessing.shared_memory
+from typing import Any, AsyncIterator, Awaitable, List, Optional, Sequence, Tuple
+
+class AcquireTimeoutError(TimeoutError):
+    """Timed out waiting for a buffer or buffer lock."""
+
+
+class ReadOnlyBufferError(RuntimeError):
+    """Write attempted through a read-only buffer guard."""
+
 
 class BufferPool:
     """Cross-process shared memory buffer pool."""
 
-    def __init__(self, name: str, capacity: int = 1024) -> None:
-        """Create a new buffer pool."""
+    def __init__(
+        self,
+        name: str,
+        capacity: int = 1024,
+        *,
+        namespace: Optional[str] = None,
+        backing_dir: Optional[str] = None,
+        huge_pages: str = "off",
+        prefault: bool = False,
+        mlock: bool = False,
+        numa_node: Optional[int] = None,
+        cache_segments: bool = False,
+        trim_idle_after: Optional[float] = None,
+        max_cached_bytes: Optional[int] = None,
+    ) -> None:
+        """Create a new buffer pool.
+
+        ``namespace`` is prepended to the name. With ``backing_dir`` the
+        segments are files in that directory (e.g. a tmpfs volume shared
+        between containers) instead of POSIX shared memory. Raises ValueError
+        for invalid names.
+
+        ``huge_pages`` requests "transparent" huge pages or "hugetlb" (needs a
+        hugetlbfs ``backing_dir``); unavailable modes fall back, see the
+        ``huge_pages`` property. ``prefault`` populates pages at allocation.
+
+        ``mlock`` locks buffer mappings so they are never swapped out (limited
+        by ``ulimit -l``). ``numa_node`` binds new buffers to a NUMA node; on
+        single-node machines this is a no-op, see the ``numa_node`` property.
+
+        ``cache_segments`` keeps the segment of a released CPU buffer so the
+        next buffer of the same size on that slot reuses it (contents are not
+        cleared). ``max_cached_bytes`` caps the cached bytes, deleting the
+        oldest segments after each release; ``trim_idle_after`` (seconds) is
+        the idle time after which ``trim()`` releases a segment's pages.
+        """
         ...
 
     @staticmethod
-    def open(name: str) -> "BufferPool":
-        """Open an existing buffer pool."""
+    def open(
+        name: str,
+        namespace: Optional[str] = None,
+        backing_dir: Optional[str] = None,
+        *,
+        mlock: bool = False,
+        cache_segments: bool = False,
+        trim_idle_after: Optional[float] = None,
+        max_cached_bytes: Optional[int] = None,
+    ) -> "BufferPool":
+        """Open an existing buffer pool created with the same namespace and backing_dir.
+
+        ``mlock`` locks the buffers this process maps. The cache settings
+        apply to buffers this process releases and should match the creator's.
+        """
         ...
 
     @property
     def name(self) -> str:
-        """Get pool name."""
+        """Get pool name, including the namespace."""
+        ...
+
+    @property
+    def huge_pages(self) -> str:
+        """Huge page mode in effect: "off", "transparent" or "hugetlb"."""
+        ...
+
+    @property
+    def numa_node(self) -> Optional[int]:
+        """NUMA node new buffers are bound to, None if unbound or single-node."""
+        ...
+
+    @property
+    def mlock(self) -> bool:
+        """Whether buffer mappings are locked in memory."""
+        ...
+
+    @property
+    def backing_dir(self) -> Optional[str]:
+        """Directory holding the segment files, or None for shared memory."""
         ...
 
     @property
@@ -24,14 +100,51 @@ class BufferPool:
         """Get pool capacity."""
         ...
 
-    def acquire_cpu(self, size: int) -> "BufferGuard":
-        """Acquire a CPU buffer."""
+    def __reduce__(self) -> Tuple[Any, Tuple[str, None, Optional[str]]]:
+        """Pickle support: reopens the pool by name."""
+        ...
+
+    def acquire_cpu(
+        self, size: int, timeout: Optional[float] = None, block: bool = False
+    ) -> "BufferGuard":
+        """Acquire a CPU buffer.
+
+        Fails immediately when the pool is full unless ``block`` is true or a
+        ``timeout`` (seconds) is given. Raises AcquireTimeoutError when the
+        timeout expires. The GIL is released while waiting.
+        """
+        ...
+
+    def acquire_cpu_async(
+        self, size: int, timeout: Optional[float] = None
+    ) -> Awaitable["BufferGuard"]:
+        """Acquire a CPU buffer without blocking the asyncio event loop.
+
+        Waits for a free slot when the pool is full; raises AcquireTimeoutError
+        after ``timeout`` seconds. Frees in any process wake the waiter at
+        once on Linux; elsewhere frees in other processes are noticed by
+        polling every 10 ms.
+        """
         ...
 
     def acquire_cuda(self, size: int, device_id: int) -> "BufferGuard":
         """Acquire a CUDA buffer (requires cuda feature)."""
         ...
 
+    def acquire_cpu_array(self, shape: Sequence[int], dtype: Any) -> "BufferGuard":
+        """Acquire a CPU buffer for a tensor and record shape/dtype metadata.
+
+        ``dtype`` may be a name (``"float32"``), a numpy type or a ``numpy.dtype``.
+        """
+        ...
+
+    def from_dlpack(self, obj: Any) -> "BufferGuard":
+        """Copy a CPU tensor supporting DLPack into a new buffer.
+
+        The new buffer records the tensor's shape and dtype.
+        """
+        ...
+
     def preallocate_cpu(self, size: int, count: int) -> List[int]:
         """Preallocate CPU buffers."""
         ...
@@ -41,11 +154,42 @@ class BufferPool:
         ...
 
     def get(self, meta_index: int) -> "BufferGuard":
-        """Get a buffer (read-only)."""
+        """Get a buffer (read-only), releasing the GIL while waiting for the lock."""
         ...
 
     def get_mut(self, meta_index: int) -> "BufferGuard":
-        """Get a buffer (read-write)."""
+        """Get a buffer (read-write), releasing the GIL while waiting for the lock."""
+        ...
+
+    def publish(self, buf: "BufferGuard") -> None:
+        """Publish a buffer to one receiver; ``buf`` becomes invalid.
+
+        Raises BufferError while views are exported.
+        """
+        ...
+
+    def try_receive(self) -> Optional["BufferGuard"]:
+        """Receive the oldest published buffer, or None."""
+        ...
+
+    def receive(self, timeout: Optional[float] = None) -> "BufferGuard":
+        """Receive a published buffer, releasing the GIL while waiting.
+
+        Publishes from any process wake the receiver immediately on Linux.
+        """
+        ...
+
+    def receive_async(self, timeout: Optional[float] = None) -> Awaitable["BufferGuard"]:
+        """Receive a published buffer without blocking the event loop.
+
+        Publishes in any process wake the receiver at once on Linux;
+        elsewhere publishes in other processes are noticed by polling every
+        10 ms.
+        """
+        ...
+
+    def incoming(self) -> AsyncIterator["BufferGuard"]:
+        """Iterate over published buffers: ``async for buf in pool.incoming()``."""
         ...
 
     def set_ref_count(self, meta_index: int, count: int) -> None:
@@ -64,9 +208,73 @@ class BufferPool:
         """Get current reference count."""
         ...
 
+    def trim(self) -> Tuple[List[int], List[int], int]:
+        """Return memory held by cached segments to the system.
+
+        Releases the pages of segments idle for at least ``trim_idle_after``
+        and deletes the oldest segments beyond ``max_cached_bytes``. Without
+        ``trim_idle_after`` the pages of every cached segment are released.
+        Pages are only released on Linux. Returns
+        ``(released, unlinked, cached_bytes)``. Call it periodically.
+        """
+        ...
+
+
+class BufferMeta:
+    """Read-only snapshot of a buffer's metadata."""
+
+    @property
+    def shape(self) -> Tuple[int, ...]:
+        """Tensor shape, empty if none was recorded."""
+        ...
+
+    @property
+    def strides(self) -> Tuple[int, ...]:
+        """Tensor strides in bytes."""
+        ...
+
+    @property
+    def dtype(self) -> Optional[str]:
+        """Element dtype name, e.g. ``"float32"``."""
+        ...
+
+    @property
+    def size(self) -> int:
+        """Buffer size in bytes."""
+        ...
+
+    @property
+    def timestamp(self) -> int:
+        """Publish time in milliseconds since the Unix epoch, 0 if never published."""
+        ...
+
+    @property
+    def seq(self) -> int:
+        """Producer-defined sequence number (seqlock counter in seqlock mode)."""
+        ...
+
+    @property
+    def content_type(self) -> str:
+        """Content type label, empty if unset."""
+        ...
+
+    @property
+    def producer(self) -> str:
+        """Producer label, empty if unset."""
+        ...
+
+    @property
+    def numa_node(self) -> Optional[int]:
+        """NUMA node the data is bound to, None if unbound."""
+        ...
+
 
 class BufferGuard:
-    """RAII guard for buffer access."""
+    """RAII guard for buffer access.
+
+    Implements the buffer protocol: ``memoryview(buf)`` exposes the CPU
+    buffer as unsigned bytes, read-only for guards from ``get()``.
+    """
 
     @property
     def meta_index(self) -> int:
@@ -103,8 +311,123 @@ class BufferGuard:
         """Get buffer size in bytes."""
         ...
 
+    def write(self, data: Any, offset: int = 0) -> int:
+        """Copy a bytes-like object into the buffer at ``offset``.
+
+        Returns the number of bytes written. Raises ValueError when the data
+        does not fit and ReadOnlyBufferError on read-only guards. The GIL is
+        released for large copies.
+        """
+        ...
+
+    def read(self, n: int = -1, offset: int = 0) -> bytes:
+        """Read ``n`` bytes from ``offset`` (``-1`` reads to the end).
+
+        Raises ValueError when the range is out of bounds.
+        """
+        ...
+
+    @property
+    def shm_name(self) -> str:
+        """Name of the shared memory segment holding the data, e.g. ``/pool_buf_3``.
+
+        Views report their parent's segment.
+        """
+        ...
+
+    def as_shared_memory(self) -> "multiprocessing.shared_memory.SharedMemory":
+        """Attach a stdlib ``SharedMemory`` to the buffer's segment.
+
+        The returned object holds its own buffer reference, never unlinks the
+        segment and is not tracked by the resource tracker. Raises
+        ReadOnlyBufferError on read-only guards and ValueError for views.
+        """
+        ...
+
+    @property
+    def meta(self) -> "BufferMeta":
+        """Get a read-only snapshot of the buffer metadata."""
+        ...
+
+    def set_meta(
+        self,
+        *,
+        shape: Optional[Sequence[int]] = None,
+        dtype: Optional[Any] = None,
+        content_type: Optional[str] = None,
+        producer: Optional[str] = None,
+        seq: Optional[int] = None,
+    ) -> None:
+        """Update buffer metadata (writable guards only).
+
+        Only the given fields change. Strides are recomputed for a C-contiguous
+        layout; the tensor must fit in the buffer. Labels are limited to 32
+        bytes of UTF-8. Raises ValueError on invalid values and
+        ReadOnlyBufferError on read-only guards.
+        """
+        ...
+
+    def numpy(
+        self,
+        shape: Optional[Sequence[int]] = None,
+        dtype: Any = None,
+        strides: Optional[Sequence[int]] = None,
+    ) -> Any:
+        """Get a zero-copy numpy array over the buffer.
+
+        Uses shape/strides/dtype from the buffer metadata unless given
+        explicitly; without metadata returns a 1-D uint8 array. The array
+        keeps this guard alive and is read-only for guards from ``get()``.
+        """
+        ...
+
+    def __dlpack__(
+        self,
+        *,
+        stream: Any = None,
+        max_version: Optional[Tuple[int, int]] = None,
+        dl_device: Optional[Tuple[int, int]] = None,
+        copy: Optional[bool] = None,
+    ) -> Any:
+        """Export the buffer as a DLPack capsule (zero-copy).
+
+        The exported tensor holds its own buffer reference and shares the
+        guard's read/write lock until the consumer releases it.
+
+        With ``max_version >= (1, 0)`` a ``"dltensor_versioned"`` capsule is
+        returned and read-only guards set the read-only flag. Older consumers
+        get a ``"dltensor"`` capsule, which cannot express read-only data, so
+        read-only guards raise ``BufferError``.
+        """
+        ...
+
+    def __dlpack_device__(self) -> Tuple[int, int]:
+        """Get the DLPack ``(device_type, device_id)`` of the buffer."""
+        ...
+
+    def __reduce__(self) -> Tuple[Any, Tuple[str, int, int, bool]]:
+        """Pickle support: moves the guard's reference into the pickle.
+
+        Like ``publish``, the guard is invalid afterwards and its lock is
+        released; unpickling takes the lock again, waiting for conflicting
+        holders. Raises BufferError while views are exported. Each pickle
+        must be unpickled exactly once.
+        """
+        ...
+
     def forget(self) -> None:
-        """Forget this guard without releasing the buffer."""
+        """Forget this guard without releasing the buffer.
+
+        Raises BufferError while views are exported.
+        """
+        ...
+
+    def __buffer__(self, flags: int) -> memoryview:
+        """Export the CPU buffer (buffer protocol)."""
+        ...
+
+    def __release_buffer__(self, view: memoryview) -> None:
+        """Release an exported view."""
         ...
 
     def __enter__(self) -> "BufferGuard":